@@ -27,7 +27,8 @@ fn reference(c: &mut Criterion) {
     });
     g.bench_function("apply_env_no_env", |b| {
         let config = &black_box(cargo_config2::de::Config::default());
-        let cx = &mut black_box(test_options().into_context(std::env::current_dir().unwrap()));
+        let cx =
+            &mut black_box(test_options().into_resolve_context(std::env::current_dir().unwrap()));
         b.iter(|| {
             let mut config = black_box(config.clone());
             config.apply_env(cx).unwrap();
@@ -50,6 +51,7 @@ fn reference(c: &mut Criterion) {
             ("CARGO_BUILD_TARGET", "triple"),
             ("CARGO_TARGET_DIR", "target"),
             ("CARGO_BUILD_TARGET_DIR", "target"),
+            ("CARGO_BUILD_BUILD_DIR", "build"),
             ("CARGO_ENCODED_RUSTFLAGS", "1"),
             ("RUSTFLAGS", "1"),
             ("CARGO_BUILD_RUSTFLAGS", "1"),
@@ -59,6 +61,7 @@ fn reference(c: &mut Criterion) {
             ("CARGO_INCREMENTAL", "false"),
             ("CARGO_BUILD_INCREMENTAL", "1"),
             ("CARGO_BUILD_DEP_INFO_BASEDIR", "1"),
+            ("CARGO_BUILD_WARNINGS", "deny"),
             ("BROWSER", "1"),
             ("CARGO_FUTURE_INCOMPAT_REPORT_FREQUENCY", "always"),
             ("CARGO_CARGO_NEW_VCS", "git"),
@@ -89,7 +92,7 @@ fn reference(c: &mut Criterion) {
                 .env(env_list)
                 .cargo_home(None)
                 .rustc(PathAndArgs::new("rustc"))
-                .into_context(std::env::current_dir().unwrap()),
+                .into_resolve_context(std::env::current_dir().unwrap()),
         );
         b.iter(|| {
             let mut config = black_box(config.clone());
@@ -99,5 +102,17 @@ fn reference(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, reference);
+fn empty(c: &mut Criterion) {
+    let mut g = c.benchmark_group("empty");
+    // Usually, there are no config files in the ancestors of the temporary directory.
+    let dir = &std::env::temp_dir();
+    g.bench_function("load_config_easy", |b| {
+        b.iter(|| {
+            let config = cargo_config2::Config::load_with_options(dir, test_options()).unwrap();
+            black_box(config)
+        });
+    });
+}
+
+criterion_group!(benches, reference, empty);
 criterion_main!(benches);
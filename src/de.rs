@@ -21,13 +21,16 @@ use serde::{
 };
 use serde_derive::{Deserialize, Serialize};
 
-pub use crate::value::{Definition, Value};
 use crate::{
     easy,
     error::{Context as _, Error, Result},
     resolve::{ResolveContext, TargetTripleRef},
     walk,
 };
+pub use crate::{
+    validate::Diagnostic,
+    value::{Definition, Value},
+};
 
 /// Cargo configuration that environment variables, config overrides, and
 /// target-specific configurations have not been resolved.
@@ -46,13 +49,13 @@ pub struct Config {
     ///
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#build)
     #[serde(default)]
-    #[serde(skip_serializing_if = "BuildConfig::is_none")]
+    #[serde(skip_serializing_if = "BuildConfig::is_empty")]
     pub build: BuildConfig,
     /// The `[doc]` table.
     ///
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#doc)
     #[serde(default)]
-    #[serde(skip_serializing_if = "DocConfig::is_none")]
+    #[serde(skip_serializing_if = "DocConfig::is_empty")]
     pub doc: DocConfig,
     /// The `[env]` table.
     ///
@@ -64,26 +67,26 @@ pub struct Config {
     ///
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#future-incompat-report)
     #[serde(default)]
-    #[serde(skip_serializing_if = "FutureIncompatReportConfig::is_none")]
+    #[serde(skip_serializing_if = "FutureIncompatReportConfig::is_empty")]
     pub future_incompat_report: FutureIncompatReportConfig,
     /// The `[cargo-new]` table.
     ///
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#cargo-new)
     #[serde(default)]
-    #[serde(skip_serializing_if = "CargoNewConfig::is_none")]
+    #[serde(skip_serializing_if = "CargoNewConfig::is_empty")]
     pub cargo_new: CargoNewConfig,
     /// The `[http]` table.
     ///
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#http)
     #[serde(default)]
-    #[serde(skip_serializing_if = "HttpConfig::is_none")]
+    #[serde(skip_serializing_if = "HttpConfig::is_empty")]
     pub http: HttpConfig,
     // TODO: install
     /// The `[net]` table.
     ///
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#net)
     #[serde(default)]
-    #[serde(skip_serializing_if = "NetConfig::is_none")]
+    #[serde(skip_serializing_if = "NetConfig::is_empty")]
     pub net: NetConfig,
     // TODO: patch
     // TODO: profile
@@ -97,7 +100,7 @@ pub struct Config {
     ///
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#registry)
     #[serde(default)]
-    #[serde(skip_serializing_if = "RegistryConfig::is_none")]
+    #[serde(skip_serializing_if = "RegistryConfig::is_empty")]
     pub registry: RegistryConfig,
     // TODO: source
     /// The `[target]` table.
@@ -110,8 +113,15 @@ pub struct Config {
     ///
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#term)
     #[serde(default)]
-    #[serde(skip_serializing_if = "TermConfig::is_none")]
+    #[serde(skip_serializing_if = "TermConfig::is_empty")]
     pub term: TermConfig,
+
+    /// The raw tables merged from config files and `--config` arguments,
+    /// including the tables and keys not modeled by this type.
+    /// See [`get_table`](Self::get_table).
+    #[cfg(feature = "toml_edit")]
+    #[serde(skip)]
+    pub(crate) raw: toml_edit::Table,
 }
 
 impl Config {
@@ -123,7 +133,7 @@ impl Config {
     /// Read config files hierarchically from the given directory and merges them.
     pub fn load_with_cwd<P: AsRef<Path>>(cwd: P) -> Result<Self> {
         let cwd = cwd.as_ref();
-        Self::_load_with_options(cwd, walk::cargo_home_with_cwd(cwd).as_deref())
+        Self::_load_with_options(cwd, walk::cargo_home_with_cwd(cwd).as_deref(), true)
     }
 
     /// Read config files hierarchically from the given directory and merges them.
@@ -131,17 +141,39 @@ impl Config {
         cwd: P,
         cargo_home: Q,
     ) -> Result<Self> {
-        Self::_load_with_options(cwd.as_ref(), cargo_home.into().as_deref())
+        Self::_load_with_options(cwd.as_ref(), cargo_home.into().as_deref(), true)
     }
     pub(crate) fn _load_with_options(
         current_dir: &Path,
         cargo_home: Option<&Path>,
+        include_cargo_home: bool,
     ) -> Result<Config> {
+        Self::_load_files(
+            crate::walk::WalkInner::with_cargo_home(current_dir, cargo_home)
+                .exclude_cargo_home(!include_cargo_home),
+        )
+    }
+
+    /// Reads the given cargo config files and merges them.
+    ///
+    /// Unlike [`load_with_cwd`](Self::load_with_cwd), this does not walk the
+    /// directory hierarchy or read the config in `CARGO_HOME`; it loads exactly
+    /// the given files. The files are given in order of precedence: values in
+    /// earlier files take precedence over values in later files, and arrays are
+    /// joined with values from earlier files placed later, in the same way as
+    /// cargo merges config files in the directory hierarchy.
+    ///
+    /// Returns the default config if no files are given.
+    pub fn load_files<I: IntoIterator<Item = P>, P: AsRef<Path>>(paths: I) -> Result<Self> {
+        Self::_load_files(paths)
+    }
+    fn _load_files<I: IntoIterator<Item = P>, P: AsRef<Path>>(paths: I) -> Result<Self> {
         let mut base = None;
-        for path in crate::walk::WalkInner::with_cargo_home(current_dir, cargo_home) {
-            let config = Self::_load_file(&path)?;
+        for path in paths {
+            let path = path.as_ref();
+            let config = Self::_load_file(path)?;
             match &mut base {
-                None => base = Some((path, config)),
+                None => base = Some((path.to_owned(), config)),
                 Some((base_path, base)) => base.merge(config, false).with_context(|| {
                     format!(
                         "failed to merge config from `{}` into `{}`",
@@ -158,32 +190,332 @@ impl Config {
     ///
     /// **Note:** Note: This just reads a file at the given path and does not
     /// respect the hierarchical structure of the cargo config.
+    ///
+    /// Like cargo, a file that defines the same key or table more than once
+    /// (e.g., two `[target.<triple>]` sections for the same triple) is
+    /// rejected, and the returned error mentions the path of the file.
     pub fn load_file<P: AsRef<Path>>(path: P) -> Result<Self> {
         Self::_load_file(path.as_ref())
     }
     fn _load_file(path: &Path) -> Result<Self> {
         let buf = fs::read_to_string(path)
             .with_context(|| format!("failed to read `{}`", path.display()))?;
-        let mut config: Config = toml_edit::de::from_str(&buf).with_context(|| {
-            format!("failed to parse `{}` as cargo configuration", path.display())
-        })?;
-        config.set_path(path);
+        #[cfg(not(feature = "toml_edit"))]
+        let mut config: Self =
+            toml_edit::de::from_str(&buf).map_err(|e| Error::parse_config(path, e))?;
+        #[cfg(feature = "toml_edit")]
+        let mut config = {
+            let doc = toml_edit::ImDocument::parse(&*buf)
+                .map_err(|e| Error::parse_config(path, e.into()))?;
+            let raw = doc.clone().into_mut().into_table();
+            let mut config = Self::deserialize(toml_edit::de::Deserializer::from(doc))
+                .map_err(|e| Error::parse_config(path, e))?;
+            config.raw = raw;
+            config
+        };
+        config.set_definition(&Definition::Path(path.to_owned()));
         Ok(config)
     }
 
+    /// Parses the given JSON string as cargo config.
+    ///
+    /// This is intended to read the output of `cargo config get --format=json`.
+    ///
+    /// **Note:** Since JSON does not contain information about where each value
+    /// was defined, the [`Definition`] of the values will be `None`.
+    #[cfg(feature = "serde_json")]
+    pub fn from_json_str(s: &str) -> Result<Self> {
+        serde_json::from_str(s).context("failed to parse JSON as cargo configuration")
+    }
+
     /// Merges the given config into this config.
     ///
     /// If `force` is `false`, this matches the way cargo [merges configs in the
-    /// parent directories](https://doc.rust-lang.org/nightly/cargo/reference/config.html#hierarchical-structure).
+    /// parent directories](https://doc.rust-lang.org/nightly/cargo/reference/config.html#hierarchical-structure):
+    /// `self` is treated as the config with higher precedence (e.g., the config
+    /// in the deeper directory), non-container values in `self` are kept, and
+    /// arrays are joined together with the values of `self` placed later.
     ///
     /// If `force` is `true`, this matches the way cargo's `--config` CLI option
-    /// overrides config.
-    pub(crate) fn merge(&mut self, low: Self, force: bool) -> Result<()> {
-        crate::merge::Merge::merge(self, low, force)
+    /// overrides config: non-container values are overridden by the values of `low`.
+    ///
+    /// Returns an error if the same key has different kinds of values (e.g.,
+    /// a string and an array) in the two configs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use cargo_config2::{de::Config, Walk};
+    ///
+    /// // Reimplementation of `Config::load_with_cwd`.
+    /// let mut config = Config::default();
+    /// for path in Walk::new(&std::env::current_dir()?) {
+    ///     config.merge(Config::load_file(&path)?, false)?;
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn merge(&mut self, low: Self, force: bool) -> Result<()> {
+        #[cfg(feature = "toml_edit")]
+        let (low, low_raw) = {
+            let mut low = low;
+            let low_raw = core::mem::take(&mut low.raw);
+            (low, low_raw)
+        };
+        crate::merge::Merge::merge(self, low, force)?;
+        #[cfg(feature = "toml_edit")]
+        crate::merge::Merge::merge(&mut self.raw, low_raw, force)?;
+        Ok(())
+    }
+
+    /// Applies the given cargo-style `--config` arguments to this config.
+    ///
+    /// Each argument is either a path to a config file, or a TOML dotted key
+    /// expression (e.g., `build.jobs=2`). Like cargo, an argument is treated
+    /// as a path if a file or directory exists at that path (relative to the
+    /// current directory of the process). Later arguments take precedence over
+    /// earlier ones, and all of them take precedence over this config: like
+    /// cargo, non-container values are overridden, and arrays are joined
+    /// together with the values of the arguments placed later.
+    ///
+    /// The [`Definition`] of the values from the arguments is [`Definition::Cli`].
+    /// Like cargo, these values also take precedence over the `CARGO_*`
+    /// environment variables corresponding to config keys: [`apply_env`](Self::apply_env)
+    /// (and [`Config::resolve`](crate::Config::resolve), which calls it) does
+    /// not override them. Environment variables that are not config keys
+    /// (e.g., `RUSTC`, `RUSTFLAGS`, and `CARGO_TARGET_DIR`) still take
+    /// precedence over them.
+    ///
+    /// This is a method of the unresolved config ([`de::Config`](Self)), not
+    /// [`Config`](crate::Config), because the resolved config no longer tracks
+    /// where values are defined. To use `--config` arguments with the resolved
+    /// config, apply them to the unresolved config and pass it to
+    /// [`Config::resolve`](crate::Config::resolve), as shown below.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an argument is neither an existing path nor a valid
+    /// dotted key expression, if a config file cannot be read or parsed, or if
+    /// merging fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use cargo_config2::{de, Config, Jobs, ResolveOptions};
+    ///
+    /// let cwd = std::env::current_dir()?;
+    /// let de = de::Config::load_with_cwd(&cwd)?.with_cli_config(["build.jobs=2"])?;
+    /// assert_eq!(de.build.jobs.as_ref().unwrap().val, Jobs::Count(2));
+    ///
+    /// // Applies environment variables and resolves relative paths.
+    /// let _config = Config::resolve(de, ResolveOptions::default(), &cwd)?;
+    /// # Ok(()) }
+    /// ```
+    pub fn with_cli_config<I: IntoIterator<Item = S>, S: AsRef<str>>(
+        mut self,
+        args: I,
+    ) -> Result<Self> {
+        for arg in args {
+            let arg = arg.as_ref();
+            let path = Path::new(arg);
+            let mut config = if !arg.is_empty() && path.exists() {
+                let mut config = Self::_load_file(path)?;
+                config.set_definition(&Definition::Cli(Some(path.to_owned())));
+                config
+            } else {
+                Self::from_cli_dotted_key(arg)?
+            };
+            // The argument has higher precedence than the config so far.
+            config
+                .merge(self, false)
+                .with_context(|| format!("failed to merge --config argument `{arg}`"))?;
+            self = config;
+        }
+        Ok(self)
+    }
+    // https://github.com/rust-lang/cargo/blob/0.80.0/src/cargo/util/context/mod.rs
+    fn from_cli_dotted_key(arg: &str) -> Result<Self> {
+        let doc: toml_edit::DocumentMut = arg.parse().with_context(|| {
+            format!(
+                "failed to parse value from --config argument `{arg}` as a dotted key expression"
+            )
+        })?;
+        // Only dotted key expressions followed by a value that is not an inline
+        // table are allowed.
+        let mut table = doc.as_table();
+        let mut is_root = true;
+        let mut got_to_value = false;
+        while table.is_dotted() || is_root {
+            is_root = false;
+            if table.len() != 1 {
+                break;
+            }
+            let (_, item) = table.iter().next().unwrap();
+            match item {
+                toml_edit::Item::Table(t) => table = t,
+                toml_edit::Item::Value(v) if v.is_inline_table() => {
+                    bail!(
+                        "--config argument `{arg}` sets a value to an inline table, which is not \
+                         accepted"
+                    );
+                }
+                toml_edit::Item::Value(_) => {
+                    got_to_value = true;
+                    break;
+                }
+                toml_edit::Item::ArrayOfTables(_) => {
+                    bail!(
+                        "--config argument `{arg}` sets a value to an array of tables, which is \
+                         not accepted"
+                    );
+                }
+                toml_edit::Item::None => bail!("--config argument `{arg}` doesn't provide a value"),
+            }
+        }
+        if !got_to_value {
+            bail!(
+                "--config argument `{arg}` was not a TOML dotted key expression (such as \
+                 `build.jobs = 2`)"
+            );
+        }
+        #[cfg(feature = "toml_edit")]
+        let raw = doc.as_table().clone();
+        let mut config: Self = toml_edit::de::from_document(doc)
+            .with_context(|| format!("failed to parse --config argument `{arg}`"))?;
+        config.set_definition(&Definition::Cli(None));
+        #[cfg(feature = "toml_edit")]
+        {
+            config.raw = raw;
+        }
+        Ok(config)
+    }
+
+    /// Returns the boolean value of the given dotted key (e.g., `net.offline`).
+    ///
+    /// Returns `Ok(None)` if the value is not set, and an error if the value
+    /// is not a boolean.
+    ///
+    /// Use [`get_definition`](Self::get_definition) to get where the value is defined.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), cargo_config2::Error> {
+    /// let config = cargo_config2::de::Config::load()?;
+    /// let offline = config.get_bool("net.offline")?.unwrap_or(false);
+    /// # let _ = offline;
+    /// # Ok(()) }
+    /// ```
+    pub fn get_bool(&self, key: &str) -> Result<Option<bool>> {
+        match self.get_value_ref(key) {
+            None => Ok(None),
+            Some(crate::get::ValueRef::Boolean(v, _)) => Ok(Some(v)),
+            Some(v) => {
+                bail!("invalid type for `{key}`: expected a boolean, but found {}", v.kind())
+            }
+        }
+    }
+    /// Returns the integer value of the given dotted key (e.g., `build.jobs`).
+    ///
+    /// Returns `Ok(None)` if the value is not set, and an error if the value
+    /// is not an integer.
+    pub fn get_integer(&self, key: &str) -> Result<Option<i64>> {
+        match self.get_value_ref(key) {
+            None => Ok(None),
+            Some(crate::get::ValueRef::Integer(v, _)) => Ok(Some(v)),
+            Some(v) => {
+                bail!("invalid type for `{key}`: expected an integer, but found {}", v.kind())
+            }
+        }
+    }
+    /// Returns the string value of the given dotted key (e.g., `build.rustc`).
+    ///
+    /// Returns `Ok(None)` if the value is not set, and an error if the value
+    /// is not a string.
+    pub fn get_string(&self, key: &str) -> Result<Option<&str>> {
+        match self.get_value_ref(key) {
+            None => Ok(None),
+            Some(crate::get::ValueRef::String(v, _)) => Ok(Some(v)),
+            Some(v) => bail!("invalid type for `{key}`: expected a string, but found {}", v.kind()),
+        }
+    }
+    /// Returns the list value of the given dotted key (e.g., `build.rustflags`).
+    ///
+    /// Values that can be specified as either a space-separated string or an
+    /// array (e.g., `build.rustflags` and `alias.<name>`) are returned as a list
+    /// in both cases.
+    ///
+    /// Returns `Ok(None)` if the value is not set, and an error if the value
+    /// is not a list.
+    pub fn get_list(&self, key: &str) -> Result<Option<Vec<&str>>> {
+        match self.get_value_ref(key) {
+            None => Ok(None),
+            Some(crate::get::ValueRef::List(v, _)) => Ok(Some(v)),
+            Some(v) => bail!("invalid type for `{key}`: expected a list, but found {}", v.kind()),
+        }
+    }
+    /// Returns the raw table of the given dotted key (e.g., `profile.release`),
+    /// including the tables and keys not modeled by this type.
+    ///
+    /// This is an escape hatch for the keys this crate does not support yet.
+    /// The tables are merged from config files and `--config` arguments in
+    /// the same way as the other values, but environment variables are not
+    /// applied to them. Configs created in other ways (e.g., from JSON) have
+    /// no raw tables.
+    ///
+    /// Returns `Ok(None)` if the value is not set, and an error if the value
+    /// is not a table.
+    ///
+    /// This is available with the `toml_edit` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), cargo_config2::Error> {
+    /// let config = cargo_config2::de::Config::load()?;
+    /// if let Some(release) = config.get_table("profile.release")? {
+    ///     let lto = release.get("lto").and_then(|v| v.as_bool());
+    ///     # let _ = lto;
+    /// }
+    /// # Ok(()) }
+    /// ```
+    #[cfg(feature = "toml_edit")]
+    pub fn get_table(&self, key: &str) -> Result<Option<&dyn toml_edit::TableLike>> {
+        get_table(&self.raw, key)
+    }
+    /// Converts to [`toml_edit::DocumentMut`](https://docs.rs/toml_edit/latest/toml_edit/struct.DocumentMut.html).
+    ///
+    /// Unlike serializing the config to a string, the returned document can be
+    /// edited key by key (including comments and decor) and then written back
+    /// with `to_string`.
+    ///
+    /// **Note:** [`Definition`]s of the values are not included in the document.
+    ///
+    /// This is available with the `toml_edit` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the config contains a value that cannot be
+    /// represented in TOML.
+    #[cfg(feature = "toml_edit")]
+    pub fn to_document(&self) -> Result<toml_edit::DocumentMut> {
+        toml_edit::ser::to_document(self).context("failed to convert config to TOML document")
+    }
+    /// Returns where the value of the given dotted key is defined.
+    ///
+    /// Returns `None` if the value is not set, the value is a table, or the
+    /// definition is unknown.
+    pub fn get_definition(&self, key: &str) -> Option<&Definition> {
+        self.get_value_ref(key)?.definition()
+    }
+    fn get_value_ref(&self, key: &str) -> Option<crate::get::ValueRef<'_>> {
+        let key: Vec<_> = key.split('.').collect();
+        crate::get::GetValue::get_value(self, &key)
     }
 
-    pub(crate) fn set_path(&mut self, path: &Path) {
-        crate::value::SetPath::set_path(self, path);
+    pub(crate) fn set_definition(&mut self, def: &Definition) {
+        crate::value::SetDefinition::set_definition(self, def);
     }
 
     #[allow(clippy::ref_option)]
@@ -214,10 +546,10 @@ impl Config {
             target_linker = Some(linker);
         }
         if let Some(runner) = cx.env_dyn(&format!("CARGO_TARGET_{target_u_upper}_RUNNER"))? {
-            target_runner = Some(
-                PathAndArgs::from_string(&runner.val, runner.definition)
-                    .context("invalid length 0, expected at least one element")?,
-            );
+            // Empty runner (or runner with only whitespace) is treated as unset.
+            if let Some(runner) = PathAndArgs::from_string(&runner.val, runner.definition) {
+                target_runner = Some(runner);
+            }
         }
         if let Some(rustflags) = cx.env_dyn(&format!("CARGO_TARGET_{target_u_upper}_RUSTFLAGS"))? {
             let mut rustflags =
@@ -241,6 +573,9 @@ impl Config {
                 target_rustdocflags @ None => *target_rustdocflags = Some(rustdocflags),
             }
         }
+        // If multiple `target.<cfg>` tables match, they are applied in the
+        // lexicographical order of the keys, as cargo does (cargo sorts the
+        // `target.<cfg>` tables by key to make the order deterministic).
         for (k, v) in target_configs {
             if !k.starts_with("cfg(") {
                 continue;
@@ -286,7 +621,13 @@ impl Config {
                 .get_or_insert_with(TargetConfig::default)
                 .rustflags
                 .clone_from(build_rustflags);
-        } else if let Some(rustflags) = target_rustflags {
+        } else if let Some(mut rustflags) = target_rustflags {
+            // Non-cargo behavior enabled by ResolveOptions::combine_rustflags.
+            if cx.combine_rustflags {
+                if let Some(build_rustflags) = build_rustflags {
+                    rustflags.flags.extend_from_slice(&build_rustflags.flags);
+                }
+            }
             target_config.get_or_insert_with(TargetConfig::default).rustflags = Some(rustflags);
         } else {
             target_config
@@ -312,6 +653,22 @@ impl Config {
     }
 }
 
+#[cfg(feature = "toml_edit")]
+pub(crate) fn get_table<'a>(
+    raw: &'a toml_edit::Table,
+    key: &str,
+) -> Result<Option<&'a dyn toml_edit::TableLike>> {
+    let mut table: &dyn toml_edit::TableLike = raw;
+    for k in key.split('.') {
+        let Some(item) = table.get(k) else { return Ok(None) };
+        let Some(t) = item.as_table_like() else {
+            bail!("invalid type for `{key}`: expected a table, but found {}", item.type_name())
+        };
+        table = t;
+    }
+    Ok(Some(table))
+}
+
 /// The `[build]` table.
 ///
 /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#build)
@@ -321,11 +678,12 @@ impl Config {
 pub struct BuildConfig {
     /// Sets the maximum number of compiler processes to run in parallel.
     /// If negative, it sets the maximum number of compiler processes to the
-    /// number of logical CPUs plus provided value. Should not be 0.
+    /// number of logical CPUs plus provided value. Should not be 0. If set to
+    /// `"default"`, the default value (the number of logical CPUs) is used.
     ///
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#buildjobs)
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub jobs: Option<Value<i32>>,
+    pub jobs: Option<Value<Jobs>>,
     /// Sets the executable to use for `rustc`.
     ///
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#buildrustc)
@@ -357,6 +715,14 @@ pub struct BuildConfig {
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#buildtarget)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub target_dir: Option<Value<String>>,
+    /// The path to where intermediate build artifacts are placed. The default if
+    /// not specified is the same as `build.target-dir`.
+    ///
+    /// This is supported in Cargo 1.91+.
+    ///
+    /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#buildbuild-dir)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_dir: Option<Value<String>>,
     /// Extra command-line flags to pass to rustc. The value may be an array
     /// of strings or a space-separated string.
     ///
@@ -379,6 +745,11 @@ pub struct BuildConfig {
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#builddep-info-basedir)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dep_info_basedir: Option<Value<String>>,
+    /// Controls how Cargo handles warnings.
+    ///
+    /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#buildwarnings)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warnings: Option<Value<Warnings>>,
 
     // Resolve contexts. Completely ignored in serialization and deserialization.
     #[serde(skip)]
@@ -413,7 +784,95 @@ pub struct TargetConfig {
     /// [reference (`target.<cfg>.rustdocflags`)](https://doc.rust-lang.org/nightly/cargo/reference/config.html#targetcfgrustdocflags)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rustdocflags: Option<Flags>,
-    // TODO: links: https://doc.rust-lang.org/nightly/cargo/reference/config.html#targettriplelinks
+    /// The `[target.<triple>.<links>]` tables, keyed by the `links` name of the package.
+    ///
+    /// Only table-valued keys are treated as link overrides; other unknown
+    /// keys are ignored.
+    ///
+    /// This is only supported in `[target.<triple>]` tables, and ignored in
+    /// `[target.<cfg>]` tables, like cargo (see also [`Config::validate`]).
+    ///
+    /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#targettriplelinks)
+    #[serde(flatten, deserialize_with = "deserialize_links")]
+    pub links: BTreeMap<String, LinkOverride>,
+}
+
+/// Deserializes the unknown keys of a `[target]` table, collecting only
+/// table-valued keys as link overrides.
+fn deserialize_links<'de, D>(deserializer: D) -> Result<BTreeMap<String, LinkOverride>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct MaybeLinkOverride(Option<LinkOverride>);
+    impl<'de> Deserialize<'de> for MaybeLinkOverride {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct MaybeLinkOverrideVisitor;
+            impl<'de> de::Visitor<'de> for MaybeLinkOverrideVisitor {
+                type Value = MaybeLinkOverride;
+                fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.write_str("any value")
+                }
+                fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                    LinkOverride::deserialize(de::value::MapAccessDeserializer::new(map))
+                        .map(|v| MaybeLinkOverride(Some(v)))
+                }
+                fn visit_seq<A: de::SeqAccess<'de>>(
+                    self,
+                    mut seq: A,
+                ) -> Result<Self::Value, A::Error> {
+                    while seq.next_element::<de::IgnoredAny>()?.is_some() {}
+                    Ok(MaybeLinkOverride(None))
+                }
+                fn visit_bool<E: de::Error>(self, _: bool) -> Result<Self::Value, E> {
+                    Ok(MaybeLinkOverride(None))
+                }
+                fn visit_i64<E: de::Error>(self, _: i64) -> Result<Self::Value, E> {
+                    Ok(MaybeLinkOverride(None))
+                }
+                fn visit_u64<E: de::Error>(self, _: u64) -> Result<Self::Value, E> {
+                    Ok(MaybeLinkOverride(None))
+                }
+                fn visit_f64<E: de::Error>(self, _: f64) -> Result<Self::Value, E> {
+                    Ok(MaybeLinkOverride(None))
+                }
+                fn visit_str<E: de::Error>(self, _: &str) -> Result<Self::Value, E> {
+                    Ok(MaybeLinkOverride(None))
+                }
+                fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+                    Ok(MaybeLinkOverride(None))
+                }
+            }
+            deserializer.deserialize_any(MaybeLinkOverrideVisitor)
+        }
+    }
+    let links = BTreeMap::<String, MaybeLinkOverride>::deserialize(deserializer)?;
+    Ok(links.into_iter().filter_map(|(k, v)| Some((k, v.0?))).collect())
+}
+
+/// A `[target.<triple>.<links>]` table, which overrides the build script
+/// of the package that links the `<links>` library.
+///
+/// [reference](https://doc.rust-lang.org/nightly/cargo/reference/build-scripts.html#overriding-build-scripts)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub struct LinkOverride {
+    /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/build-scripts.html#rustc-link-lib)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rustc_link_lib: Option<Vec<Value<String>>>,
+    /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/build-scripts.html#rustc-link-search)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rustc_link_search: Option<Vec<Value<String>>>,
+    /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/build-scripts.html#rustc-flags)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rustc_flags: Option<Value<String>>,
+    /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/build-scripts.html#rustc-cfg)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rustc_cfg: Option<Vec<Value<String>>>,
+    /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/build-scripts.html#rustc-env)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub rustc_env: BTreeMap<String, Value<String>>,
 }
 
 /// The `[doc]` table.
@@ -456,6 +915,11 @@ impl EnvConfigValue {
             Self::Table { .. } => "table",
         }
     }
+    pub(crate) fn definition(&self) -> Option<&Definition> {
+        match self {
+            Self::Value(v) | Self::Table { value: v, .. } => v.definition.as_ref(),
+        }
+    }
 
     pub(crate) fn resolve(&self, current_dir: &Path) -> Cow<'_, OsStr> {
         match self {
@@ -633,6 +1097,31 @@ pub struct NetConfig {
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#netoffline)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub offline: Option<Value<bool>>,
+    /// The `[net.ssh]` table.
+    ///
+    /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#netssh)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "NetSshConfig::is_empty")]
+    pub ssh: NetSshConfig,
+}
+
+/// The `[net.ssh]` table.
+///
+/// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#netssh)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub struct NetSshConfig {
+    /// A list of SSH host keys that should be accepted as valid when
+    /// connecting to Git hosts, in the same format as OpenSSH `known_hosts`
+    /// lines.
+    ///
+    /// **Note:** Unlike most other config values, cargo does not read this
+    /// from the environment variable.
+    ///
+    /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#netsshknown-hosts)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub known_hosts: Option<StringList>,
 }
 
 /// A value of the `[registries]` table.
@@ -767,11 +1256,15 @@ pub struct TermConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub color: Option<Value<Color>>,
     #[serde(default)]
-    #[serde(skip_serializing_if = "TermProgress::is_none")]
+    #[serde(skip_serializing_if = "TermProgress::is_empty")]
     pub progress: TermProgress,
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// The `[term.progress]` table.
+///
+/// The shorthand form (`term.progress = <bool>`) is also accepted, which is
+/// equivalent to setting `when` to `auto` (`true`) or `never` (`false`).
+#[derive(Debug, Clone, Default, Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[non_exhaustive]
 pub struct TermProgress {
@@ -787,6 +1280,35 @@ pub struct TermProgress {
     pub width: Option<Value<u32>>,
 }
 
+impl<'de> Deserialize<'de> for TermProgress {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "kebab-case")]
+        struct Table {
+            #[serde(default)]
+            when: Option<Value<When>>,
+            #[serde(default)]
+            width: Option<Value<u32>>,
+        }
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum BoolOrTable {
+            Bool(bool),
+            Table(Table),
+        }
+        match BoolOrTable::deserialize(deserializer)? {
+            BoolOrTable::Bool(progress) => {
+                let when = if progress { When::Auto } else { When::Never };
+                Ok(Self { when: Some(Value { val: when, definition: None }), width: None })
+            }
+            BoolOrTable::Table(Table { when, width }) => Ok(Self { when, width }),
+        }
+    }
+}
+
 #[allow(clippy::exhaustive_enums)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -828,6 +1350,20 @@ impl FromStr for Color {
     }
 }
 
+/// Converts to [`anstream::ColorChoice`](https://docs.rs/anstream/latest/anstream/enum.ColorChoice.html).
+///
+/// This is available with the `color` feature.
+#[cfg(feature = "color")]
+impl From<Color> for colorchoice::ColorChoice {
+    fn from(color: Color) -> Self {
+        match color {
+            Color::Auto => Self::Auto,
+            Color::Always => Self::Always,
+            Color::Never => Self::Never,
+        }
+    }
+}
+
 #[allow(clippy::exhaustive_enums)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -907,7 +1443,110 @@ impl FromStr for Frequency {
     }
 }
 
+/// The value of [`build.jobs`](BuildConfig::jobs).
+///
+/// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#buildjobs)
+#[allow(clippy::exhaustive_enums)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Jobs {
+    /// `"default"`: Uses the default number of jobs (the number of logical CPUs).
+    #[default]
+    Default,
+    /// Sets the maximum number of compiler processes to run in parallel.
+    /// If negative, it sets the maximum number of compiler processes to the
+    /// number of logical CPUs plus provided value.
+    Count(i32),
+}
+
+impl FromStr for Jobs {
+    type Err = Error;
+
+    fn from_str(jobs: &str) -> Result<Self, Self::Err> {
+        if jobs == "default" {
+            return Ok(Self::Default);
+        }
+        match jobs.parse() {
+            Ok(jobs) => Ok(Self::Count(jobs)),
+            Err(_) => bail!(
+                "could not parse `{jobs}`. Number of parallel jobs should be `default` or a number."
+            ),
+        }
+    }
+}
+
+impl Serialize for Jobs {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            Self::Default => serializer.serialize_str("default"),
+            Self::Count(jobs) => serializer.serialize_i32(*jobs),
+        }
+    }
+}
+impl<'de> Deserialize<'de> for Jobs {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum IntegerOrString {
+            Integer(i32),
+            String(String),
+        }
+        match IntegerOrString::deserialize(deserializer)? {
+            IntegerOrString::Integer(jobs) => Ok(Self::Count(jobs)),
+            IntegerOrString::String(jobs) if jobs == "default" => Ok(Self::Default),
+            IntegerOrString::String(jobs) => Err(de::Error::custom(format_args!(
+                "could not parse `{jobs}`. Number of parallel jobs should be `default` or a number."
+            ))),
+        }
+    }
+}
+
+#[allow(clippy::exhaustive_enums)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Warnings {
+    /// (default) Warnings are displayed and do not affect the operation.
+    #[default]
+    Warn,
+    /// Warnings are hidden and do not affect the operation.
+    Allow,
+    /// Warnings are displayed and the operation fails.
+    Deny,
+}
+
+impl Warnings {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Warn => "warn",
+            Self::Allow => "allow",
+            Self::Deny => "deny",
+        }
+    }
+}
+
+impl FromStr for Warnings {
+    type Err = Error;
+
+    fn from_str(warnings: &str) -> Result<Self, Self::Err> {
+        match warnings {
+            "warn" => Ok(Self::Warn),
+            "allow" => Ok(Self::Allow),
+            "deny" => Ok(Self::Deny),
+            other => bail!("must be warn, allow, or deny, but found `{other}`"),
+        }
+    }
+}
+
 /// A representation of rustflags and rustdocflags.
+///
+/// As a lenient extension to cargo, a string containing ASCII unit separator
+/// (`'\x1f'`) is split by it instead of by space, in the same way as
+/// `CARGO_ENCODED_RUSTFLAGS`. Such a value is treated as an array when merging.
 #[derive(Debug, Clone, Serialize)]
 #[serde(transparent)]
 pub struct Flags {
@@ -975,13 +1614,92 @@ impl<'de> Deserialize<'de> for Flags {
     where
         D: Deserializer<'de>,
     {
-        let v: StringOrArray = Deserialize::deserialize(deserializer)?;
-        match v {
-            StringOrArray::String(s) => {
-                Ok(Self::from_space_separated(&s.val, s.definition.as_ref()))
+        // Use a visitor instead of StringOrArray to report which element of
+        // the array has an invalid type, instead of the generic untagged enum error.
+        struct FlagsVisitor;
+        impl<'de> de::Visitor<'de> for FlagsVisitor {
+            type Value = Flags;
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a string or an array of strings")
+            }
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+                // Cargo always splits string-form flags in config by space, but as
+                // a lenient extension, we handle strings containing ASCII unit
+                // separator ('\x1f') in the same way as CARGO_ENCODED_RUSTFLAGS.
+                if s.contains('\x1f') {
+                    Ok(Flags::from_encoded(&Value { val: s.to_owned(), definition: None }))
+                } else {
+                    Ok(Flags::from_space_separated(s, None))
+                }
+            }
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut flags = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(flag) = seq.next_element::<FlagElement>()? {
+                    match flag {
+                        FlagElement::String(val) => flags.push(Value { val, definition: None }),
+                        FlagElement::Other(kind) => {
+                            return Err(de::Error::custom(format_args!(
+                                "expected an array of strings, found {kind} at index {}",
+                                flags.len()
+                            )));
+                        }
+                    }
+                }
+                Ok(Flags::from_array(flags))
+            }
+        }
+        enum FlagElement {
+            String(String),
+            Other(&'static str),
+        }
+        impl<'de> Deserialize<'de> for FlagElement {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct FlagElementVisitor;
+                impl<'de> de::Visitor<'de> for FlagElementVisitor {
+                    type Value = FlagElement;
+                    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                        f.write_str("a string")
+                    }
+                    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+                        Ok(FlagElement::String(v.to_owned()))
+                    }
+                    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+                        Ok(FlagElement::String(v))
+                    }
+                    fn visit_bool<E: de::Error>(self, _: bool) -> Result<Self::Value, E> {
+                        Ok(FlagElement::Other("boolean"))
+                    }
+                    fn visit_i64<E: de::Error>(self, _: i64) -> Result<Self::Value, E> {
+                        Ok(FlagElement::Other("integer"))
+                    }
+                    fn visit_u64<E: de::Error>(self, _: u64) -> Result<Self::Value, E> {
+                        Ok(FlagElement::Other("integer"))
+                    }
+                    fn visit_f64<E: de::Error>(self, _: f64) -> Result<Self::Value, E> {
+                        Ok(FlagElement::Other("float"))
+                    }
+                    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+                        Ok(FlagElement::Other("null"))
+                    }
+                    fn visit_seq<A: de::SeqAccess<'de>>(
+                        self,
+                        mut seq: A,
+                    ) -> Result<Self::Value, A::Error> {
+                        while seq.next_element::<de::IgnoredAny>()?.is_some() {}
+                        Ok(FlagElement::Other("array"))
+                    }
+                    fn visit_map<A: de::MapAccess<'de>>(
+                        self,
+                        mut map: A,
+                    ) -> Result<Self::Value, A::Error> {
+                        while map.next_entry::<de::IgnoredAny, de::IgnoredAny>()?.is_some() {}
+                        Ok(FlagElement::Other("table"))
+                    }
+                }
+                deserializer.deserialize_any(FlagElementVisitor)
             }
-            StringOrArray::Array(v) => Ok(Self::from_array(v)),
         }
+        deserializer.deserialize_any(FlagsVisitor)
     }
 }
 
@@ -1193,6 +1911,12 @@ impl StringOrArray {
             Self::Array(..) => "array",
         }
     }
+    pub(crate) fn definition(&self) -> Option<&Definition> {
+        match self {
+            Self::String(v) => v.definition.as_ref(),
+            Self::Array(v) => v.first().and_then(|v| v.definition.as_ref()),
+        }
+    }
 
     // pub(crate) fn string(&self) -> Option<&Value<String>> {
     //     match self {
@@ -1223,10 +1947,39 @@ pub(crate) fn target_u_upper(target: &str) -> String {
     target
 }
 
-pub(crate) fn split_encoded(s: &str) -> impl Iterator<Item = &str> {
+/// Splits a string separated with ASCII unit separator (`'\x1f'`) in the same way as cargo.
+///
+/// This is a valid format for `CARGO_ENCODED_RUSTFLAGS` and
+/// `CARGO_ENCODED_RUSTDOCFLAGS` environment variables. Unlike
+/// [`split_space_separated`], elements are neither trimmed nor filtered.
+///
+/// # Examples
+///
+/// ```
+/// use cargo_config2::split_encoded;
+///
+/// let v: Vec<_> = split_encoded("-C\x1fopt-level=3\x1f\x1f a ").collect();
+/// assert_eq!(v, ["-C", "opt-level=3", "", " a "]);
+/// ```
+pub fn split_encoded(s: &str) -> impl Iterator<Item = &str> {
     s.split('\x1f')
 }
-pub(crate) fn split_space_separated(s: &str) -> impl Iterator<Item = &str> {
+/// Splits a space-separated string in the same way as cargo.
+///
+/// This is a valid format for `RUSTFLAGS` and `RUSTDOCFLAGS` environment
+/// variables and for string values of `build.rustflags` and similar configs.
+/// Each element is trimmed and empty elements are removed.
+///
+/// # Examples
+///
+/// ```
+/// use cargo_config2::split_space_separated;
+///
+/// let v: Vec<_> = split_space_separated("  -C  opt-level=3 ").collect();
+/// assert_eq!(v, ["-C", "opt-level=3"]);
+/// assert_eq!(split_space_separated("   ").count(), 0);
+/// ```
+pub fn split_space_separated(s: &str) -> impl Iterator<Item = &str> {
     // TODO: tab/line?
     // https://github.com/rust-lang/cargo/blob/0.80.0/src/cargo/util/context/path.rs#L89
     s.split(' ').map(str::trim).filter(|s| !s.is_empty())
@@ -1,32 +1,74 @@
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
-use core::{cell::RefCell, fmt, ops};
+use core::{fmt, iter, ops, slice};
 use std::{
     borrow::Cow,
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet, HashSet},
     ffi::{OsStr, OsString},
     path::{Path, PathBuf},
     process::Command,
 };
 
-use serde::ser::{Serialize, Serializer};
+use serde::{
+    de::{Deserialize, Deserializer, Error as _},
+    ser::{Serialize, Serializer},
+};
 use serde_derive::Serialize;
 
 use crate::{
     de::{
-        self, split_encoded, split_space_separated, Color, Frequency, RegistriesProtocol,
-        VersionControlSoftware, When,
+        self, split_encoded, split_space_separated, Color, Frequency, Jobs, RegistriesProtocol,
+        VersionControlSoftware, Warnings, When,
     },
+    env::registry_env_key,
     error::{Context as _, Result},
     process::ProcessBuilder,
     resolve::{
-        CargoVersion, ResolveContext, ResolveOptions, RustcVersion, TargetTriple,
+        Cache, CargoVersion, ResolveContext, ResolveOptions, RustcVersion, TargetTriple,
         TargetTripleBorrow, TargetTripleRef,
     },
-    value::Value,
+    validate,
+    value::{Definition, Value},
+    walk::{self, Walk},
 };
 
 /// Cargo configuration.
+///
+/// # Laziness
+///
+/// Loading config reads config files and environment variables, and converts
+/// the tables other than `[target]`, which is cheap and does not spawn any
+/// processes.
+///
+/// The `[target]` table is resolved lazily for each target, the first time
+/// [`target`](Self::target) or other target-specific methods are called for
+/// that target. Evaluating `[target.<cfg>]` tables may spawn `rustc`, so
+/// tools that only need other tables (e.g., `[net]`) never spawn `rustc`.
+///
+/// # Serialization
+///
+/// Serialization output is deterministic: tables such as `[alias]`, `[env]`,
+/// and `[registries]` are sorted by key.
+///
+/// The `[target]` table only contains targets that have been resolved by
+/// [`target`](Self::target) or other target-specific methods, and is sorted by
+/// the value passed to cargo's `--target` option
+/// (see [`TargetTriple::cli_target_string`]). Thus, custom targets are keyed
+/// and sorted by their spec paths, not by the file stems.
+///
+/// # Deserialization
+///
+/// A config serialized by this type can be deserialized again, e.g., to cache
+/// a resolved config on disk. The deserialized config has the same public
+/// fields, but the information that is not serialized is lost:
+///
+/// - Environment variables are not read again, `CARGO_HOME` is not set, and
+///   relative paths are kept as is.
+/// - Definitions (e.g., [`EnvConfigValue::definition`]) are `None`.
+/// - Caches are empty, so the host triple, the versions of rustc and cargo,
+///   and the `[target]` table are resolved again when needed. Targets that were
+///   not resolved before serialization, including `[target.<cfg>]` tables, are
+///   not available. If you need them, re-resolve the config from config files.
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[non_exhaustive]
@@ -42,13 +84,13 @@ pub struct Config {
     ///
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#build)
     #[serde(default)]
-    #[serde(skip_serializing_if = "BuildConfig::is_none")]
+    #[serde(skip_serializing_if = "BuildConfig::is_empty")]
     pub build: BuildConfig,
     /// The `[doc]` table.
     ///
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#doc)
     #[serde(default)]
-    #[serde(skip_serializing_if = "DocConfig::is_none")]
+    #[serde(skip_serializing_if = "DocConfig::is_empty")]
     pub doc: DocConfig,
     /// The `[env]` table.
     ///
@@ -60,26 +102,26 @@ pub struct Config {
     ///
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#future-incompat-report)
     #[serde(default)]
-    #[serde(skip_serializing_if = "FutureIncompatReportConfig::is_none")]
+    #[serde(skip_serializing_if = "FutureIncompatReportConfig::is_empty")]
     pub future_incompat_report: FutureIncompatReportConfig,
     /// The `[cargo-new]` table.
     ///
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#cargo-new)
     #[serde(default)]
-    #[serde(skip_serializing_if = "CargoNewConfig::is_none")]
+    #[serde(skip_serializing_if = "CargoNewConfig::is_empty")]
     pub cargo_new: CargoNewConfig,
     /// The `[http]` table.
     ///
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#http)
     #[serde(default)]
-    #[serde(skip_serializing_if = "HttpConfig::is_none")]
+    #[serde(skip_serializing_if = "HttpConfig::is_empty")]
     pub http: HttpConfig,
     // TODO: install
     /// The `[net]` table.
     ///
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#net)
     #[serde(default)]
-    #[serde(skip_serializing_if = "NetConfig::is_none")]
+    #[serde(skip_serializing_if = "NetConfig::is_empty")]
     pub net: NetConfig,
     // TODO: patch
     // TODO: profile
@@ -93,13 +135,13 @@ pub struct Config {
     ///
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#registry)
     #[serde(default)]
-    #[serde(skip_serializing_if = "RegistryConfig::is_none")]
+    #[serde(skip_serializing_if = "RegistryConfig::is_empty")]
     pub registry: RegistryConfig,
     // TODO: source
     /// The resolved `[target]` table.
     #[serde(skip_deserializing)]
-    #[serde(skip_serializing_if = "ref_cell_bree_map_is_empty")]
-    target: RefCell<BTreeMap<TargetTripleBorrow<'static>, TargetConfig>>,
+    #[serde(skip_serializing_if = "cache_bree_map_is_empty")]
+    target: Cache<BTreeMap<TargetTripleBorrow<'static>, TargetConfig>>,
     /// The unresolved `[target]` table.
     #[serde(default)]
     #[serde(skip_serializing)]
@@ -110,16 +152,41 @@ pub struct Config {
     ///
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#term)
     #[serde(default)]
-    #[serde(skip_serializing_if = "TermConfig::is_none")]
+    #[serde(skip_serializing_if = "TermConfig::is_empty")]
     pub term: TermConfig,
 
     // Resolve contexts. Completely ignored in serialization and deserialization.
     #[serde(skip)]
     cx: ResolveContext,
+    /// `build.target` from config files, before environment variables are applied.
+    #[serde(skip)]
+    config_build_target: Option<Vec<TargetTriple>>,
+    /// The raw tables from config files. See [`de::Config::get_table`].
+    #[cfg(feature = "toml_edit")]
+    #[serde(skip)]
+    raw: toml_edit::Table,
+}
+
+// https://github.com/rust-lang/cargo/blob/0.80.0/src/cargo/sources/registry/mod.rs
+const CRATES_IO_GIT_INDEX: &str = "https://github.com/rust-lang/crates.io-index";
+const CRATES_IO_SPARSE_INDEX: &str = "sparse+https://index.crates.io/";
+
+fn cache_bree_map_is_empty<K, V>(map: &Cache<BTreeMap<K, V>>) -> bool {
+    map.lock().is_empty()
+}
+
+fn encode_flags(flags: Option<Flags>) -> Result<Option<String>> {
+    match flags {
+        Some(flags) if !flags.flags.is_empty() => Ok(Some(flags.encode()?)),
+        _ => Ok(None),
+    }
 }
 
-fn ref_cell_bree_map_is_empty<K, V>(map: &RefCell<BTreeMap<K, V>>) -> bool {
-    map.borrow().is_empty()
+/// Removes duplicate targets while preserving the order in which each target first appeared.
+fn dedup_targets(mut targets: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    targets.retain(|t| seen.insert(t.clone()));
+    targets
 }
 
 impl Config {
@@ -136,14 +203,121 @@ impl Config {
 
     /// Read config files hierarchically from the given directory and merges them.
     pub fn load_with_options<P: AsRef<Path>>(cwd: P, options: ResolveOptions) -> Result<Self> {
-        let cwd = cwd.as_ref();
-        let cx = options.into_context(cwd.to_owned());
+        let cwd = &options.current_dir_for_load(cwd.as_ref().to_owned())?;
+        let cx = options.into_context(cwd.clone());
 
-        let de = de::Config::_load_with_options(&cx.current_dir, cx.cargo_home(cwd))?;
+        let paths: Vec<_> =
+            walk::WalkInner::with_cargo_home(&cx.current_dir, cx.cargo_home_for_load(cwd)?)
+                .exclude_cargo_home(!cx.include_cargo_home)
+                .collect();
+        // Fast path: if there are no config files and no relevant environment
+        // variables, the result is the same as the default config, so skip
+        // loading, merging, and resolving config.
+        if paths.is_empty() && cx.env.is_empty() {
+            return Ok(Self::empty(cx));
+        }
+        let de = de::Config::load_files(paths)?;
         Self::from_unresolved(de, cx)
     }
 
+    /// Read config files hierarchically from the given directory and merges them,
+    /// reading the given key-values as environment variables instead of the
+    /// environment variables of the current process.
+    ///
+    /// This is a shorthand for
+    /// [`load_with_options(cwd, ResolveOptions::default().env(vars))`](Self::load_with_options).
+    /// See [`ResolveOptions::env`] for more.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), cargo_config2::Error> {
+    /// use cargo_config2::Config;
+    ///
+    /// let config = Config::load_with_env(".", [("CARGO_BUILD_JOBS", "2")])?;
+    /// assert_eq!(config.build.jobs, Some(cargo_config2::Jobs::Count(2)));
+    /// # Ok(()) }
+    /// ```
+    pub fn load_with_env<
+        P: AsRef<Path>,
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<OsString>,
+        V: Into<OsString>,
+    >(
+        cwd: P,
+        vars: I,
+    ) -> Result<Self> {
+        Self::load_with_options(cwd, ResolveOptions::default().env(vars))
+    }
+
+    /// Read config files hierarchically from the given directory and merges them,
+    /// taking all inputs from outside of the config files explicitly.
+    ///
+    /// Unlike [`load`](Self::load) and [`load_with_env`](Self::load_with_env),
+    /// this does not depend on the state of the current process: the given
+    /// key-values are read as environment variables instead of the
+    /// environment variables of the current process, the config in
+    /// `CARGO_HOME` is read only if `cargo_home` is `Some`, and the given
+    /// `rustc` is used to get the target information instead of detecting it
+    /// from config and environment variables.
+    ///
+    /// This is a shorthand for
+    /// [`load_with_options`](Self::load_with_options) with
+    /// [`ResolveOptions::env`], [`ResolveOptions::cargo_home`], and
+    /// [`ResolveOptions::rustc`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), cargo_config2::Error> {
+    /// use std::collections::HashMap;
+    ///
+    /// use cargo_config2::{Color, Config, Jobs, PathAndArgs};
+    ///
+    /// let env = HashMap::from([("CARGO_BUILD_JOBS", "2"), ("CARGO_TERM_COLOR", "never")]);
+    /// let config = Config::load_reproducible(".", env, None, PathAndArgs::new("rustc"))?;
+    /// assert_eq!(config.build.jobs, Some(Jobs::Count(2)));
+    /// assert_eq!(config.term.color, Some(Color::Never));
+    /// # Ok(()) }
+    /// ```
+    pub fn load_reproducible<
+        P: AsRef<Path>,
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<OsString>,
+        V: Into<OsString>,
+        H: Into<Option<PathBuf>>,
+        R: Into<PathAndArgs>,
+    >(
+        cwd: P,
+        vars: I,
+        cargo_home: H,
+        rustc: R,
+    ) -> Result<Self> {
+        let options = ResolveOptions::default().env(vars).cargo_home(cargo_home).rustc(rustc);
+        Self::load_with_options(cwd, options)
+    }
+
+    /// Resolves the given unresolved config.
+    ///
+    /// This applies environment variables and resolves relative paths in the
+    /// same way as [`load_with_options`](Self::load_with_options), but does not
+    /// read any config files. This is useful to resolve config obtained in
+    /// other ways, such as [`de::Config::from_json_str`] or [`de::Config::load_files`].
+    ///
+    /// `cwd` is used as the current directory to resolve relative paths defined
+    /// in environment variables or on the command line. Relative paths whose
+    /// [`Definition`](de::Definition) is `None` are kept as is.
+    pub fn resolve<P: AsRef<Path>>(
+        de: de::Config,
+        options: ResolveOptions,
+        cwd: P,
+    ) -> Result<Self> {
+        Self::from_unresolved(de, options.into_context(cwd.as_ref().to_owned()))
+    }
+
     fn from_unresolved(mut de: de::Config, cx: ResolveContext) -> Result<Self> {
+        let config_build_target =
+            de.build.target.as_ref().map(|t| target_from_unresolved(t, &cx.current_dir));
         de.apply_env(&cx)?;
 
         let mut alias = BTreeMap::new();
@@ -163,6 +337,16 @@ impl Config {
         let net = NetConfig::from_unresolved(de.net);
         let mut registries = BTreeMap::new();
         for (k, v) in de.registries {
+            // https://doc.rust-lang.org/nightly/cargo/reference/config.html#registriescrates-ioprotocol
+            if k != "crates-io" {
+                if let Some(protocol) = &v.protocol {
+                    let msg = validate::registry_protocol_not_allowed(&k);
+                    match &protocol.definition {
+                        Some(def) => bail!("{msg} (defined in {def})"),
+                        None => bail!("{msg}"),
+                    }
+                }
+            }
             registries.insert(k, RegistriesConfigValue::from_unresolved(v));
         }
         let registry = RegistryConfig::from_unresolved(de.registry);
@@ -179,13 +363,44 @@ impl Config {
             net,
             registries,
             registry,
-            target: RefCell::new(BTreeMap::new()),
+            target: Cache::default(),
             de_target: de.target,
             term,
             cx,
+            config_build_target,
+            #[cfg(feature = "toml_edit")]
+            raw: de.raw,
         })
     }
 
+    /// Returns the config resolved from an empty unresolved config without
+    /// environment variables.
+    ///
+    /// This is equivalent to `from_unresolved(de::Config::default(), cx)` if
+    /// `cx` has no environment variables.
+    fn empty(cx: ResolveContext) -> Self {
+        debug_assert!(cx.env.is_empty());
+        Self {
+            alias: BTreeMap::new(),
+            build: BuildConfig::default(),
+            doc: DocConfig::default(),
+            env: BTreeMap::new(),
+            future_incompat_report: FutureIncompatReportConfig::default(),
+            cargo_new: CargoNewConfig::default(),
+            http: HttpConfig::default(),
+            net: NetConfig::default(),
+            registries: BTreeMap::new(),
+            registry: RegistryConfig::default(),
+            target: Cache::default(),
+            de_target: BTreeMap::new(),
+            term: TermConfig::default(),
+            cx,
+            config_build_target: None,
+            #[cfg(feature = "toml_edit")]
+            raw: toml_edit::Table::new(),
+        }
+    }
+
     /// Selects target triples to build.
     ///
     /// The targets returned are based on the order of priority in which cargo
@@ -196,6 +411,9 @@ impl Config {
     /// 3. `build.target` config
     /// 4. [host triple](Self::host_triple)
     ///
+    /// Like cargo, an empty `build.target` (`build.target = []`) is treated as
+    /// unset, so the host triple is returned in that case.
+    ///
     /// **Note:** The result of this function is intended to handle target-specific
     /// configurations and is not always appropriate to propagate directly to Cargo.
     /// See [`build_target_for_cli`](Self::build_target_for_cli) for more.
@@ -294,6 +512,19 @@ impl Config {
     /// above) is set.
     /// Also, Unlike [`build_target_for_config`](Self::build_target_for_config)
     /// the target name specified in path is preserved.
+    ///
+    /// Like cargo, duplicate targets are removed, and the order in which each
+    /// target first appeared is preserved. A target specified by path and a
+    /// target specified by triple name are not considered duplicates, even if
+    /// the file stem of the path is the same as the triple name.
+    ///
+    /// If none of the above is set, an empty vec is returned, which means that
+    /// `--target` option should not be passed to cargo (cargo builds for the
+    /// host in that case). Like cargo, an empty `build.target`
+    /// (`build.target = []`) is treated as unset, so an empty vec is also
+    /// returned in that case. This is consistent with
+    /// [`build_target_for_config`](Self::build_target_for_config), which
+    /// returns the host triple in that case.
     #[allow(clippy::unnecessary_wraps)] // TODO: change in next breaking release?
     pub fn build_target_for_cli<I: IntoIterator<Item = S>, S: AsRef<str>>(
         &self,
@@ -301,17 +532,19 @@ impl Config {
     ) -> Result<Vec<String>> {
         let targets: Vec<_> = targets.into_iter().map(|t| t.as_ref().to_owned()).collect();
         if !targets.is_empty() {
-            return Ok(targets);
+            return Ok(dedup_targets(targets));
         }
         let config_targets = self.build.target.as_deref().unwrap_or_default();
         if !config_targets.is_empty() {
-            return Ok(config_targets.iter().map(|t| t.cli_target_string().into_owned()).collect());
+            return Ok(dedup_targets(
+                config_targets.iter().map(|t| t.cli_target_string().into_owned()).collect(),
+            ));
         }
         Ok(vec![])
     }
 
     fn init_target_config(&self, target: &TargetTripleRef<'_>) -> Result<()> {
-        let mut target_configs = self.target.borrow_mut();
+        let mut target_configs = self.target.lock();
         if !target_configs.contains_key(target.cli_target()) {
             let target_config = TargetConfig::from_unresolved(
                 de::Config::resolve_target(
@@ -335,13 +568,32 @@ impl Config {
     pub fn target<'a, T: Into<TargetTripleRef<'a>>>(&self, target: T) -> Result<TargetConfig> {
         let target = target.into();
         self.init_target_config(&target)?;
-        Ok(self.target.borrow()[target.cli_target()].clone())
+        Ok(self.target.lock()[target.cli_target()].clone())
+    }
+    /// Returns the keys of the `[target]` table declared in config.
+    ///
+    /// The returned keys are target triples (`target.<triple>`) or `cfg`
+    /// expressions (`target.<cfg>`) as written in config, in sorted order.
+    /// Each key that is a target triple can be passed to
+    /// [`target`](Self::target) to get the resolved configuration.
+    pub fn configured_targets(&self) -> Vec<String> {
+        self.de_target.keys().cloned().collect()
     }
     /// Returns the resolved linker path for the given target.
+    ///
+    /// The linker is selected in the following order of priority, like cargo:
+    ///
+    /// 1. `CARGO_TARGET_<triple>_LINKER` environment variable
+    /// 2. `target.<triple>.linker` config
+    /// 3. `target.<cfg>.linker` config
+    ///
+    /// Returns `None` if none of the above is set. In that case, rustc uses
+    /// its default linker (usually `cc`) and this library does not try to
+    /// emulate the `CC` environment variable or other heuristics.
     pub fn linker<'a, T: Into<TargetTripleRef<'a>>>(&self, target: T) -> Result<Option<PathBuf>> {
         let target = target.into();
         self.init_target_config(&target)?;
-        Ok(self.target.borrow()[target.cli_target()].linker.clone())
+        Ok(self.target.lock()[target.cli_target()].linker.clone())
     }
     /// Returns the resolved runner path and args for the given target.
     pub fn runner<'a, T: Into<TargetTripleRef<'a>>>(
@@ -350,13 +602,382 @@ impl Config {
     ) -> Result<Option<PathAndArgs>> {
         let target = target.into();
         self.init_target_config(&target)?;
-        Ok(self.target.borrow()[target.cli_target()].runner.clone())
+        Ok(self.target.lock()[target.cli_target()].runner.clone())
+    }
+    /// Returns the name and value of the `CARGO_TARGET_<triple>_RUNNER`
+    /// environment variable that makes cargo use the resolved runner for the
+    /// given target.
+    ///
+    /// The value is the runner path and args joined with spaces, which is the
+    /// format cargo expects for this environment variable. Returns `None` if no
+    /// runner is set.
+    ///
+    /// Returns an error if the path or any of the args is not valid UTF-8 or
+    /// contains whitespace, because such runners cannot be represented as a
+    /// space-separated string.
+    pub fn target_runner_env<'a, T: Into<TargetTripleRef<'a>>>(
+        &self,
+        target: T,
+    ) -> Result<Option<(String, String)>> {
+        let target = target.into();
+        let Some(runner) = self.runner(&target)? else { return Ok(None) };
+        let key = format!("CARGO_TARGET_{}_RUNNER", de::target_u_upper(target.triple()));
+        let mut value = String::new();
+        for part in
+            Some(runner.path.as_os_str()).into_iter().chain(runner.args.iter().map(|a| &**a))
+        {
+            let Some(part) = part.to_str() else {
+                bail!("runner for `{}` is not valid UTF-8: {part:?}", target.triple());
+            };
+            if part.contains(char::is_whitespace) {
+                bail!("runner for `{}` must not contain whitespace: {part:?}", target.triple());
+            }
+            if !value.is_empty() {
+                value.push(' ');
+            }
+            value.push_str(part);
+        }
+        Ok(Some((key, value)))
+    }
+    /// Returns the name and value of the `CARGO_TARGET_<triple>_LINKER`
+    /// environment variable that makes cargo use the resolved linker for the
+    /// given target.
+    ///
+    /// The value is the resolved linker path. Unlike the runner, the linker is
+    /// not split by spaces, so the path may contain whitespace. Returns `None`
+    /// if no linker is set.
+    ///
+    /// Returns an error if the path is not valid UTF-8.
+    pub fn target_linker_env<'a, T: Into<TargetTripleRef<'a>>>(
+        &self,
+        target: T,
+    ) -> Result<Option<(String, String)>> {
+        let target = target.into();
+        let Some(linker) = self.linker(&target)? else { return Ok(None) };
+        let key = format!("CARGO_TARGET_{}_LINKER", de::target_u_upper(target.triple()));
+        let Some(value) = linker.to_str() else {
+            bail!("linker for `{}` is not valid UTF-8: {linker:?}", target.triple());
+        };
+        Ok(Some((key, value.to_owned())))
+    }
+    /// Returns the environment variables that express this config.
+    ///
+    /// This is the inverse of [`de::Config::apply_env`]: applying the returned
+    /// environment variables to an empty config (e.g., by loading config with
+    /// them set by [`ResolveOptions::env`] in a directory without config files)
+    /// reproduces this config. This is useful to pass the resolved config to a
+    /// child cargo process so that it reaches the same decisions.
+    ///
+    /// The variables use the `CARGO_*` names cargo reads (e.g.,
+    /// `CARGO_BUILD_RUSTC` instead of `RUSTC`), except for `CARGO_ENCODED_RUSTFLAGS`
+    /// and `CARGO_ENCODED_RUSTDOCFLAGS` which are used if the flags were set by
+    /// the environment variables that override target-specific flags, and
+    /// `BROWSER` for `doc.browser`.
+    ///
+    /// Target-specific values (`CARGO_TARGET_<triple>_*`) are included for each
+    /// target triple in the `[target]` table and each target that has already
+    /// been resolved by [`target`](Self::target) or other target-specific
+    /// methods. Resolving them may spawn `rustc` to evaluate `cfg(...)`.
+    ///
+    /// The following cannot be expressed by environment variables and are not
+    /// included: the `[env]` table, the `[net.ssh]` table, and `target.<cfg>`
+    /// tables (other than through the resolved target-specific values).
+    ///
+    /// **Note:** Cargo appends `CARGO_TARGET_<triple>_RUSTFLAGS` to the
+    /// rustflags in config instead of replacing them, so if the child reads the
+    /// same config files, the target-specific rustflags are applied twice.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a value is not valid UTF-8 or cannot be represented
+    /// as an environment variable (e.g., multiple targets in `build.target`, or
+    /// a space-separated value with an element containing whitespace), or if
+    /// resolving a target fails.
+    pub fn to_env_vars(&self) -> Result<Vec<(String, String)>> {
+        fn to_str<'a>(key: &str, v: &'a Path) -> Result<&'a str> {
+            v.to_str().ok_or_else(|| format_err!("`{key}` is not valid UTF-8: {v:?}"))
+        }
+        fn space_separated(
+            key: &str,
+            parts: impl IntoIterator<Item = impl AsRef<OsStr>>,
+        ) -> Result<String> {
+            let mut value = String::new();
+            for part in parts {
+                let part = part.as_ref();
+                let Some(part) = part.to_str() else {
+                    bail!("`{key}` is not valid UTF-8: {part:?}");
+                };
+                if part.is_empty() || part.contains(char::is_whitespace) {
+                    bail!("`{key}` must not contain empty element or whitespace: {part:?}");
+                }
+                if !value.is_empty() {
+                    value.push(' ');
+                }
+                value.push_str(part);
+            }
+            Ok(value)
+        }
+        fn path_and_args(key: &str, v: &PathAndArgs) -> Result<String> {
+            space_separated(key, iter::once(v.path.as_os_str()).chain(v.args.iter().map(|a| &**a)))
+        }
+        fn flags(key: &str, v: &Flags) -> Result<String> {
+            v.encode_space_separated().with_context(|| format!("failed to encode `{key}`"))
+        }
+
+        let mut vars = vec![];
+        let mut push = |k: String, v: String| vars.push((k, v));
+        for (k, v) in &self.alias {
+            let key = format!("CARGO_ALIAS_{k}");
+            let value = space_separated(&key, v.list.iter().map(OsStr::new))?;
+            push(key, value);
+        }
+
+        let build = &self.build;
+        if let Some(jobs) = build.jobs {
+            let jobs = match jobs {
+                Jobs::Default => "default".to_owned(),
+                Jobs::Count(n) => n.to_string(),
+            };
+            push("CARGO_BUILD_JOBS".to_owned(), jobs);
+        }
+        for (key, v) in [
+            ("CARGO_BUILD_RUSTC", &build.rustc),
+            ("CARGO_BUILD_RUSTC_WRAPPER", &build.rustc_wrapper),
+            ("CARGO_BUILD_RUSTC_WORKSPACE_WRAPPER", &build.rustc_workspace_wrapper),
+            ("CARGO_BUILD_RUSTDOC", &build.rustdoc),
+        ] {
+            if let Some(v) = v {
+                push(key.to_owned(), to_str(key, v)?.to_owned());
+            }
+        }
+        if let Some(target) = &build.target {
+            match &**target {
+                [] => {}
+                [target] => {
+                    push("CARGO_BUILD_TARGET".to_owned(), target.cli_target_string().into_owned());
+                }
+                _ => bail!("`CARGO_BUILD_TARGET` cannot represent multiple targets: {target:?}"),
+            }
+        }
+        for (key, v) in [
+            ("CARGO_BUILD_TARGET_DIR", &build.target_dir),
+            ("CARGO_BUILD_BUILD_DIR", &build.build_dir),
+        ] {
+            if let Some(v) = v {
+                push(key.to_owned(), to_str(key, v)?.to_owned());
+            }
+        }
+        for (override_target, encoded_key, key, v) in [
+            (
+                build.override_target_rustflags,
+                "CARGO_ENCODED_RUSTFLAGS",
+                "CARGO_BUILD_RUSTFLAGS",
+                &build.rustflags,
+            ),
+            (
+                build.override_target_rustdocflags,
+                "CARGO_ENCODED_RUSTDOCFLAGS",
+                "CARGO_BUILD_RUSTDOCFLAGS",
+                &build.rustdocflags,
+            ),
+        ] {
+            match v {
+                Some(v) if override_target => push(encoded_key.to_owned(), v.encode()?),
+                Some(v) => push(key.to_owned(), flags(key, v)?),
+                None => {}
+            }
+        }
+        if let Some(incremental) = build.incremental {
+            push("CARGO_BUILD_INCREMENTAL".to_owned(), incremental.to_string());
+        }
+        if let Some(v) = &build.dep_info_basedir {
+            let key = "CARGO_BUILD_DEP_INFO_BASEDIR";
+            push(key.to_owned(), to_str(key, v)?.to_owned());
+        }
+        if let Some(warnings) = build.warnings {
+            push("CARGO_BUILD_WARNINGS".to_owned(), warnings.as_str().to_owned());
+        }
+
+        if let Some(browser) = &self.doc.browser {
+            push("BROWSER".to_owned(), path_and_args("BROWSER", browser)?);
+        }
+        if let Some(frequency) = self.future_incompat_report.frequency {
+            let key = "CARGO_FUTURE_INCOMPAT_REPORT_FREQUENCY";
+            push(key.to_owned(), frequency.as_str().to_owned());
+        }
+        if let Some(vcs) = self.cargo_new.vcs {
+            push("CARGO_CARGO_NEW_VCS".to_owned(), vcs.as_str().to_owned());
+        }
+
+        let http = &self.http;
+        for (key, v) in [
+            ("CARGO_HTTP_DEBUG", http.debug.map(|v| v.to_string())),
+            ("CARGO_HTTP_PROXY", http.proxy.clone()),
+            ("CARGO_HTTP_TIMEOUT", http.timeout.map(|v| v.to_string())),
+            ("CARGO_HTTP_CAINFO", http.cainfo.clone()),
+            ("CARGO_HTTP_CHECK_REVOKE", http.check_revoke.map(|v| v.to_string())),
+            ("CARGO_HTTP_LOW_SPEED_LIMIT", http.low_speed_limit.map(|v| v.to_string())),
+            ("CARGO_HTTP_MULTIPLEXING", http.multiplexing.map(|v| v.to_string())),
+            ("CARGO_HTTP_USER_AGENT", http.user_agent.clone()),
+            ("CARGO_NET_RETRY", self.net.retry.map(|v| v.to_string())),
+            ("CARGO_NET_GIT_FETCH_WITH_CLI", self.net.git_fetch_with_cli.map(|v| v.to_string())),
+            ("CARGO_NET_OFFLINE", self.net.offline.map(|v| v.to_string())),
+        ] {
+            if let Some(v) = v {
+                push(key.to_owned(), v);
+            }
+        }
+
+        for (name, registry) in &self.registries {
+            // https://doc.rust-lang.org/nightly/cargo/reference/config.html#environment-variables
+            let name = registry_env_key(name);
+            if let Some(index) = &registry.index {
+                push(format!("CARGO_REGISTRIES_{name}_INDEX"), index.clone());
+            }
+            if let Some(token) = &registry.token {
+                push(format!("CARGO_REGISTRIES_{name}_TOKEN"), token.clone());
+            }
+            if let Some(protocol) = &registry.protocol {
+                let protocol = match protocol {
+                    RegistriesProtocol::Git => "git",
+                    RegistriesProtocol::Sparse => "sparse",
+                };
+                push(format!("CARGO_REGISTRIES_{name}_PROTOCOL"), protocol.to_owned());
+            }
+        }
+        if let Some(default) = &self.registry.default {
+            push("CARGO_REGISTRY_DEFAULT".to_owned(), default.clone());
+        }
+        if let Some(token) = &self.registry.token {
+            push("CARGO_REGISTRY_TOKEN".to_owned(), token.clone());
+        }
+
+        let term = &self.term;
+        for (key, v) in [
+            ("CARGO_TERM_QUIET", term.quiet.map(|v| v.to_string())),
+            ("CARGO_TERM_VERBOSE", term.verbose.map(|v| v.to_string())),
+            ("CARGO_TERM_COLOR", term.color.map(|v| v.as_str().to_owned())),
+            ("CARGO_TERM_PROGRESS_WHEN", term.progress.when.map(|v| v.as_str().to_owned())),
+            ("CARGO_TERM_PROGRESS_WIDTH", term.progress.width.map(|v| v.to_string())),
+        ] {
+            if let Some(v) = v {
+                push(key.to_owned(), v);
+            }
+        }
+
+        let mut targets: BTreeSet<TargetTriple> = self
+            .de_target
+            .keys()
+            .filter(|k| !k.starts_with("cfg("))
+            .map(|k| TargetTriple::from(k.clone()))
+            .collect();
+        targets.extend(self.target.lock().keys().map(|t| t.0.clone().into_owned()));
+        for target in &targets {
+            let target_config = self.target(target)?;
+            let target_u_upper = de::target_u_upper(target.triple());
+            if let Some((key, value)) = self.target_linker_env(target)? {
+                push(key, value);
+            }
+            if let Some((key, value)) = self.target_runner_env(target)? {
+                push(key, value);
+            }
+            // Target-specific flags that are the same as build.rustflags do not
+            // need to be set, and they are ignored if overridden.
+            for (override_target, name, v, build_v) in [
+                (
+                    build.override_target_rustflags,
+                    "RUSTFLAGS",
+                    &target_config.rustflags,
+                    &build.rustflags,
+                ),
+                (
+                    build.override_target_rustdocflags,
+                    "RUSTDOCFLAGS",
+                    &target_config.rustdocflags,
+                    &build.rustdocflags,
+                ),
+            ] {
+                if let Some(v) = v {
+                    if !override_target && v != build_v.as_ref().unwrap_or(&Flags::default()) {
+                        let key = format!("CARGO_TARGET_{target_u_upper}_{name}");
+                        let value = flags(&key, v)?;
+                        push(key, value);
+                    }
+                }
+            }
+        }
+
+        Ok(vars)
     }
     /// Returns the resolved rustflags for the given target.
     pub fn rustflags<'a, T: Into<TargetTripleRef<'a>>>(&self, target: T) -> Result<Option<Flags>> {
         let target = target.into();
         self.init_target_config(&target)?;
-        Ok(self.target.borrow()[target.cli_target()].rustflags.clone())
+        Ok(self.target.lock()[target.cli_target()].rustflags.clone())
+    }
+    /// Returns the base flags before target resolution.
+    ///
+    /// This is [`build.rustflags`](BuildConfig::rustflags) after applying the
+    /// environment variables (`CARGO_ENCODED_RUSTFLAGS`, `RUSTFLAGS`, and
+    /// `CARGO_BUILD_RUSTFLAGS`), and does not reflect `target.<triple>.rustflags`,
+    /// `target.<cfg>.rustflags`, or `CARGO_TARGET_<triple>_RUSTFLAGS`. Use
+    /// [`rustflags`](Self::rustflags) to get the flags cargo passes to rustc
+    /// for a target.
+    pub fn build_rustflags(&self) -> Option<&Flags> {
+        self.build.rustflags.as_ref()
+    }
+    /// Returns where the [resolved rustflags](Self::rustflags) for the given
+    /// target come from.
+    ///
+    /// Returns `None` if there are no rustflags for the given target.
+    ///
+    /// Rustflags from `target.<triple>.rustflags`, `CARGO_TARGET_<triple>_RUSTFLAGS`,
+    /// and matching `target.<cfg>.rustflags` are joined, like cargo. In that
+    /// case, this returns the first of them that has flags, in that order.
+    /// If [`ResolveOptions::combine_rustflags`] is enabled, `build.rustflags`
+    /// may also be appended to them.
+    pub fn rustflags_source<'a, T: Into<TargetTripleRef<'a>>>(
+        &self,
+        target: T,
+    ) -> Result<Option<RustflagsSource>> {
+        let target = target.into();
+        if self.rustflags(&target)?.is_none() {
+            return Ok(None);
+        }
+        if self.build.override_target_rustflags {
+            if self.cx.env.contains_key("CARGO_ENCODED_RUSTFLAGS") {
+                return Ok(Some(RustflagsSource::EncodedEnv));
+            }
+            return Ok(Some(RustflagsSource::Env));
+        }
+        if self.de_target.get(target.triple()).is_some_and(|t| t.rustflags.is_some()) {
+            return Ok(Some(RustflagsSource::TargetTriple));
+        }
+        let key = format!("CARGO_TARGET_{}_RUSTFLAGS", de::target_u_upper(target.triple()));
+        if self.cx.env.contains_key(&key) {
+            return Ok(Some(RustflagsSource::TargetEnv));
+        }
+        for (k, v) in &self.de_target {
+            if k.starts_with("cfg(")
+                && v.rustflags.is_some()
+                && self.cx.eval_cfg(k, &target, &self.build)?
+            {
+                return Ok(Some(RustflagsSource::TargetCfg(k.clone())));
+            }
+        }
+        Ok(Some(RustflagsSource::BuildConfig))
+    }
+    /// Returns the target information of the given target.
+    ///
+    /// Each element is a non-empty line of the output of
+    /// `rustc --print cfg --target <target>` (e.g., `unix` or
+    /// `target_os="linux"`), which is used to evaluate `cfg(...)` in
+    /// `[target.<cfg>]` tables. The result is cached.
+    ///
+    /// If the target is the host and [`ResolveOptions::host_cfgs`] is set,
+    /// this returns the values set by it.
+    pub fn target_cfgs<'a, T: Into<TargetTripleRef<'a>>>(&self, target: T) -> Result<Vec<String>> {
+        self.cx.target_cfgs(&target.into(), &self.build)
     }
     /// Returns the resolved rustdocflags for the given target.
     pub fn rustdocflags<'a, T: Into<TargetTripleRef<'a>>>(
@@ -365,7 +986,33 @@ impl Config {
     ) -> Result<Option<Flags>> {
         let target = target.into();
         self.init_target_config(&target)?;
-        Ok(self.target.borrow()[target.cli_target()].rustdocflags.clone())
+        Ok(self.target.lock()[target.cli_target()].rustdocflags.clone())
+    }
+    /// Returns the resolved rustflags for the given target, encoded in the
+    /// format of `CARGO_ENCODED_RUSTFLAGS`.
+    ///
+    /// This is the robust way to pass the resolved rustflags to a child cargo
+    /// process. Returns `None` if there are no flags.
+    ///
+    /// See [`Flags::encode`] for the errors returned when encoding.
+    pub fn rustflags_encoded<'a, T: Into<TargetTripleRef<'a>>>(
+        &self,
+        target: T,
+    ) -> Result<Option<String>> {
+        encode_flags(self.rustflags(target)?)
+    }
+    /// Returns the resolved rustdocflags for the given target, encoded in the
+    /// format of `CARGO_ENCODED_RUSTDOCFLAGS`.
+    ///
+    /// This is the robust way to pass the resolved rustdocflags to a child cargo
+    /// process. Returns `None` if there are no flags.
+    ///
+    /// See [`Flags::encode`] for the errors returned when encoding.
+    pub fn rustdocflags_encoded<'a, T: Into<TargetTripleRef<'a>>>(
+        &self,
+        target: T,
+    ) -> Result<Option<String>> {
+        encode_flags(self.rustdocflags(target)?)
     }
 
     /// Returns the path and args that calls `rustc`.
@@ -379,6 +1026,51 @@ impl Config {
     pub fn rustc(&self) -> &PathAndArgs {
         self.cx.rustc(&self.build)
     }
+    /// Returns the command that cargo uses to compile a crate.
+    ///
+    /// Like cargo, [`RUSTC_WORKSPACE_WRAPPER`](BuildConfig::rustc_workspace_wrapper)
+    /// is only applied if `is_workspace_member` is `true`.
+    /// [`RUSTC_WRAPPER`](BuildConfig::rustc_wrapper) is applied to all crates,
+    /// and if both are applied, `RUSTC_WRAPPER` wraps `RUSTC_WORKSPACE_WRAPPER`.
+    ///
+    /// Unlike [`rustc`](Self::rustc), this does not respect the `rustc` path
+    /// set by [`ResolveOptions::rustc`].
+    pub fn rustc_command_for(&self, is_workspace_member: bool) -> Command {
+        self.cx.rustc_for(&self.build, is_workspace_member).into()
+    }
+    /// Returns the effective path of the rustc wrapper.
+    ///
+    /// This is the value of the `RUSTC_WRAPPER` environment variable if it is
+    /// set, otherwise the value of
+    /// [`build.rustc-wrapper`](BuildConfig::rustc_wrapper) (`CARGO_BUILD_RUSTC_WRAPPER`).
+    /// Like cargo, setting it to an empty string disables the wrapper, and this
+    /// returns `None` in that case.
+    pub fn rustc_wrapper(&self) -> Option<&Path> {
+        self.build.rustc_wrapper.as_deref()
+    }
+    /// Returns the effective path of the rustc wrapper for workspace members.
+    ///
+    /// This is the value of the `RUSTC_WORKSPACE_WRAPPER` environment variable
+    /// if it is set, otherwise the value of
+    /// [`build.rustc-workspace-wrapper`](BuildConfig::rustc_workspace_wrapper)
+    /// (`CARGO_BUILD_RUSTC_WORKSPACE_WRAPPER`). Like cargo, setting it to an
+    /// empty string disables the wrapper, and this returns `None` in that case.
+    pub fn rustc_workspace_wrapper(&self) -> Option<&Path> {
+        self.build.rustc_workspace_wrapper.as_deref()
+    }
+    /// Returns a clone of this config without the lazily resolved caches.
+    ///
+    /// Unlike [`clone`](Clone::clone), the returned config does not share the
+    /// resolved `[target]` table, the results of `rustc --print cfg`, or the
+    /// toolchain versions with this config, and resolves them again when needed.
+    ///
+    /// This is useful when the clone should not rely on results cached by this
+    /// config. Note that `Config` is `Sync`, so a config can also be shared
+    /// across threads along with its caches.
+    #[must_use]
+    pub fn fresh_clone(&self) -> Self {
+        Self { target: Cache::default(), cx: self.cx.fresh_clone(), ..self.clone() }
+    }
     /// Returns the path to `cargo`.
     ///
     /// The returned path is the value of the `CARGO` environment variable if it is set. Otherwise, "cargo".
@@ -387,6 +1079,22 @@ impl Config {
     pub fn cargo(&self) -> &OsStr {
         &self.cx.cargo
     }
+    /// Returns the current directory used to load and resolve this config.
+    ///
+    /// Relative paths defined in environment variables or on the command line
+    /// are resolved against this directory.
+    pub fn current_dir(&self) -> &Path {
+        &self.cx.current_dir
+    }
+    /// Returns the `build.target` declared in config files.
+    ///
+    /// Unlike [`build_target_for_config`](Self::build_target_for_config) and
+    /// [`build_target_for_cli`](Self::build_target_for_cli), this only returns
+    /// the value from config files (after merge) and ignores both the
+    /// `CARGO_BUILD_TARGET` environment variable and the `--target` option.
+    pub fn config_build_target(&self) -> Option<&[TargetTriple]> {
+        self.config_build_target.as_deref()
+    }
     /// Returns the host triple.
     pub fn host_triple(&self) -> Result<&str> {
         self.cx.host_triple(&self.build)
@@ -408,12 +1116,306 @@ impl Config {
     pub fn rustc_version(&self) -> Result<RustcVersion> {
         self.cx.rustc_version(&self.build)
     }
+    /// Returns `true` if the [current rustc](Self::rustc) is a nightly or dev
+    /// toolchain, i.e., unstable features such as `-Z` flags are available.
+    ///
+    /// This is a shorthand for [`rustc_version()?.nightly`](RustcVersion::nightly).
+    ///
+    /// **Note:** This does not consider `RUSTC_BOOTSTRAP`.
+    pub fn is_nightly_rustc(&self) -> Result<bool> {
+        Ok(self.rustc_version()?.nightly)
+    }
     /// Returns the version of the [current cargo](Self::cargo).
     ///
     /// See also [`rustc_version`](Self::rustc_version).
     pub fn cargo_version(&self) -> Result<CargoVersion> {
         self.cx.cargo_version(&self.build)
     }
+    /// Returns the versions of the [current rustc](Self::rustc) and the
+    /// [current cargo](Self::cargo).
+    ///
+    /// This spawns each of `rustc -vV` and `cargo -vV` at most once. The
+    /// results (and the [host triple](Self::host_triple), which is derived
+    /// from the same output) are cached in this config, so subsequent calls to
+    /// this, [`rustc_version`](Self::rustc_version), [`cargo_version`](Self::cargo_version),
+    /// and [`host_triple`](Self::host_triple) do not spawn processes again.
+    pub fn toolchain_versions(&self) -> Result<(RustcVersion, CargoVersion)> {
+        // cargo_version fetches the host triple at the same time, so call it first.
+        let cargo_version = self.cargo_version()?;
+        Ok((self.rustc_version()?, cargo_version))
+    }
+    /// Returns the path to the target directory.
+    ///
+    /// This is [`build.target-dir`](BuildConfig::target_dir) if it is set
+    /// (`CARGO_TARGET_DIR` and `CARGO_BUILD_TARGET_DIR` environment variables
+    /// take precedence over config, like cargo). Otherwise, this is the `target`
+    /// directory in the given workspace root, which is the default of cargo.
+    ///
+    /// This library does not know the workspace root, so the caller needs to
+    /// pass it (e.g., the `workspace_root` field of `cargo metadata` output).
+    pub fn target_dir_or_default(&self, workspace_root: &Path) -> PathBuf {
+        match &self.build.target_dir {
+            Some(target_dir) => target_dir.clone(),
+            None => workspace_root.join("target"),
+        }
+    }
+    /// Returns an iterator over the config file paths that are read when
+    /// loading config with the current directory and `CARGO_HOME` of this config.
+    ///
+    /// If this config was created by [`load_with_options`](Self::load_with_options)
+    /// (or other `load*` functions), this yields the same files that were loaded,
+    /// in order of precedence, unless files were added or removed after loading.
+    /// The file system is not accessed until the iterator is consumed.
+    pub fn walk(&self) -> Walk<'_> {
+        let cwd = &self.cx.current_dir;
+        Walk(
+            walk::WalkInner::with_cargo_home(cwd, self.cx.cargo_home(cwd).map(Path::to_path_buf))
+                .exclude_cargo_home(!self.cx.include_cargo_home),
+        )
+    }
+    /// Returns the raw table of the given dotted key (e.g., `profile.release`),
+    /// including the tables and keys not modeled by this type.
+    ///
+    /// The values are not resolved: environment variables are not applied,
+    /// and relative paths are kept as is. See [`de::Config::get_table`] for
+    /// details.
+    ///
+    /// This is available with the `toml_edit` feature.
+    #[cfg(feature = "toml_edit")]
+    pub fn get_table(&self, key: &str) -> Result<Option<&dyn toml_edit::TableLike>> {
+        de::get_table(&self.raw, key)
+    }
+    /// Returns the path to where intermediate build artifacts are placed.
+    ///
+    /// This is [`build.build-dir`](BuildConfig::build_dir) if it is set. Otherwise,
+    /// this falls back to [`build.target-dir`](BuildConfig::target_dir), which
+    /// matches the behavior of cargo.
+    ///
+    /// `build.build-dir` is supported in Cargo 1.91+. With older cargo, intermediate
+    /// artifacts are always placed in the target directory, so you may want to
+    /// check [`cargo_version`](Self::cargo_version) before relying on this.
+    ///
+    /// Template variables in `build.build-dir` (e.g., `{workspace-root}`) are
+    /// not expanded; see [`BuildConfig::build_dir`] for details.
+    pub fn build_dir(&self) -> Option<&Path> {
+        self.build.build_dir.as_deref().or(self.build.target_dir.as_deref())
+    }
+    /// Returns how cargo handles warnings.
+    ///
+    /// This is [`build.warnings`](BuildConfig::warnings) if it is set, otherwise
+    /// the default value ([`Warnings::Warn`]).
+    pub fn build_warnings(&self) -> Warnings {
+        self.build.warnings.unwrap_or_default()
+    }
+    /// Returns [`build.jobs`](BuildConfig::jobs) formatted as the value of
+    /// `cargo --jobs`.
+    ///
+    /// Negative values are returned as is, so that the child cargo interprets
+    /// them relative to the number of logical CPUs. Returns `None` if the
+    /// number of jobs is not set or is `"default"`.
+    pub fn build_jobs_for_cli(&self) -> Option<String> {
+        match self.build.jobs? {
+            Jobs::Default => None,
+            Jobs::Count(jobs) => Some(jobs.to_string()),
+        }
+    }
+    /// Returns whether or not colored output is used in the terminal.
+    ///
+    /// This is [`term.color`](TermConfig::color) if it is set, otherwise the
+    /// default value ([`Color::Auto`]).
+    ///
+    /// With the `color` feature, the result can be converted into
+    /// `anstream::ColorChoice`.
+    pub fn effective_color(&self) -> Color {
+        self.term.color.unwrap_or_default()
+    }
+    /// Returns the verbosity of cargo's output, in the same way as cargo.
+    ///
+    /// `cli_quiet` is whether `--quiet` is passed, and `cli_verbose` is the
+    /// number of times `--verbose` is passed. If either is set, the command
+    /// line takes precedence over [`term.quiet`](TermConfig::quiet) and
+    /// [`term.verbose`](TermConfig::verbose).
+    ///
+    /// Returns an error if both quiet and verbose are requested on the command
+    /// line, or (when neither is set on the command line) both are `true` in
+    /// config.
+    pub fn verbosity(&self, cli_quiet: bool, cli_verbose: u8) -> Result<Verbosity> {
+        // https://github.com/rust-lang/cargo/blob/0.80.0/src/cargo/util/context/mod.rs
+        match (cli_verbose != 0, cli_quiet) {
+            (true, true) => bail!("cannot set both --verbose and --quiet"),
+            (true, false) => Ok(Verbosity::Verbose),
+            (false, true) => Ok(Verbosity::Quiet),
+            (false, false) => match (self.term.verbose, self.term.quiet) {
+                (Some(true), Some(true)) => {
+                    bail!("cannot set both `term.verbose` and `term.quiet`")
+                }
+                (Some(true), _) => Ok(Verbosity::Verbose),
+                (_, Some(true)) => Ok(Verbosity::Quiet),
+                _ => Ok(Verbosity::Normal),
+            },
+        }
+    }
+    /// Returns the width for the progress bar, in the same way as cargo.
+    ///
+    /// This is [`term.progress.width`](TermProgressConfig::width) if it is
+    /// set, otherwise the given width of the terminal. Detecting the width of
+    /// the terminal is left to the caller. Returns `None` if neither is known,
+    /// in which case cargo does not show the progress bar.
+    pub fn progress_width(&self, terminal_width: Option<u32>) -> Option<u32> {
+        // https://github.com/rust-lang/cargo/blob/0.80.0/src/cargo/util/progress.rs
+        self.term.progress.width.or(terminal_width)
+    }
+    /// Returns whether or not a notification should be displayed when a command
+    /// produces a future incompat report.
+    ///
+    /// This is `true` if [`future-incompat-report.frequency`](FutureIncompatReportConfig::frequency)
+    /// is [`Frequency::Always`] or not set (the default), and `false` if it is
+    /// [`Frequency::Never`].
+    pub fn should_show_future_incompat(&self) -> bool {
+        match self.future_incompat_report.frequency.unwrap_or_default() {
+            Frequency::Always => true,
+            Frequency::Never => false,
+        }
+    }
+    /// Returns the command to open documentation with `cargo doc --open`.
+    ///
+    /// This is [`doc.browser`](DocConfig::browser) if it is set, otherwise the
+    /// `BROWSER` environment variable. Returns `None` if neither is set, in
+    /// which case cargo uses the system default browser.
+    pub fn doc_browser_command(&self) -> Option<Command> {
+        self.doc.browser.as_ref().map(Command::from)
+    }
+    /// Returns the index URL of the registry with the given name.
+    ///
+    /// This is [`registries.<name>.index`](RegistriesConfigValue::index) if it
+    /// is set. If it is not set and `name` is `crates-io`, this returns the
+    /// built-in crates.io index URL according to
+    /// [`registries.crates-io.protocol`](RegistriesConfigValue::protocol)
+    /// (the sparse index by default, like Cargo 1.70+).
+    ///
+    /// Like the `CARGO_REGISTRIES_<name>_*` environment variables, `name` also
+    /// matches a registry whose name differs only in case and `-`/`_` (e.g.,
+    /// `my_registry` matches `registries.my-registry`) if there is no registry
+    /// with exactly that name.
+    ///
+    /// Returns an error if `name` is not a valid registry name.
+    pub fn registry_index(&self, name: &str) -> Result<Option<&str>> {
+        if name.is_empty() {
+            bail!("registry name cannot be empty");
+        }
+        if let Some(ch) = name.chars().find(|&ch| !ch.is_alphanumeric() && ch != '-' && ch != '_') {
+            bail!("invalid character `{ch}` in registry name: `{name}`");
+        }
+        let registry = self.registry(name);
+        if let Some(index) = registry.and_then(|r| r.index.as_deref()) {
+            return Ok(Some(index));
+        }
+        if name == "crates-io" {
+            return Ok(Some(match registry.and_then(|r| r.protocol.as_ref()) {
+                Some(RegistriesProtocol::Git) => CRATES_IO_GIT_INDEX,
+                _ => CRATES_IO_SPARSE_INDEX,
+            }));
+        }
+        Ok(None)
+    }
+    /// Returns the validated index URL of the registry with the given name, in
+    /// the form cargo uses to identify the registry source (e.g., in `Cargo.lock`).
+    ///
+    /// This is the [index URL](Self::registry_index) with the scheme prefix
+    /// cargo uses: sparse indexes keep the `sparse+` prefix, and git indexes
+    /// get the `registry+` prefix. For example, `https://github.com/rust-lang/crates.io-index`
+    /// is returned as `registry+https://github.com/rust-lang/crates.io-index`.
+    ///
+    /// This only performs a light check of the URL: the scheme must be
+    /// `http` or `https` for sparse indexes, and `http`, `https`, `ssh`, `git`,
+    /// or `file` for git indexes, and the URL must not be empty after the scheme
+    /// or contain whitespace.
+    ///
+    /// Returns an error if `name` is not a valid registry name, or if the index
+    /// URL is malformed.
+    pub fn registry_index_url(&self, name: &str) -> Result<Option<String>> {
+        const SPARSE_SCHEMES: &[&str] = &["http", "https"];
+        const GIT_SCHEMES: &[&str] = &["http", "https", "ssh", "git", "file"];
+        let Some(index) = self.registry_index(name)? else { return Ok(None) };
+        let (prefix, url, schemes) = match index.strip_prefix("sparse+") {
+            Some(url) => ("sparse+", url, SPARSE_SCHEMES),
+            None => ("registry+", index.strip_prefix("registry+").unwrap_or(index), GIT_SCHEMES),
+        };
+        let invalid =
+            |msg: &str| format_err!("invalid index URL `{index}` for registry `{name}`: {msg}");
+        let Some((scheme, rest)) = url.split_once("://") else {
+            return Err(invalid("missing scheme"));
+        };
+        if !schemes.contains(&scheme) {
+            return Err(invalid(&format!("unsupported scheme `{scheme}`")));
+        }
+        if rest.is_empty() || (scheme != "file" && rest.starts_with('/')) {
+            return Err(invalid("missing host"));
+        }
+        if url.contains(char::is_whitespace) {
+            return Err(invalid("contains whitespace"));
+        }
+        Ok(Some(format!("{prefix}{url}")))
+    }
+    /// Returns the authentication token for the registry with the given name.
+    ///
+    /// For `crates-io`, this is [`registry.token`](RegistryConfig::token)
+    /// (which can be overridden by the `CARGO_REGISTRY_TOKEN` environment
+    /// variable), like cargo. For other registries, this is
+    /// [`registries.<name>.token`](RegistriesConfigValue::token) (which can be
+    /// overridden by the `CARGO_REGISTRIES_<name>_TOKEN` environment variable).
+    ///
+    /// **Note:** This library does not read any values in the
+    /// [credentials](https://doc.rust-lang.org/nightly/cargo/reference/config.html#credentials)
+    /// file, and does not support
+    /// [credential providers](https://doc.rust-lang.org/nightly/cargo/reference/registry-authentication.html).
+    pub fn registry_token(&self, name: &str) -> Option<&str> {
+        if name == "crates-io" {
+            return self.registry.token.as_deref();
+        }
+        self.registry(name)?.token.as_deref()
+    }
+    fn registry(&self, name: &str) -> Option<&RegistriesConfigValue> {
+        // Registries that are only defined by environment variables are named
+        // after the environment variables, so also look them up by that name.
+        self.registries.get(name).or_else(|| {
+            let key = registry_env_key(name);
+            self.registries.iter().find(|(k, _)| registry_env_key(k) == key).map(|(_, v)| v)
+        })
+    }
+
+    /// Returns an iterator over the user-defined aliases in the
+    /// [`[alias]`](Self::alias) table.
+    ///
+    /// Note that cargo's built-in aliases (`b`, `c`, `d`, `r`, `t`, and `rm`)
+    /// are not included unless they are overridden in config.
+    ///
+    /// This includes aliases that are ignored by cargo because they are
+    /// shadowed by built-in subcommands; see [`shadowed_aliases`](Self::shadowed_aliases).
+    pub fn aliases(&self) -> impl Iterator<Item = (&str, &StringList)> {
+        self.alias.iter().map(|(k, v)| (k.as_str(), v))
+    }
+    /// Returns an iterator over the names of the user-defined aliases that
+    /// are ignored because they are shadowed by built-in subcommands (e.g.,
+    /// `alias.build`).
+    ///
+    /// Cargo warns about such aliases and runs the built-in subcommand instead.
+    pub fn shadowed_aliases(&self) -> impl Iterator<Item = &str> {
+        self.alias.keys().map(String::as_str).filter(|name| validate::is_shadowed_alias(name))
+    }
+    /// Returns the command-line arguments the given alias expands to.
+    ///
+    /// This only performs single-level expansion: if the first element of the
+    /// result is itself an alias, it is not expanded further.
+    ///
+    /// Returns `None` if `name` is not defined in the [`[alias]`](Self::alias)
+    /// table, or if it is [shadowed by a built-in subcommand](Self::shadowed_aliases).
+    pub fn alias_argv(&self, name: &str) -> Option<Vec<String>> {
+        if validate::is_shadowed_alias(name) {
+            return None;
+        }
+        self.alias.get(name).map(|v| v.list.clone())
+    }
 
     // TODO: add override instead?
     // /// Merges the given config into this config.
@@ -428,6 +1430,61 @@ impl Config {
     // }
 }
 
+/// Creates an empty config, without reading any config files or environment
+/// variables.
+///
+/// The current directory is `.` and `CARGO_HOME` is not used. This is useful
+/// for mocking a config in tests of code that takes a `&Config`.
+///
+/// **Note:** Like a loaded config, methods that need information about the
+/// toolchain (e.g., [`host_triple`](Config::host_triple) and
+/// [`rustc_version`](Config::rustc_version)) spawn `rustc` or `cargo`. Use
+/// [`Config::load_with_options`] with [`ResolveOptions`] to control this.
+impl Default for Config {
+    fn default() -> Self {
+        let cx = ResolveOptions::default()
+            .env::<_, OsString, OsString>([])
+            .cargo_home(None)
+            .into_context(PathBuf::from("."));
+        Self::empty(cx)
+    }
+}
+
+impl<'de> Deserialize<'de> for Config {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // The serialized form of the resolved config is also a valid unresolved
+        // config, so deserialize it as such and resolve it with an empty context.
+        let de = de::Config::deserialize(deserializer)?;
+        let cx = ResolveOptions::default()
+            .env::<_, OsString, OsString>([])
+            .cargo_home(None)
+            .into_context(PathBuf::from("."));
+        Self::from_unresolved(de, cx).map_err(D::Error::custom)
+    }
+}
+
+// Compares the resolved public fields only. The lazily resolved `[target]`
+// table and the resolve context are not compared.
+impl PartialEq for Config {
+    fn eq(&self, other: &Self) -> bool {
+        self.alias == other.alias
+            && self.build == other.build
+            && self.doc == other.doc
+            && self.env == other.env
+            && self.future_incompat_report == other.future_incompat_report
+            && self.cargo_new == other.cargo_new
+            && self.http == other.http
+            && self.net == other.net
+            && self.registries == other.registries
+            && self.registry == other.registry
+            && self.term == other.term
+    }
+}
+impl Eq for Config {}
+
 /// The `[build]` table.
 ///
 /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#build)
@@ -437,11 +1494,12 @@ impl Config {
 pub struct BuildConfig {
     /// Sets the maximum number of compiler processes to run in parallel.
     /// If negative, it sets the maximum number of compiler processes to the
-    /// number of logical CPUs plus provided value. Should not be 0.
+    /// number of logical CPUs plus provided value. Should not be 0. If set to
+    /// `"default"`, the default value (the number of logical CPUs) is used.
     ///
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#buildjobs)
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub jobs: Option<i32>,
+    pub jobs: Option<Jobs>,
     /// Sets the executable to use for `rustc`.
     ///
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#buildrustc)
@@ -485,6 +1543,19 @@ pub struct BuildConfig {
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#buildtarget)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub target_dir: Option<PathBuf>,
+    /// The path to where intermediate build artifacts are placed. The default if
+    /// not specified is the same as [`target_dir`](Self::target_dir).
+    ///
+    /// This is supported in Cargo 1.91+.
+    ///
+    /// **Note:** Template variables (`{workspace-root}`, `{cargo-cache-home}`, and
+    /// `{workspace-path-hash}`) are not expanded because they depend on the
+    /// workspace. A value starting with a template variable is kept as is, and
+    /// other values are resolved relative to the config directory as usual.
+    ///
+    /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#buildbuild-dir)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_dir: Option<PathBuf>,
     /// Extra command-line flags to pass to rustc. The value may be an array
     /// of strings or a space-separated string.
     ///
@@ -515,6 +1586,14 @@ pub struct BuildConfig {
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#builddep-info-basedir)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dep_info_basedir: Option<PathBuf>,
+    /// Controls how Cargo handles warnings.
+    ///
+    /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#buildwarnings)
+    ///
+    /// **Note:** You may want to use [`Config::build_warnings`] which returns
+    /// the default value if this is not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warnings: Option<Warnings>,
 
     // Resolve contexts. Completely ignored in serialization and deserialization.
     #[serde(skip)]
@@ -527,28 +1606,40 @@ pub struct BuildConfig {
     de_rustdocflags: Option<de::Flags>,
 }
 
+fn target_from_unresolved(target: &de::StringOrArray, current_dir: &Path) -> Vec<TargetTriple> {
+    target
+        .as_array_no_split()
+        .iter()
+        .map(|v| TargetTriple::new(v.val.clone().into(), v.definition.as_ref(), Some(current_dir)))
+        .collect()
+}
+
 impl BuildConfig {
     pub(crate) fn from_unresolved(de: de::BuildConfig, current_dir: &Path) -> Self {
         let jobs = de.jobs.map(|v| v.val);
         let rustc = de.rustc.map(|v| v.resolve_as_program_path(current_dir).into_owned());
-        let rustc_wrapper =
-            de.rustc_wrapper.map(|v| v.resolve_as_program_path(current_dir).into_owned());
-        let rustc_workspace_wrapper =
-            de.rustc_workspace_wrapper.map(|v| v.resolve_as_program_path(current_dir).into_owned());
+        // Setting wrapper to an empty string instructs cargo to not use a wrapper.
+        // We handle this for environment variables in ApplyEnv, and here for config.
+        let rustc_wrapper = de
+            .rustc_wrapper
+            .filter(|v| !v.val.is_empty())
+            .map(|v| v.resolve_as_program_path(current_dir).into_owned());
+        let rustc_workspace_wrapper = de
+            .rustc_workspace_wrapper
+            .filter(|v| !v.val.is_empty())
+            .map(|v| v.resolve_as_program_path(current_dir).into_owned());
         let rustdoc = de.rustdoc.map(|v| v.resolve_as_program_path(current_dir).into_owned());
-        let target = de.target.map(|t| {
-            t.as_array_no_split()
-                .iter()
-                .map(|v| {
-                    TargetTriple::new(
-                        v.val.clone().into(),
-                        v.definition.as_ref(),
-                        Some(current_dir),
-                    )
-                })
-                .collect()
-        });
+        let target = de.target.map(|t| target_from_unresolved(&t, current_dir));
         let target_dir = de.target_dir.map(|v| v.resolve_as_path(current_dir).into_owned());
+        let build_dir = de.build_dir.map(|v| {
+            // A path starting with a template variable (e.g., `{workspace-root}/build`)
+            // is absolute once cargo expands the template, so keep it as is.
+            if v.val.starts_with('{') {
+                PathBuf::from(v.val)
+            } else {
+                v.resolve_as_path(current_dir).into_owned()
+            }
+        });
         let de_rustflags = de.rustflags.clone();
         let rustflags =
             de.rustflags.map(|v| Flags { flags: v.flags.into_iter().map(|v| v.val).collect() });
@@ -558,6 +1649,7 @@ impl BuildConfig {
         let incremental = de.incremental.map(|v| v.val);
         let dep_info_basedir =
             de.dep_info_basedir.map(|v| v.resolve_as_path(current_dir).into_owned());
+        let warnings = de.warnings.map(|v| v.val);
         let override_target_rustflags = de.override_target_rustflags;
         let override_target_rustdocflags = de.override_target_rustdocflags;
         Self {
@@ -568,10 +1660,12 @@ impl BuildConfig {
             rustdoc,
             target,
             target_dir,
+            build_dir,
             rustflags,
             rustdocflags,
             incremental,
             dep_info_basedir,
+            warnings,
             override_target_rustflags,
             de_rustflags,
             override_target_rustdocflags,
@@ -580,11 +1674,34 @@ impl BuildConfig {
     }
 }
 
+// de_rustflags and de_rustdocflags are the unresolved forms of rustflags and
+// rustdocflags, so they are not compared.
+impl PartialEq for BuildConfig {
+    fn eq(&self, other: &Self) -> bool {
+        self.jobs == other.jobs
+            && self.rustc == other.rustc
+            && self.rustc_wrapper == other.rustc_wrapper
+            && self.rustc_workspace_wrapper == other.rustc_workspace_wrapper
+            && self.rustdoc == other.rustdoc
+            && self.target == other.target
+            && self.target_dir == other.target_dir
+            && self.build_dir == other.build_dir
+            && self.rustflags == other.rustflags
+            && self.rustdocflags == other.rustdocflags
+            && self.incremental == other.incremental
+            && self.dep_info_basedir == other.dep_info_basedir
+            && self.warnings == other.warnings
+            && self.override_target_rustflags == other.override_target_rustflags
+            && self.override_target_rustdocflags == other.override_target_rustdocflags
+    }
+}
+impl Eq for BuildConfig {}
+
 // https://github.com/rust-lang/cargo/blob/0.80.0/src/cargo/util/context/target.rs
 /// A `[target.<triple>]` or `[target.<cfg>]` table.
 ///
 /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#target)
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[non_exhaustive]
 pub struct TargetConfig {
@@ -606,7 +1723,11 @@ pub struct TargetConfig {
     /// [reference (`target.<cfg>.rustdocflags`)](https://doc.rust-lang.org/nightly/cargo/reference/config.html#targetcfgrustdocflags)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rustdocflags: Option<Flags>,
-    // TODO: links: https://doc.rust-lang.org/nightly/cargo/reference/config.html#targettriplelinks
+    /// The `[target.<triple>.<links>]` tables, keyed by the `links` name of the package.
+    ///
+    /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#targettriplelinks)
+    #[serde(flatten)]
+    pub links: BTreeMap<String, LinkOverride>,
 }
 
 impl TargetConfig {
@@ -623,14 +1744,80 @@ impl TargetConfig {
             de.rustflags.map(|v| Flags { flags: v.flags.into_iter().map(|v| v.val).collect() });
         let rustdocflags =
             de.rustdocflags.map(|v| Flags { flags: v.flags.into_iter().map(|v| v.val).collect() });
-        Self { linker, runner, rustflags, rustdocflags }
+        let links = de
+            .links
+            .into_iter()
+            .map(|(k, v)| (k, LinkOverride::from_unresolved(v, current_dir)))
+            .collect();
+        Self { linker, runner, rustflags, rustdocflags, links }
+    }
+}
+
+/// A `[target.<triple>.<links>]` table, which overrides the build script
+/// of the package that links the `<links>` library.
+///
+/// [reference](https://doc.rust-lang.org/nightly/cargo/reference/build-scripts.html#overriding-build-scripts)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub struct LinkOverride {
+    /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/build-scripts.html#rustc-link-lib)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rustc_link_lib: Vec<String>,
+    /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/build-scripts.html#rustc-link-search)
+    ///
+    /// Relative paths are resolved as [config-relative paths](https://doc.rust-lang.org/nightly/cargo/reference/config.html#config-relative-paths).
+    /// The `KIND=` prefix (e.g., `native=`) is preserved if present.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rustc_link_search: Vec<PathBuf>,
+    /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/build-scripts.html#rustc-flags)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rustc_flags: Option<String>,
+    /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/build-scripts.html#rustc-cfg)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rustc_cfg: Vec<String>,
+    /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/build-scripts.html#rustc-env)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub rustc_env: BTreeMap<String, String>,
+}
+
+impl LinkOverride {
+    fn from_unresolved(de: de::LinkOverride, current_dir: &Path) -> Self {
+        let values = |v: Option<Vec<Value<String>>>| -> Vec<String> {
+            v.unwrap_or_default().into_iter().map(|v| v.val).collect()
+        };
+        let rustc_link_search = de
+            .rustc_link_search
+            .unwrap_or_default()
+            .into_iter()
+            .map(|v| {
+                // https://doc.rust-lang.org/nightly/cargo/reference/build-scripts.html#rustc-link-search
+                // > The optional KIND may be one of dependency, crate, native, framework, or all.
+                if let Some((kind, path)) = v.val.split_once('=') {
+                    if matches!(kind, "dependency" | "crate" | "native" | "framework" | "all") {
+                        let path = Value { val: path.to_owned(), definition: v.definition };
+                        let mut s = OsString::from(format!("{kind}="));
+                        s.push(path.resolve_as_path(current_dir).as_os_str());
+                        return PathBuf::from(s);
+                    }
+                }
+                v.resolve_as_path(current_dir).into_owned()
+            })
+            .collect();
+        Self {
+            rustc_link_lib: values(de.rustc_link_lib),
+            rustc_link_search,
+            rustc_flags: de.rustc_flags.map(|v| v.val),
+            rustc_cfg: values(de.rustc_cfg),
+            rustc_env: de.rustc_env.into_iter().map(|(k, v)| (k, v.val)).collect(),
+        }
     }
 }
 
 /// The `[doc]` table.
 ///
 /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#doc)
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[non_exhaustive]
 pub struct DocConfig {
@@ -661,10 +1848,16 @@ pub struct EnvConfigValue {
     pub value: OsString,
     pub force: bool,
     pub relative: bool,
+    /// The location where this value was defined.
+    ///
+    /// This is `None` if the definition is unknown (e.g., the config was
+    /// created programmatically).
+    pub definition: Option<Definition>,
 }
 
 impl EnvConfigValue {
     fn from_unresolved(de: de::EnvConfigValue, current_dir: &Path) -> Self {
+        let definition = de.definition().cloned();
         if let de::EnvConfigValue::Table {
             force, relative: Some(Value { val: true, .. }), ..
         } = &de
@@ -674,21 +1867,64 @@ impl EnvConfigValue {
                 force: force.as_ref().is_some_and(|v| v.val),
                 // Since we resolved the value, it is no longer relative.
                 relative: false,
+                definition,
             };
         }
         match de {
             de::EnvConfigValue::Value(value) => {
-                Self { value: value.val.into(), force: false, relative: false }
+                Self { value: value.val.into(), force: false, relative: false, definition }
             }
             de::EnvConfigValue::Table { value, force, .. } => Self {
                 value: value.val.into(),
                 force: force.is_some_and(|v| v.val),
                 relative: false,
+                definition,
             },
         }
     }
+
+    /// Expands `${NAME}` references in the value using the given `lookup` function.
+    ///
+    /// References for which `lookup` returns `None` are left intact, and `$$`
+    /// is replaced with `$`. Expanded values are not expanded again.
+    ///
+    /// **Note:** Cargo does not expand references in the `[env]` table, so this
+    /// is not applied automatically and is intended for tools that want to
+    /// emulate it. If the value is not valid UTF-8, it is returned as is.
+    pub fn expand<F: Fn(&str) -> Option<OsString>>(&self, lookup: F) -> OsString {
+        let Some(mut rest) = self.value.to_str() else { return self.value.clone() };
+        let mut expanded = OsString::with_capacity(rest.len());
+        while let Some(pos) = rest.find('$') {
+            expanded.push(&rest[..pos]);
+            rest = &rest[pos..];
+            if let Some(r) = rest.strip_prefix("$$") {
+                expanded.push("$");
+                rest = r;
+            } else if let Some((name, r)) = rest.strip_prefix("${").and_then(|r| r.split_once('}'))
+            {
+                match lookup(name) {
+                    Some(v) => expanded.push(v),
+                    None => expanded.push(&rest[..name.len() + 3]),
+                }
+                rest = r;
+            } else {
+                expanded.push("$");
+                rest = &rest[1..];
+            }
+        }
+        expanded.push(rest);
+        expanded
+    }
 }
 
+// Like other resolved values, values are equivalent no matter where they're defined.
+impl PartialEq for EnvConfigValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value && self.force == other.force && self.relative == other.relative
+    }
+}
+impl Eq for EnvConfigValue {}
+
 impl Serialize for EnvConfigValue {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -707,7 +1943,7 @@ impl Serialize for EnvConfigValue {
             },
         }
         match self {
-            Self { value, force: false, relative: false } => {
+            Self { value, force: false, relative: false, .. } => {
                 EnvRepr::Value(value.to_string_lossy()).serialize(serializer)
             }
             Self { value, force, relative, .. } => EnvRepr::Table {
@@ -723,7 +1959,7 @@ impl Serialize for EnvConfigValue {
 /// The `[future-incompat-report]` table.
 ///
 /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#future-incompat-report)
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[non_exhaustive]
 pub struct FutureIncompatReportConfig {
@@ -744,7 +1980,7 @@ impl FutureIncompatReportConfig {
 /// The `[cargo-new]` table.
 ///
 /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#cargo-new)
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[non_exhaustive]
 pub struct CargoNewConfig {
@@ -764,7 +2000,7 @@ impl CargoNewConfig {
 /// The `[http]` table.
 ///
 /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#http)
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[non_exhaustive]
 pub struct HttpConfig {
@@ -841,7 +2077,7 @@ impl HttpConfig {
 /// The `[net]` table.
 ///
 /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#net)
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[non_exhaustive]
 pub struct NetConfig {
@@ -865,6 +2101,11 @@ pub struct NetConfig {
     /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#netoffline)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub offline: Option<bool>,
+    /// The `[net.ssh]` table.
+    ///
+    /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#netssh)
+    #[serde(skip_serializing_if = "NetSshConfig::is_empty")]
+    pub ssh: NetSshConfig,
 }
 
 impl NetConfig {
@@ -872,14 +2113,38 @@ impl NetConfig {
         let retry = de.retry.map(|v| v.val);
         let git_fetch_with_cli = de.git_fetch_with_cli.map(|v| v.val);
         let offline = de.offline.map(|v| v.val);
-        Self { retry, git_fetch_with_cli, offline }
+        let ssh = NetSshConfig::from_unresolved(de.ssh);
+        Self { retry, git_fetch_with_cli, offline, ssh }
+    }
+}
+
+/// The `[net.ssh]` table.
+///
+/// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#netssh)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub struct NetSshConfig {
+    /// A list of SSH host keys that should be accepted as valid when
+    /// connecting to Git hosts, in the same format as OpenSSH `known_hosts`
+    /// lines.
+    ///
+    /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#netsshknown-hosts)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub known_hosts: Option<StringList>,
+}
+
+impl NetSshConfig {
+    fn from_unresolved(de: de::NetSshConfig) -> Self {
+        let known_hosts = de.known_hosts.map(StringList::from_unresolved);
+        Self { known_hosts }
     }
 }
 
 /// A value of the `[registries]` table.
 ///
 /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#registries)
-#[derive(Clone, Default, Serialize)]
+#[derive(Clone, Default, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[non_exhaustive]
 pub struct RegistriesConfigValue {
@@ -932,7 +2197,7 @@ impl fmt::Debug for RegistriesConfigValue {
 /// The `[registry]` table.
 ///
 /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#registry)
-#[derive(Clone, Default, Serialize)]
+#[derive(Clone, Default, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[non_exhaustive]
 pub struct RegistryConfig {
@@ -977,7 +2242,7 @@ impl fmt::Debug for RegistryConfig {
 /// The `[term]` table.
 ///
 /// [reference](https://doc.rust-lang.org/nightly/cargo/reference/config.html#term)
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[non_exhaustive]
 pub struct TermConfig {
@@ -997,7 +2262,7 @@ pub struct TermConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub color: Option<Color>,
     #[serde(default)]
-    #[serde(skip_serializing_if = "TermProgressConfig::is_none")]
+    #[serde(skip_serializing_if = "TermProgressConfig::is_empty")]
     pub progress: TermProgressConfig,
 }
 
@@ -1011,7 +2276,7 @@ impl TermConfig {
     }
 }
 
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[non_exhaustive]
 pub struct TermProgressConfig {
@@ -1035,6 +2300,38 @@ impl TermProgressConfig {
     }
 }
 
+/// The verbosity of cargo's output, returned by [`Config::verbosity`].
+#[allow(clippy::exhaustive_enums)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verbosity {
+    /// Do not print cargo log messages (`--quiet`).
+    Quiet,
+    /// (default) Print cargo log messages.
+    Normal,
+    /// Use verbose output (`--verbose`).
+    Verbose,
+}
+
+/// The source of rustflags, returned by [`Config::rustflags_source`].
+///
+/// The variants are listed in order of priority.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RustflagsSource {
+    /// `CARGO_ENCODED_RUSTFLAGS` environment variable.
+    EncodedEnv,
+    /// `RUSTFLAGS` environment variable.
+    Env,
+    /// `target.<triple>.rustflags` config.
+    TargetTriple,
+    /// `CARGO_TARGET_<triple>_RUSTFLAGS` environment variable.
+    TargetEnv,
+    /// `target.<cfg>.rustflags` config. Contains the `cfg(...)` expression.
+    TargetCfg(String),
+    /// `build.rustflags` config (or `CARGO_BUILD_RUSTFLAGS` environment variable).
+    BuildConfig,
+}
+
 /// A representation of rustflags or rustdocflags.
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
 #[serde(transparent)]
@@ -1156,6 +2453,159 @@ impl Flags {
     pub fn push<S: Into<String>>(&mut self, flag: S) {
         self.flags.push(flag.into());
     }
+
+    /// Returns `true` if this and `other` are equivalent after normalization.
+    ///
+    /// Unlike `==`, which compares the flags exactly, this compares flags as
+    /// follows:
+    ///
+    /// 1. Options that take a value are merged with their value, so the
+    ///    separate form (`-C opt-level=3`), the joined form (`-Copt-level=3`),
+    ///    and the long form (`--codegen opt-level=3`, `--codegen=opt-level=3`)
+    ///    are equivalent. The options handled are `-C`/`--codegen`, `-Z`,
+    ///    `-L`, `-l`, `-A`/`--allow`, `-W`/`--warn`, `-D`/`--deny`,
+    ///    `-F`/`--forbid`, `--force-warn`, `--cap-lints`, `--cfg`, and
+    ///    `--check-cfg`. Other flags are compared as is.
+    /// 2. The relative order of independent flags is ignored. The order is
+    ///    only significant between:
+    ///    - `-C` or `-Z` options with the same key (the part before `=`),
+    ///      since later ones override or are appended to earlier ones, e.g.,
+    ///      `-C opt-level=1 -C opt-level=3` is not equivalent to
+    ///      `-C opt-level=3 -C opt-level=1`.
+    ///    - lint options (`-A`, `-W`, `-D`, `-F`, `--force-warn`, and
+    ///      `--cap-lints`), since the lint levels depend on their order.
+    ///    - library search paths and linked libraries (`-L` and `-l`), since
+    ///      the linker resolves libraries in the order they are given.
+    ///    - identical flags, whose order does not matter anyway.
+    ///
+    /// Duplicate flags are not collapsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cargo_config2::Flags;
+    ///
+    /// let a = Flags::from(["-C", "opt-level=3", "-C", "debuginfo=2", "--cfg", "a"]);
+    /// let b = Flags::from(["--cfg=a", "-Cdebuginfo=2", "-Copt-level=3"]);
+    /// assert!(a.eq_normalized(&b));
+    /// assert_ne!(a, b);
+    ///
+    /// let a = Flags::from(["-C", "opt-level=1", "-C", "opt-level=3"]);
+    /// let b = Flags::from(["-C", "opt-level=3", "-C", "opt-level=1"]);
+    /// assert!(!a.eq_normalized(&b));
+    /// ```
+    #[must_use]
+    pub fn eq_normalized(&self, other: &Self) -> bool {
+        self.normalized() == other.normalized()
+    }
+
+    /// Returns an iterator over codegen options (`-C`/`--codegen` flags) as
+    /// key/value pairs.
+    ///
+    /// Both the separate form (`-C opt-level=3`, `--codegen opt-level=3`) and
+    /// the joined form (`-Copt-level=3`, `--codegen=opt-level=3`) are
+    /// supported. The value is `None` if the option has no `=` (e.g., `-C rpath`).
+    /// A trailing `-C` without a value is ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cargo_config2::Flags;
+    ///
+    /// let flags = Flags::from(["-C", "opt-level=3", "--cfg", "a", "-Ctarget-feature=+avx"]);
+    /// assert_eq!(flags.codegen_options().collect::<Vec<_>>(), [
+    ///     ("opt-level", Some("3")),
+    ///     ("target-feature", Some("+avx")),
+    /// ]);
+    /// ```
+    pub fn codegen_options(&self) -> impl Iterator<Item = (&str, Option<&str>)> + '_ {
+        let mut flags = self.flags.iter();
+        iter::from_fn(move || {
+            while let Some(flag) = flags.next() {
+                let option = if flag == "-C" || flag == "--codegen" {
+                    flags.next()?
+                } else if let Some(option) = flag.strip_prefix("--codegen=") {
+                    option
+                } else if let Some(option) = flag.strip_prefix("-C").filter(|v| !v.is_empty()) {
+                    option
+                } else {
+                    continue;
+                };
+                return Some(match option.split_once('=') {
+                    Some((key, value)) => (key, Some(value)),
+                    None => (option, None),
+                });
+            }
+            None
+        })
+    }
+
+    fn normalized(&self) -> Vec<(FlagGroup, String)> {
+        // (canonical name, alias, is lint option)
+        const VALUE_OPTIONS: &[(&str, Option<&str>, bool)] = &[
+            ("-C", Some("--codegen"), false),
+            ("-Z", None, false),
+            ("-L", None, false),
+            ("-l", None, false),
+            ("-A", Some("--allow"), true),
+            ("-W", Some("--warn"), true),
+            ("-D", Some("--deny"), true),
+            ("-F", Some("--forbid"), true),
+            ("--force-warn", None, true),
+            ("--cap-lints", None, true),
+            ("--cfg", None, false),
+            ("--check-cfg", None, false),
+        ];
+        fn value<'a>(
+            flag: &'a str,
+            name: &str,
+            rest: &mut slice::Iter<'a, String>,
+        ) -> Option<&'a str> {
+            if flag == name {
+                return rest.next().map(String::as_str);
+            }
+            let v = flag.strip_prefix(name)?;
+            if name.starts_with("--") {
+                v.strip_prefix('=')
+            } else {
+                Some(v).filter(|v| !v.is_empty())
+            }
+        }
+
+        let mut normalized = Vec::with_capacity(self.flags.len());
+        let mut flags = self.flags.iter();
+        'outer: while let Some(flag) = flags.next() {
+            for &(name, alias, is_lint) in VALUE_OPTIONS {
+                for name_or_alias in iter::once(name).chain(alias) {
+                    let Some(value) = value(flag, name_or_alias, &mut flags) else { continue };
+                    let group = if is_lint {
+                        FlagGroup::Lint
+                    } else if matches!(name, "-L" | "-l") {
+                        FlagGroup::Link
+                    } else if matches!(name, "-C" | "-Z") {
+                        let key = value.split_once('=').map_or(value, |(k, _)| k);
+                        FlagGroup::Other(format!("{name} {key}"))
+                    } else {
+                        FlagGroup::Other(format!("{name} {value}"))
+                    };
+                    normalized.push((group, format!("{name} {value}")));
+                    continue 'outer;
+                }
+            }
+            normalized.push((FlagGroup::Other(flag.clone()), flag.clone()));
+        }
+        // Stable sort keeps the relative order of flags in the same group.
+        normalized.sort_by(|(a, _), (b, _)| a.cmp(b));
+        normalized
+    }
+}
+
+/// A group of flags whose relative order is significant.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum FlagGroup {
+    Lint,
+    Link,
+    Other(String),
 }
 
 impl From<Vec<String>> for Flags {
@@ -1209,6 +2659,24 @@ impl PathAndArgs {
         self.args.extend(args.into_iter().map(Into::into));
         self
     }
+    /// Returns the program path and the arguments to pass to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::Path;
+    ///
+    /// use cargo_config2::PathAndArgs;
+    ///
+    /// let mut runner = PathAndArgs::new("qemu-aarch64");
+    /// runner.args(["-L", "/usr/aarch64-linux-gnu"]);
+    /// let (program, args) = runner.program_and_args();
+    /// assert_eq!(program, Path::new("qemu-aarch64"));
+    /// assert_eq!(args, ["-L", "/usr/aarch64-linux-gnu"]);
+    /// ```
+    pub fn program_and_args(&self) -> (&Path, &[OsString]) {
+        (&self.path, &self.args)
+    }
 }
 
 impl Serialize for PathAndArgs {
@@ -3,17 +3,96 @@
 // Environment variables are prefer over config values.
 // https://doc.rust-lang.org/nightly/cargo/reference/config.html#environment-variables
 
+use std::collections::BTreeMap;
+
 use crate::{
     de::{
         BuildConfig, CargoNewConfig, Config, DocConfig, Flags, FutureIncompatReportConfig,
         HttpConfig, NetConfig, PathAndArgs, RegistriesConfigValue, RegistryConfig, StringList,
         StringOrArray, TermConfig, TermProgress,
     },
-    error::{Context as _, Error, Result},
+    error::{Error, Result},
     resolve::ResolveContext,
     value::{Definition, Value},
 };
 
+/// Returns the `<name>` part of `CARGO_REGISTRIES_<name>_*` environment
+/// variables for the given registry name.
+///
+/// Like cargo, this is the registry name in uppercase with `-` replaced by
+/// `_`, so `CARGO_REGISTRIES_MY_REGISTRY_INDEX` refers to `registries.my-registry`.
+pub(crate) fn registry_env_key(name: &str) -> String {
+    name.to_ascii_uppercase().replace('-', "_")
+}
+
+/// Returns the registries that `CARGO_REGISTRIES_<name>_*` environment
+/// variables with the given `<name>` refer to.
+///
+/// If no such registry is defined, a registry with the name `<name>` in
+/// lowercase with `_` replaced by `-` (e.g., `my-registry` for `MY_REGISTRY`)
+/// is inserted.
+fn registries_for_env<'a>(
+    registries: &'a mut BTreeMap<String, RegistriesConfigValue>,
+    env_name: &'a str,
+) -> impl Iterator<Item = &'a mut RegistriesConfigValue> {
+    if !registries.keys().any(|name| registry_env_key(name) == env_name) {
+        registries.insert(
+            env_name.to_ascii_lowercase().replace('_', "-"),
+            RegistriesConfigValue::default(),
+        );
+    }
+    registries
+        .iter_mut()
+        .filter(move |(name, _)| registry_env_key(name) == env_name)
+        .map(|(_, v)| v)
+}
+
+/// Values set by `--config` arguments (see [`Config::with_cli_config`]).
+///
+/// Like cargo, `--config` arguments take precedence over the `CARGO_*`
+/// environment variables corresponding to config keys. Environment variables
+/// that are not config keys (e.g., `RUSTC`, `RUSTFLAGS`, and `CARGO_TARGET_DIR`)
+/// still take precedence over them.
+trait SetByCli {
+    fn set_by_cli(&self) -> bool;
+}
+impl<T> SetByCli for Value<T> {
+    fn set_by_cli(&self) -> bool {
+        matches!(self.definition, Some(Definition::Cli(..)))
+    }
+}
+impl SetByCli for Flags {
+    fn set_by_cli(&self) -> bool {
+        self.flags.iter().any(Value::set_by_cli)
+    }
+}
+impl SetByCli for StringList {
+    fn set_by_cli(&self) -> bool {
+        self.list.iter().any(Value::set_by_cli)
+    }
+}
+impl SetByCli for StringOrArray {
+    fn set_by_cli(&self) -> bool {
+        match self {
+            Self::String(s) => s.set_by_cli(),
+            Self::Array(v) => v.iter().any(Value::set_by_cli),
+        }
+    }
+}
+impl<T: SetByCli> SetByCli for Option<T> {
+    fn set_by_cli(&self) -> bool {
+        self.as_ref().is_some_and(T::set_by_cli)
+    }
+}
+
+/// Sets the value from an environment variable unless it was set by a
+/// `--config` argument.
+fn set<T: SetByCli>(slot: &mut Option<T>, value: T) {
+    if !slot.set_by_cli() {
+        *slot = Some(value);
+    }
+}
+
 pub(crate) trait ApplyEnv {
     /// Applies configuration environment variables.
     fn apply_env(&mut self, cx: &ResolveContext) -> Result<()>;
@@ -22,12 +101,21 @@ pub(crate) trait ApplyEnv {
 impl Config {
     /// Applies configuration environment variables.
     ///
+    /// Values set by environment variables take precedence over values in
+    /// config, like cargo. Values set by `--config` arguments (see
+    /// [`with_cli_config`](Self::with_cli_config)) are not overridden by the
+    /// `CARGO_*` environment variables corresponding to config keys. The environment variables are read from the given
+    /// context, which can be created by [`ResolveOptions::into_resolve_context`]
+    /// (or [`ResolveOptions::resolve_context`]) and reused for many configs.
+    ///
+    /// [`ResolveOptions::into_resolve_context`]: crate::ResolveOptions::into_resolve_context
+    /// [`ResolveOptions::resolve_context`]: crate::ResolveOptions::resolve_context
+    ///
     /// **Note:** This ignores environment variables for target-specific
-    /// configurations ([`self.target`](Self::target). This is because it is
+    /// configurations ([`self.target`](Self::target)). This is because it is
     /// difficult to determine exactly which target the target-specific
     /// configuration defined in the environment variables are for.
     /// (e.g., In environment variables, `-` and `.` in the target triple are replaced by `_`)
-    #[doc(hidden)] // Not public API.
     pub fn apply_env(&mut self, cx: &ResolveContext) -> Result<()> {
         for (k, v) in &cx.env {
             let definition = || Some(Definition::Environment(k.clone().into()));
@@ -36,6 +124,9 @@ impl Config {
 
             // https://doc.rust-lang.org/nightly/cargo/reference/config.html#alias
             if let Some(k) = k.strip_prefix("CARGO_ALIAS_") {
+                if self.alias.get(k).is_some_and(StringList::set_by_cli) {
+                    continue;
+                }
                 self.alias.insert(
                     k.to_owned(),
                     StringList::from_string(
@@ -49,28 +140,16 @@ impl Config {
             else if let Some(k) = k.strip_prefix("CARGO_REGISTRIES_") {
                 if let Some(k) = k.strip_suffix("_INDEX") {
                     let v = v.to_str().ok_or_else(error_env_not_unicode)?;
-                    let index = Some(Value { val: v.to_owned(), definition: definition() });
-                    if let Some(registries_config_value) = self.registries.get_mut(k) {
-                        registries_config_value.index = index;
-                    } else {
-                        self.registries.insert(k.to_owned(), RegistriesConfigValue {
-                            index,
-                            token: None,
-                            protocol: None,
-                        });
+                    let index = Value { val: v.to_owned(), definition: definition() };
+                    for registries_config_value in registries_for_env(&mut self.registries, k) {
+                        set(&mut registries_config_value.index, index.clone());
                     }
                     continue;
                 } else if let Some(k) = k.strip_suffix("_TOKEN") {
                     let v = v.to_str().ok_or_else(error_env_not_unicode_redacted)?;
-                    let token = Some(Value { val: v.to_owned(), definition: definition() });
-                    if let Some(registries_config_value) = self.registries.get_mut(k) {
-                        registries_config_value.token = token;
-                    } else {
-                        self.registries.insert(k.to_owned(), RegistriesConfigValue {
-                            index: None,
-                            token,
-                            protocol: None,
-                        });
+                    let token = Value { val: v.to_owned(), definition: definition() };
+                    for registries_config_value in registries_for_env(&mut self.registries, k) {
+                        set(&mut registries_config_value.token, token.clone());
                     }
                     continue;
                 } else if k == "CRATES_IO_PROTOCOL" {
@@ -79,7 +158,9 @@ impl Config {
                     let protocol =
                         Some(Value { val: v.to_owned(), definition: definition() }.parse()?);
                     if let Some(registries_config_value) = self.registries.get_mut(k) {
-                        registries_config_value.protocol = protocol;
+                        if !registries_config_value.protocol.set_by_cli() {
+                            registries_config_value.protocol = protocol;
+                        }
                     } else {
                         self.registries.insert(k.to_owned(), RegistriesConfigValue {
                             index: None,
@@ -97,6 +178,7 @@ impl Config {
         self.build.apply_env(cx)?;
         self.doc.apply_env(cx)?;
         self.future_incompat_report.apply_env(cx)?;
+        self.cargo_new.apply_env(cx)?;
         self.http.apply_env(cx)?;
         self.net.apply_env(cx)?;
         self.registry.apply_env(cx)?;
@@ -109,7 +191,7 @@ impl ApplyEnv for BuildConfig {
     fn apply_env(&mut self, cx: &ResolveContext) -> Result<()> {
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#buildjobs
         if let Some(jobs) = cx.env_parse("CARGO_BUILD_JOBS")? {
-            self.jobs = Some(jobs);
+            set(&mut self.jobs, jobs);
         }
 
         // The following priorities are not documented, but at as of cargo
@@ -121,7 +203,7 @@ impl ApplyEnv for BuildConfig {
         if let Some(rustc) = cx.env("RUSTC")? {
             self.rustc = Some(rustc);
         } else if let Some(rustc) = cx.env("CARGO_BUILD_RUSTC")? {
-            self.rustc = Some(rustc);
+            set(&mut self.rustc, rustc);
         }
         // 1. RUSTC_WRAPPER
         // 2. build.rustc-wrapper (CARGO_BUILD_RUSTC_WRAPPER)
@@ -135,10 +217,8 @@ impl ApplyEnv for BuildConfig {
                 self.rustc_wrapper = Some(rustc_wrapper);
             }
         } else if let Some(rustc_wrapper) = cx.env("CARGO_BUILD_RUSTC_WRAPPER")? {
-            if rustc_wrapper.val.is_empty() {
-                self.rustc_wrapper = None;
-            } else {
-                self.rustc_wrapper = Some(rustc_wrapper);
+            if !self.rustc_wrapper.set_by_cli() {
+                self.rustc_wrapper = Some(rustc_wrapper).filter(|v| !v.val.is_empty());
             }
         }
         // 1. RUSTC_WORKSPACE_WRAPPER
@@ -155,10 +235,9 @@ impl ApplyEnv for BuildConfig {
         } else if let Some(rustc_workspace_wrapper) =
             cx.env("CARGO_BUILD_RUSTC_WORKSPACE_WRAPPER")?
         {
-            if rustc_workspace_wrapper.val.is_empty() {
-                self.rustc_workspace_wrapper = None;
-            } else {
-                self.rustc_workspace_wrapper = Some(rustc_workspace_wrapper);
+            if !self.rustc_workspace_wrapper.set_by_cli() {
+                self.rustc_workspace_wrapper =
+                    Some(rustc_workspace_wrapper).filter(|v| !v.val.is_empty());
             }
         }
         // 1. RUSTDOC
@@ -167,12 +246,12 @@ impl ApplyEnv for BuildConfig {
         if let Some(rustdoc) = cx.env("RUSTDOC")? {
             self.rustdoc = Some(rustdoc);
         } else if let Some(rustdoc) = cx.env("CARGO_BUILD_RUSTDOC")? {
-            self.rustdoc = Some(rustdoc);
+            set(&mut self.rustdoc, rustdoc);
         }
 
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#buildtarget
         if let Some(target) = cx.env("CARGO_BUILD_TARGET")? {
-            self.target = Some(StringOrArray::String(target));
+            set(&mut self.target, StringOrArray::String(target));
         }
 
         // The following priorities are not documented, but at as of cargo
@@ -183,7 +262,12 @@ impl ApplyEnv for BuildConfig {
         if let Some(target_dir) = cx.env("CARGO_TARGET_DIR")? {
             self.target_dir = Some(target_dir);
         } else if let Some(target_dir) = cx.env("CARGO_BUILD_TARGET_DIR")? {
-            self.target_dir = Some(target_dir);
+            set(&mut self.target_dir, target_dir);
+        }
+
+        // https://doc.rust-lang.org/nightly/cargo/reference/config.html#buildbuild-dir
+        if let Some(build_dir) = cx.env("CARGO_BUILD_BUILD_DIR")? {
+            set(&mut self.build_dir, build_dir);
         }
 
         // 1. CARGO_ENCODED_RUSTFLAGS
@@ -201,8 +285,10 @@ impl ApplyEnv for BuildConfig {
                 Some(Flags::from_space_separated(&rustflags.val, rustflags.definition.as_ref()));
             self.override_target_rustflags = true;
         } else if let Some(rustflags) = cx.env("CARGO_BUILD_RUSTFLAGS")? {
-            self.rustflags =
-                Some(Flags::from_space_separated(&rustflags.val, rustflags.definition.as_ref()));
+            set(
+                &mut self.rustflags,
+                Flags::from_space_separated(&rustflags.val, rustflags.definition.as_ref()),
+            );
         }
         // 1. CARGO_ENCODED_RUSTDOCFLAGS
         // 2. RUSTDOCFLAGS
@@ -221,10 +307,10 @@ impl ApplyEnv for BuildConfig {
             ));
             self.override_target_rustdocflags = true;
         } else if let Some(rustdocflags) = cx.env("CARGO_BUILD_RUSTDOCFLAGS")? {
-            self.rustdocflags = Some(Flags::from_space_separated(
-                &rustdocflags.val,
-                rustdocflags.definition.as_ref(),
-            ));
+            set(
+                &mut self.rustdocflags,
+                Flags::from_space_separated(&rustdocflags.val, rustdocflags.definition.as_ref()),
+            );
         }
 
         // The following priorities are not documented, but at as of cargo
@@ -237,12 +323,17 @@ impl ApplyEnv for BuildConfig {
             self.incremental =
                 Some(Value { val: incremental.val == "1", definition: incremental.definition });
         } else if let Some(incremental) = cx.env_parse("CARGO_BUILD_INCREMENTAL")? {
-            self.incremental = Some(incremental);
+            set(&mut self.incremental, incremental);
         }
 
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#builddep-info-basedir
         if let Some(dep_info_basedir) = cx.env("CARGO_BUILD_DEP_INFO_BASEDIR")? {
-            self.dep_info_basedir = Some(dep_info_basedir);
+            set(&mut self.dep_info_basedir, dep_info_basedir);
+        }
+
+        // https://doc.rust-lang.org/nightly/cargo/reference/config.html#buildwarnings
+        if let Some(warnings) = cx.env_parse("CARGO_BUILD_WARNINGS")? {
+            set(&mut self.warnings, warnings);
         }
 
         Ok(())
@@ -255,10 +346,8 @@ impl ApplyEnv for DocConfig {
         // https://github.com/rust-lang/cargo/blob/0.80.0/src/cargo/ops/cargo_doc.rs#L143-L144
         if self.browser.is_none() {
             if let Some(browser) = cx.env("BROWSER")? {
-                self.browser = Some(
-                    PathAndArgs::from_string(&browser.val, browser.definition)
-                        .context("invalid length 0, expected at least one element")?,
-                );
+                // Empty BROWSER (or BROWSER with only whitespace) is treated as unset.
+                self.browser = PathAndArgs::from_string(&browser.val, browser.definition);
             }
         }
         Ok(())
@@ -269,7 +358,7 @@ impl ApplyEnv for FutureIncompatReportConfig {
     fn apply_env(&mut self, cx: &ResolveContext) -> Result<()> {
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#future-incompat-reportfrequency
         if let Some(frequency) = cx.env_parse("CARGO_FUTURE_INCOMPAT_REPORT_FREQUENCY")? {
-            self.frequency = Some(frequency);
+            set(&mut self.frequency, frequency);
         }
         Ok(())
     }
@@ -279,7 +368,7 @@ impl ApplyEnv for CargoNewConfig {
     fn apply_env(&mut self, cx: &ResolveContext) -> Result<()> {
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#cargo-newvcs
         if let Some(vcs) = cx.env_parse("CARGO_CARGO_NEW_VCS")? {
-            self.vcs = Some(vcs);
+            set(&mut self.vcs, vcs);
         }
         Ok(())
     }
@@ -289,39 +378,39 @@ impl ApplyEnv for HttpConfig {
     fn apply_env(&mut self, cx: &ResolveContext) -> Result<()> {
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#httpdebug
         if let Some(debug) = cx.env_parse("CARGO_HTTP_DEBUG")? {
-            self.debug = Some(debug);
+            set(&mut self.debug, debug);
         }
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#httpproxy
         // TODO:
         // > CARGO_HTTP_PROXY or HTTPS_PROXY or https_proxy or http_proxy
         if let Some(proxy) = cx.env("CARGO_HTTP_PROXY")? {
-            self.proxy = Some(proxy);
+            set(&mut self.proxy, proxy);
         }
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#httptimeout
         // TODO:
         // > CARGO_HTTP_TIMEOUT or HTTP_TIMEOUT
         if let Some(timeout) = cx.env_parse("CARGO_HTTP_TIMEOUT")? {
-            self.timeout = Some(timeout);
+            set(&mut self.timeout, timeout);
         }
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#httpcainfo
         if let Some(cainfo) = cx.env("CARGO_HTTP_CAINFO")? {
-            self.cainfo = Some(cainfo);
+            set(&mut self.cainfo, cainfo);
         }
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#httpcheck-revoke
         if let Some(check_revoke) = cx.env_parse("CARGO_HTTP_CHECK_REVOKE")? {
-            self.check_revoke = Some(check_revoke);
+            set(&mut self.check_revoke, check_revoke);
         }
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#httplow-speed-limit
         if let Some(low_speed_limit) = cx.env_parse("CARGO_HTTP_LOW_SPEED_LIMIT")? {
-            self.low_speed_limit = Some(low_speed_limit);
+            set(&mut self.low_speed_limit, low_speed_limit);
         }
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#httpmultiplexing
         if let Some(multiplexing) = cx.env_parse("CARGO_HTTP_MULTIPLEXING")? {
-            self.multiplexing = Some(multiplexing);
+            set(&mut self.multiplexing, multiplexing);
         }
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#httpuser-agent
         if let Some(user_agent) = cx.env("CARGO_HTTP_USER_AGENT")? {
-            self.user_agent = Some(user_agent);
+            set(&mut self.user_agent, user_agent);
         }
         Ok(())
     }
@@ -331,16 +420,18 @@ impl ApplyEnv for NetConfig {
     fn apply_env(&mut self, cx: &ResolveContext) -> Result<()> {
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#netretry
         if let Some(retry) = cx.env_parse("CARGO_NET_RETRY")? {
-            self.retry = Some(retry);
+            set(&mut self.retry, retry);
         }
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#netgit-fetch-with-cli
         if let Some(git_fetch_with_cli) = cx.env_parse("CARGO_NET_GIT_FETCH_WITH_CLI")? {
-            self.git_fetch_with_cli = Some(git_fetch_with_cli);
+            set(&mut self.git_fetch_with_cli, git_fetch_with_cli);
         }
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#netoffline
         if let Some(offline) = cx.env_parse("CARGO_NET_OFFLINE")? {
-            self.offline = Some(offline);
+            set(&mut self.offline, offline);
         }
+        // net.ssh.known-hosts is not read from the environment variable by cargo:
+        // https://doc.rust-lang.org/nightly/cargo/reference/config.html#netsshknown-hosts
         Ok(())
     }
 }
@@ -349,11 +440,11 @@ impl ApplyEnv for RegistryConfig {
     fn apply_env(&mut self, cx: &ResolveContext) -> Result<()> {
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#registrydefault
         if let Some(default) = cx.env("CARGO_REGISTRY_DEFAULT")? {
-            self.default = Some(default);
+            set(&mut self.default, default);
         }
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#registrytoken
         if let Some(token) = cx.env_redacted("CARGO_REGISTRY_TOKEN")? {
-            self.token = Some(token);
+            set(&mut self.token, token);
         }
         Ok(())
     }
@@ -363,15 +454,15 @@ impl ApplyEnv for TermConfig {
     fn apply_env(&mut self, cx: &ResolveContext) -> Result<()> {
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#termquiet
         if let Some(quiet) = cx.env_parse("CARGO_TERM_QUIET")? {
-            self.quiet = Some(quiet);
+            set(&mut self.quiet, quiet);
         }
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#termverbose
         if let Some(verbose) = cx.env_parse("CARGO_TERM_VERBOSE")? {
-            self.verbose = Some(verbose);
+            set(&mut self.verbose, verbose);
         }
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#termcolor
         if let Some(color) = cx.env_parse("CARGO_TERM_COLOR")? {
-            self.color = Some(color);
+            set(&mut self.color, color);
         }
         self.progress.apply_env(cx)?;
         Ok(())
@@ -382,11 +473,11 @@ impl ApplyEnv for TermProgress {
     fn apply_env(&mut self, cx: &ResolveContext) -> Result<()> {
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#termprogresswhen
         if let Some(when) = cx.env_parse("CARGO_TERM_PROGRESS_WHEN")? {
-            self.when = Some(when);
+            set(&mut self.when, when);
         }
         // https://doc.rust-lang.org/nightly/cargo/reference/config.html#termprogresswidth
         if let Some(width) = cx.env_parse("CARGO_TERM_PROGRESS_WIDTH")? {
-            self.width = Some(width);
+            set(&mut self.width, width);
         }
         Ok(())
     }
@@ -399,15 +490,45 @@ mod tests {
 
     #[test]
     fn empty_string_wrapper_envs() {
-        let env_list = [("RUSTC_WRAPPER", ""), ("RUSTC_WORKSPACE_WRAPPER", "")];
+        for env_list in [[("RUSTC_WRAPPER", ""), ("RUSTC_WORKSPACE_WRAPPER", "")], [
+            ("CARGO_BUILD_RUSTC_WRAPPER", ""),
+            ("CARGO_BUILD_RUSTC_WORKSPACE_WRAPPER", ""),
+        ]] {
+            let mut config = crate::de::BuildConfig::default();
+            let cx = &ResolveOptions::default()
+                .env(env_list)
+                .into_context(std::env::current_dir().unwrap());
+            config.rustc_wrapper =
+                Some(Value { val: "rustc_wrapper".to_owned(), definition: None });
+            config.rustc_workspace_wrapper =
+                Some(Value { val: "rustc_workspace_wrapper".to_owned(), definition: None });
+            config.apply_env(cx).unwrap();
+            assert!(config.rustc_wrapper.is_none(), "{env_list:?}");
+            assert!(config.rustc_workspace_wrapper.is_none(), "{env_list:?}");
+
+            // Also disabled when not set in config.
+            let mut config = crate::de::BuildConfig::default();
+            config.apply_env(cx).unwrap();
+            assert!(config.rustc_wrapper.is_none(), "{env_list:?}");
+            assert!(config.rustc_workspace_wrapper.is_none(), "{env_list:?}");
+        }
+    }
+
+    #[test]
+    fn wrapper_envs_precedence() {
+        // RUSTC_*WRAPPER is preferred over CARGO_BUILD_RUSTC_*WRAPPER, even if empty.
+        let env_list = [
+            ("RUSTC_WRAPPER", ""),
+            ("CARGO_BUILD_RUSTC_WRAPPER", "a"),
+            ("RUSTC_WORKSPACE_WRAPPER", "b"),
+            ("CARGO_BUILD_RUSTC_WORKSPACE_WRAPPER", ""),
+        ];
         let mut config = crate::de::BuildConfig::default();
         let cx =
             &ResolveOptions::default().env(env_list).into_context(std::env::current_dir().unwrap());
         config.rustc_wrapper = Some(Value { val: "rustc_wrapper".to_owned(), definition: None });
-        config.rustc_workspace_wrapper =
-            Some(Value { val: "rustc_workspace_wrapper".to_owned(), definition: None });
         config.apply_env(cx).unwrap();
         assert!(config.rustc_wrapper.is_none());
-        assert!(config.rustc_workspace_wrapper.is_none());
+        assert_eq!(config.rustc_workspace_wrapper.unwrap().val, "b");
     }
 }
@@ -1,6 +1,11 @@
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
-use std::{ffi::OsString, fmt, io};
+use core::ops::Range;
+use std::{
+    ffi::OsString,
+    fmt, io,
+    path::{Path, PathBuf},
+};
 
 macro_rules! format_err {
     ($($tt:tt)*) => {
@@ -29,9 +34,12 @@ pub(crate) enum ErrorKind {
     Io(io::Error),
 
     CfgExprParse(crate::cfg_expr::error::ParseError),
+    ParseConfig(PathBuf, toml_edit::de::Error),
 
     Other(String),
     WithContext(String, Option<Box<dyn std::error::Error + Send + Sync + 'static>>),
+    /// Failed to merge the value of the dotted key.
+    MergeKey(String, Box<Error>),
 }
 
 impl Error {
@@ -51,6 +59,44 @@ impl Error {
             Some("environment variable was not valid unicode: [REDACTED]".into()),
         ))
     }
+    pub(crate) fn parse_config(path: &Path, e: toml_edit::de::Error) -> Self {
+        Self(ErrorKind::ParseConfig(path.to_owned(), e))
+    }
+    /// Adds the key that failed to merge to the error.
+    ///
+    /// The keys of nested tables are joined into a single dotted key, so the
+    /// error reads "failed to merge key `build.rustflags`" instead of
+    /// reporting each level separately.
+    pub(crate) fn merge_key(self, key: &str) -> Self {
+        match self.0 {
+            ErrorKind::MergeKey(path, source) => {
+                Self(ErrorKind::MergeKey(format!("{key}.{path}"), source))
+            }
+            _ => Self(ErrorKind::MergeKey(key.to_owned(), Box::new(self))),
+        }
+    }
+
+    /// Returns the byte range in the config file where the error occurred, if
+    /// this error (or its cause) is a failure to parse a config file and the
+    /// location is known.
+    ///
+    /// The range is relative to the contents of the file returned by
+    /// [`config_path`](Self::config_path).
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.parse_config_error()?.1.span()
+    }
+    /// Returns the path of the config file that failed to parse, if this error
+    /// (or its cause) is a failure to parse a config file.
+    pub fn config_path(&self) -> Option<&Path> {
+        Some(self.parse_config_error()?.0)
+    }
+    fn parse_config_error(&self) -> Option<(&Path, &toml_edit::de::Error)> {
+        match &self.0 {
+            ErrorKind::ParseConfig(path, e) => Some((path, e)),
+            ErrorKind::WithContext(_, Some(e)) => e.downcast_ref::<Self>()?.parse_config_error(),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for Error {
@@ -58,7 +104,11 @@ impl fmt::Display for Error {
         match &self.0 {
             ErrorKind::Io(e) => fmt::Display::fmt(e, f),
             ErrorKind::CfgExprParse(e) => fmt::Display::fmt(e, f),
+            ErrorKind::ParseConfig(path, _) => {
+                write!(f, "failed to parse `{}` as cargo configuration", path.display())
+            }
             ErrorKind::Other(e) | ErrorKind::WithContext(e, ..) => fmt::Display::fmt(e, f),
+            ErrorKind::MergeKey(key, _) => write!(f, "failed to merge key `{key}`"),
         }
     }
 }
@@ -68,8 +118,10 @@ impl std::error::Error for Error {
         match &self.0 {
             ErrorKind::Io(e) => e.source(),
             ErrorKind::CfgExprParse(e) => e.source(),
+            ErrorKind::ParseConfig(_, e) => Some(e),
             ErrorKind::Other(_) => None,
             ErrorKind::WithContext(_, e) => Some(&**e.as_ref()?),
+            ErrorKind::MergeKey(_, e) => Some(&**e),
         }
     }
 }
@@ -80,6 +132,8 @@ impl From<Error> for io::Error {
         match e.0 {
             ErrorKind::Io(e) => e,
             ErrorKind::CfgExprParse(e) => Self::new(io::ErrorKind::Other, e),
+            e @ ErrorKind::ParseConfig(..) => Self::new(io::ErrorKind::InvalidData, Error(e)),
+            e @ ErrorKind::MergeKey(..) => Self::new(io::ErrorKind::Other, Error(e)),
             ErrorKind::Other(e) | ErrorKind::WithContext(e, None) => {
                 Self::new(io::ErrorKind::Other, e)
             }
@@ -68,6 +68,11 @@ const _: fn() = || {
     assert_unpin::<crate::de::TargetConfig>();
     assert_unwind_safe::<crate::de::TargetConfig>();
     assert_ref_unwind_safe::<crate::de::TargetConfig>();
+    assert_send::<crate::de::LinkOverride>();
+    assert_sync::<crate::de::LinkOverride>();
+    assert_unpin::<crate::de::LinkOverride>();
+    assert_unwind_safe::<crate::de::LinkOverride>();
+    assert_ref_unwind_safe::<crate::de::LinkOverride>();
     assert_send::<crate::de::DocConfig>();
     assert_sync::<crate::de::DocConfig>();
     assert_unpin::<crate::de::DocConfig>();
@@ -103,6 +108,11 @@ const _: fn() = || {
     assert_unpin::<crate::de::NetConfig>();
     assert_unwind_safe::<crate::de::NetConfig>();
     assert_ref_unwind_safe::<crate::de::NetConfig>();
+    assert_send::<crate::de::NetSshConfig>();
+    assert_sync::<crate::de::NetSshConfig>();
+    assert_unpin::<crate::de::NetSshConfig>();
+    assert_unwind_safe::<crate::de::NetSshConfig>();
+    assert_ref_unwind_safe::<crate::de::NetSshConfig>();
     assert_send::<crate::de::RegistriesConfigValue>();
     assert_sync::<crate::de::RegistriesConfigValue>();
     assert_unpin::<crate::de::RegistriesConfigValue>();
@@ -143,6 +153,16 @@ const _: fn() = || {
     assert_unpin::<crate::de::Frequency>();
     assert_unwind_safe::<crate::de::Frequency>();
     assert_ref_unwind_safe::<crate::de::Frequency>();
+    assert_send::<crate::de::Jobs>();
+    assert_sync::<crate::de::Jobs>();
+    assert_unpin::<crate::de::Jobs>();
+    assert_unwind_safe::<crate::de::Jobs>();
+    assert_ref_unwind_safe::<crate::de::Jobs>();
+    assert_send::<crate::de::Warnings>();
+    assert_sync::<crate::de::Warnings>();
+    assert_unpin::<crate::de::Warnings>();
+    assert_unwind_safe::<crate::de::Warnings>();
+    assert_ref_unwind_safe::<crate::de::Warnings>();
     assert_send::<crate::de::Flags>();
     assert_sync::<crate::de::Flags>();
     assert_unpin::<crate::de::Flags>();
@@ -169,10 +189,10 @@ const _: fn() = || {
     assert_unwind_safe::<crate::de::StringOrArray>();
     assert_ref_unwind_safe::<crate::de::StringOrArray>();
     assert_send::<crate::easy::Config>();
-    assert_not_sync!(crate::easy::Config);
+    assert_sync::<crate::easy::Config>();
     assert_unpin::<crate::easy::Config>();
     assert_unwind_safe::<crate::easy::Config>();
-    assert_not_ref_unwind_safe!(crate::easy::Config);
+    assert_ref_unwind_safe::<crate::easy::Config>();
     assert_send::<crate::easy::BuildConfig>();
     assert_sync::<crate::easy::BuildConfig>();
     assert_unpin::<crate::easy::BuildConfig>();
@@ -183,6 +203,11 @@ const _: fn() = || {
     assert_unpin::<crate::easy::TargetConfig>();
     assert_unwind_safe::<crate::easy::TargetConfig>();
     assert_ref_unwind_safe::<crate::easy::TargetConfig>();
+    assert_send::<crate::easy::LinkOverride>();
+    assert_sync::<crate::easy::LinkOverride>();
+    assert_unpin::<crate::easy::LinkOverride>();
+    assert_unwind_safe::<crate::easy::LinkOverride>();
+    assert_ref_unwind_safe::<crate::easy::LinkOverride>();
     assert_send::<crate::easy::DocConfig>();
     assert_sync::<crate::easy::DocConfig>();
     assert_unpin::<crate::easy::DocConfig>();
@@ -213,6 +238,11 @@ const _: fn() = || {
     assert_unpin::<crate::easy::NetConfig>();
     assert_unwind_safe::<crate::easy::NetConfig>();
     assert_ref_unwind_safe::<crate::easy::NetConfig>();
+    assert_send::<crate::easy::NetSshConfig>();
+    assert_sync::<crate::easy::NetSshConfig>();
+    assert_unpin::<crate::easy::NetSshConfig>();
+    assert_unwind_safe::<crate::easy::NetSshConfig>();
+    assert_ref_unwind_safe::<crate::easy::NetSshConfig>();
     assert_send::<crate::easy::RegistriesConfigValue>();
     assert_sync::<crate::easy::RegistriesConfigValue>();
     assert_unpin::<crate::easy::RegistriesConfigValue>();
@@ -233,6 +263,16 @@ const _: fn() = || {
     assert_unpin::<crate::easy::TermProgressConfig>();
     assert_unwind_safe::<crate::easy::TermProgressConfig>();
     assert_ref_unwind_safe::<crate::easy::TermProgressConfig>();
+    assert_send::<crate::easy::Verbosity>();
+    assert_sync::<crate::easy::Verbosity>();
+    assert_unpin::<crate::easy::Verbosity>();
+    assert_unwind_safe::<crate::easy::Verbosity>();
+    assert_ref_unwind_safe::<crate::easy::Verbosity>();
+    assert_send::<crate::easy::RustflagsSource>();
+    assert_sync::<crate::easy::RustflagsSource>();
+    assert_unpin::<crate::easy::RustflagsSource>();
+    assert_unwind_safe::<crate::easy::RustflagsSource>();
+    assert_ref_unwind_safe::<crate::easy::RustflagsSource>();
     assert_send::<crate::easy::Flags>();
     assert_sync::<crate::easy::Flags>();
     assert_unpin::<crate::easy::Flags>();
@@ -259,10 +299,10 @@ const _: fn() = || {
     assert_unwind_safe::<crate::resolve::ResolveOptions>();
     assert_ref_unwind_safe::<crate::resolve::ResolveOptions>();
     assert_send::<crate::resolve::ResolveContext>();
-    assert_not_sync!(crate::resolve::ResolveContext);
+    assert_sync::<crate::resolve::ResolveContext>();
     assert_unpin::<crate::resolve::ResolveContext>();
     assert_unwind_safe::<crate::resolve::ResolveContext>();
-    assert_not_ref_unwind_safe!(crate::resolve::ResolveContext);
+    assert_ref_unwind_safe::<crate::resolve::ResolveContext>();
     assert_send::<crate::resolve::TargetTripleRef<'_>>();
     assert_sync::<crate::resolve::TargetTripleRef<'_>>();
     assert_unpin::<crate::resolve::TargetTripleRef<'_>>();
@@ -283,6 +323,11 @@ const _: fn() = || {
     assert_unpin::<crate::resolve::CargoVersion>();
     assert_unwind_safe::<crate::resolve::CargoVersion>();
     assert_ref_unwind_safe::<crate::resolve::CargoVersion>();
+    assert_send::<crate::validate::Diagnostic>();
+    assert_sync::<crate::validate::Diagnostic>();
+    assert_unpin::<crate::validate::Diagnostic>();
+    assert_unwind_safe::<crate::validate::Diagnostic>();
+    assert_ref_unwind_safe::<crate::validate::Diagnostic>();
     assert_send::<crate::value::Value<()>>();
     assert_send::<crate::value::Value<NotSync>>();
     assert_not_send!(crate::value::Value<NotSend>);
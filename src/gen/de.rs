@@ -4,263 +4,554 @@
 // It is not intended for manual editing.
 
 #![cfg_attr(rustfmt, rustfmt::skip)]
-use std::path::Path;
-use crate::{error::Result, merge::Merge, value::SetPath};
+use crate::{
+    error::Result, get::{GetValue, ValueRef},
+    merge::Merge, value::{Definition, SetDefinition},
+};
 impl Merge for crate::de::Config {
     fn merge(&mut self, low: Self, force: bool) -> Result<()> {
-        self.alias.merge(low.alias, force)?;
-        self.build.merge(low.build, force)?;
-        self.doc.merge(low.doc, force)?;
-        self.env.merge(low.env, force)?;
-        self.future_incompat_report.merge(low.future_incompat_report, force)?;
-        self.cargo_new.merge(low.cargo_new, force)?;
-        self.http.merge(low.http, force)?;
-        self.net.merge(low.net, force)?;
-        self.registries.merge(low.registries, force)?;
-        self.registry.merge(low.registry, force)?;
-        self.target.merge(low.target, force)?;
-        self.term.merge(low.term, force)?;
+        self.alias.merge(low.alias, force).map_err(|e| e.merge_key("alias"))?;
+        self.build.merge(low.build, force).map_err(|e| e.merge_key("build"))?;
+        self.doc.merge(low.doc, force).map_err(|e| e.merge_key("doc"))?;
+        self.env.merge(low.env, force).map_err(|e| e.merge_key("env"))?;
+        self.future_incompat_report
+            .merge(low.future_incompat_report, force)
+            .map_err(|e| e.merge_key("future-incompat-report"))?;
+        self.cargo_new
+            .merge(low.cargo_new, force)
+            .map_err(|e| e.merge_key("cargo-new"))?;
+        self.http.merge(low.http, force).map_err(|e| e.merge_key("http"))?;
+        self.net.merge(low.net, force).map_err(|e| e.merge_key("net"))?;
+        self.registries
+            .merge(low.registries, force)
+            .map_err(|e| e.merge_key("registries"))?;
+        self.registry.merge(low.registry, force).map_err(|e| e.merge_key("registry"))?;
+        self.target.merge(low.target, force).map_err(|e| e.merge_key("target"))?;
+        self.term.merge(low.term, force).map_err(|e| e.merge_key("term"))?;
         Ok(())
     }
 }
-impl SetPath for crate::de::Config {
-    fn set_path(&mut self, path: &Path) {
-        self.alias.set_path(path);
-        self.build.set_path(path);
-        self.doc.set_path(path);
-        self.env.set_path(path);
-        self.future_incompat_report.set_path(path);
-        self.cargo_new.set_path(path);
-        self.http.set_path(path);
-        self.net.set_path(path);
-        self.registries.set_path(path);
-        self.registry.set_path(path);
-        self.target.set_path(path);
-        self.term.set_path(path);
+impl GetValue for crate::de::Config {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        let Some((first, rest)) = key.split_first() else {
+            return Some(ValueRef::Table);
+        };
+        match *first {
+            "alias" => self.alias.get_value(rest),
+            "build" => self.build.get_value(rest),
+            "doc" => self.doc.get_value(rest),
+            "env" => self.env.get_value(rest),
+            "future-incompat-report" => self.future_incompat_report.get_value(rest),
+            "cargo-new" => self.cargo_new.get_value(rest),
+            "http" => self.http.get_value(rest),
+            "net" => self.net.get_value(rest),
+            "registries" => self.registries.get_value(rest),
+            "registry" => self.registry.get_value(rest),
+            "target" => self.target.get_value(rest),
+            "term" => self.term.get_value(rest),
+            _ => None,
+        }
+    }
+}
+impl SetDefinition for crate::de::Config {
+    fn set_definition(&mut self, def: &Definition) {
+        self.alias.set_definition(def);
+        self.build.set_definition(def);
+        self.doc.set_definition(def);
+        self.env.set_definition(def);
+        self.future_incompat_report.set_definition(def);
+        self.cargo_new.set_definition(def);
+        self.http.set_definition(def);
+        self.net.set_definition(def);
+        self.registries.set_definition(def);
+        self.registry.set_definition(def);
+        self.target.set_definition(def);
+        self.term.set_definition(def);
     }
 }
 impl Merge for crate::de::BuildConfig {
     fn merge(&mut self, low: Self, force: bool) -> Result<()> {
-        self.jobs.merge(low.jobs, force)?;
-        self.rustc.merge(low.rustc, force)?;
-        self.rustc_wrapper.merge(low.rustc_wrapper, force)?;
-        self.rustc_workspace_wrapper.merge(low.rustc_workspace_wrapper, force)?;
-        self.rustdoc.merge(low.rustdoc, force)?;
-        self.target.merge(low.target, force)?;
-        self.target_dir.merge(low.target_dir, force)?;
-        self.rustflags.merge(low.rustflags, force)?;
-        self.rustdocflags.merge(low.rustdocflags, force)?;
-        self.incremental.merge(low.incremental, force)?;
-        self.dep_info_basedir.merge(low.dep_info_basedir, force)?;
+        self.jobs.merge(low.jobs, force).map_err(|e| e.merge_key("jobs"))?;
+        self.rustc.merge(low.rustc, force).map_err(|e| e.merge_key("rustc"))?;
+        self.rustc_wrapper
+            .merge(low.rustc_wrapper, force)
+            .map_err(|e| e.merge_key("rustc-wrapper"))?;
+        self.rustc_workspace_wrapper
+            .merge(low.rustc_workspace_wrapper, force)
+            .map_err(|e| e.merge_key("rustc-workspace-wrapper"))?;
+        self.rustdoc.merge(low.rustdoc, force).map_err(|e| e.merge_key("rustdoc"))?;
+        self.target.merge(low.target, force).map_err(|e| e.merge_key("target"))?;
+        self.target_dir
+            .merge(low.target_dir, force)
+            .map_err(|e| e.merge_key("target-dir"))?;
+        self.build_dir
+            .merge(low.build_dir, force)
+            .map_err(|e| e.merge_key("build-dir"))?;
+        self.rustflags
+            .merge(low.rustflags, force)
+            .map_err(|e| e.merge_key("rustflags"))?;
+        self.rustdocflags
+            .merge(low.rustdocflags, force)
+            .map_err(|e| e.merge_key("rustdocflags"))?;
+        self.incremental
+            .merge(low.incremental, force)
+            .map_err(|e| e.merge_key("incremental"))?;
+        self.dep_info_basedir
+            .merge(low.dep_info_basedir, force)
+            .map_err(|e| e.merge_key("dep-info-basedir"))?;
+        self.warnings.merge(low.warnings, force).map_err(|e| e.merge_key("warnings"))?;
         Ok(())
     }
 }
-impl SetPath for crate::de::BuildConfig {
-    fn set_path(&mut self, path: &Path) {
-        self.jobs.set_path(path);
-        self.rustc.set_path(path);
-        self.rustc_wrapper.set_path(path);
-        self.rustc_workspace_wrapper.set_path(path);
-        self.rustdoc.set_path(path);
-        self.target.set_path(path);
-        self.target_dir.set_path(path);
-        self.rustflags.set_path(path);
-        self.rustdocflags.set_path(path);
-        self.incremental.set_path(path);
-        self.dep_info_basedir.set_path(path);
+impl GetValue for crate::de::BuildConfig {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        let Some((first, rest)) = key.split_first() else {
+            return Some(ValueRef::Table);
+        };
+        match *first {
+            "jobs" => self.jobs.get_value(rest),
+            "rustc" => self.rustc.get_value(rest),
+            "rustc-wrapper" => self.rustc_wrapper.get_value(rest),
+            "rustc-workspace-wrapper" => self.rustc_workspace_wrapper.get_value(rest),
+            "rustdoc" => self.rustdoc.get_value(rest),
+            "target" => self.target.get_value(rest),
+            "target-dir" => self.target_dir.get_value(rest),
+            "build-dir" => self.build_dir.get_value(rest),
+            "rustflags" => self.rustflags.get_value(rest),
+            "rustdocflags" => self.rustdocflags.get_value(rest),
+            "incremental" => self.incremental.get_value(rest),
+            "dep-info-basedir" => self.dep_info_basedir.get_value(rest),
+            "warnings" => self.warnings.get_value(rest),
+            _ => None,
+        }
+    }
+}
+impl SetDefinition for crate::de::BuildConfig {
+    fn set_definition(&mut self, def: &Definition) {
+        self.jobs.set_definition(def);
+        self.rustc.set_definition(def);
+        self.rustc_wrapper.set_definition(def);
+        self.rustc_workspace_wrapper.set_definition(def);
+        self.rustdoc.set_definition(def);
+        self.target.set_definition(def);
+        self.target_dir.set_definition(def);
+        self.build_dir.set_definition(def);
+        self.rustflags.set_definition(def);
+        self.rustdocflags.set_definition(def);
+        self.incremental.set_definition(def);
+        self.dep_info_basedir.set_definition(def);
+        self.warnings.set_definition(def);
     }
 }
 impl Merge for crate::de::TargetConfig {
     fn merge(&mut self, low: Self, force: bool) -> Result<()> {
-        self.linker.merge(low.linker, force)?;
-        self.runner.merge(low.runner, force)?;
-        self.rustflags.merge(low.rustflags, force)?;
-        self.rustdocflags.merge(low.rustdocflags, force)?;
+        self.linker.merge(low.linker, force).map_err(|e| e.merge_key("linker"))?;
+        self.runner.merge(low.runner, force).map_err(|e| e.merge_key("runner"))?;
+        self.rustflags
+            .merge(low.rustflags, force)
+            .map_err(|e| e.merge_key("rustflags"))?;
+        self.rustdocflags
+            .merge(low.rustdocflags, force)
+            .map_err(|e| e.merge_key("rustdocflags"))?;
+        self.links.merge(low.links, force).map_err(|e| e.merge_key("links"))?;
         Ok(())
     }
 }
-impl SetPath for crate::de::TargetConfig {
-    fn set_path(&mut self, path: &Path) {
-        self.linker.set_path(path);
-        self.runner.set_path(path);
-        self.rustflags.set_path(path);
-        self.rustdocflags.set_path(path);
+impl GetValue for crate::de::TargetConfig {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        let Some((first, rest)) = key.split_first() else {
+            return Some(ValueRef::Table);
+        };
+        match *first {
+            "linker" => self.linker.get_value(rest),
+            "runner" => self.runner.get_value(rest),
+            "rustflags" => self.rustflags.get_value(rest),
+            "rustdocflags" => self.rustdocflags.get_value(rest),
+            _ => self.links.get_value(key),
+        }
+    }
+}
+impl SetDefinition for crate::de::TargetConfig {
+    fn set_definition(&mut self, def: &Definition) {
+        self.linker.set_definition(def);
+        self.runner.set_definition(def);
+        self.rustflags.set_definition(def);
+        self.rustdocflags.set_definition(def);
+        self.links.set_definition(def);
+    }
+}
+impl Merge for crate::de::LinkOverride {
+    fn merge(&mut self, low: Self, force: bool) -> Result<()> {
+        self.rustc_link_lib
+            .merge(low.rustc_link_lib, force)
+            .map_err(|e| e.merge_key("rustc-link-lib"))?;
+        self.rustc_link_search
+            .merge(low.rustc_link_search, force)
+            .map_err(|e| e.merge_key("rustc-link-search"))?;
+        self.rustc_flags
+            .merge(low.rustc_flags, force)
+            .map_err(|e| e.merge_key("rustc-flags"))?;
+        self.rustc_cfg
+            .merge(low.rustc_cfg, force)
+            .map_err(|e| e.merge_key("rustc-cfg"))?;
+        self.rustc_env
+            .merge(low.rustc_env, force)
+            .map_err(|e| e.merge_key("rustc-env"))?;
+        Ok(())
+    }
+}
+impl GetValue for crate::de::LinkOverride {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        let Some((first, rest)) = key.split_first() else {
+            return Some(ValueRef::Table);
+        };
+        match *first {
+            "rustc-link-lib" => self.rustc_link_lib.get_value(rest),
+            "rustc-link-search" => self.rustc_link_search.get_value(rest),
+            "rustc-flags" => self.rustc_flags.get_value(rest),
+            "rustc-cfg" => self.rustc_cfg.get_value(rest),
+            "rustc-env" => self.rustc_env.get_value(rest),
+            _ => None,
+        }
+    }
+}
+impl SetDefinition for crate::de::LinkOverride {
+    fn set_definition(&mut self, def: &Definition) {
+        self.rustc_link_lib.set_definition(def);
+        self.rustc_link_search.set_definition(def);
+        self.rustc_flags.set_definition(def);
+        self.rustc_cfg.set_definition(def);
+        self.rustc_env.set_definition(def);
     }
 }
 impl Merge for crate::de::DocConfig {
     fn merge(&mut self, low: Self, force: bool) -> Result<()> {
-        self.browser.merge(low.browser, force)?;
+        self.browser.merge(low.browser, force).map_err(|e| e.merge_key("browser"))?;
         Ok(())
     }
 }
-impl SetPath for crate::de::DocConfig {
-    fn set_path(&mut self, path: &Path) {
-        self.browser.set_path(path);
+impl GetValue for crate::de::DocConfig {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        let Some((first, rest)) = key.split_first() else {
+            return Some(ValueRef::Table);
+        };
+        match *first {
+            "browser" => self.browser.get_value(rest),
+            _ => None,
+        }
+    }
+}
+impl SetDefinition for crate::de::DocConfig {
+    fn set_definition(&mut self, def: &Definition) {
+        self.browser.set_definition(def);
     }
 }
-impl SetPath for crate::de::EnvConfigValue {
-    fn set_path(&mut self, path: &Path) {
+impl SetDefinition for crate::de::EnvConfigValue {
+    fn set_definition(&mut self, def: &Definition) {
         match self {
             Self::Value(v) => {
-                v.set_path(path);
+                v.set_definition(def);
             }
             Self::Table { value, force, relative } => {
-                value.set_path(path);
-                force.set_path(path);
-                relative.set_path(path);
+                value.set_definition(def);
+                force.set_definition(def);
+                relative.set_definition(def);
             }
         }
     }
 }
 impl Merge for crate::de::FutureIncompatReportConfig {
     fn merge(&mut self, low: Self, force: bool) -> Result<()> {
-        self.frequency.merge(low.frequency, force)?;
+        self.frequency
+            .merge(low.frequency, force)
+            .map_err(|e| e.merge_key("frequency"))?;
         Ok(())
     }
 }
-impl SetPath for crate::de::FutureIncompatReportConfig {
-    fn set_path(&mut self, path: &Path) {
-        self.frequency.set_path(path);
+impl GetValue for crate::de::FutureIncompatReportConfig {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        let Some((first, rest)) = key.split_first() else {
+            return Some(ValueRef::Table);
+        };
+        match *first {
+            "frequency" => self.frequency.get_value(rest),
+            _ => None,
+        }
+    }
+}
+impl SetDefinition for crate::de::FutureIncompatReportConfig {
+    fn set_definition(&mut self, def: &Definition) {
+        self.frequency.set_definition(def);
     }
 }
 impl Merge for crate::de::CargoNewConfig {
     fn merge(&mut self, low: Self, force: bool) -> Result<()> {
-        self.vcs.merge(low.vcs, force)?;
+        self.vcs.merge(low.vcs, force).map_err(|e| e.merge_key("vcs"))?;
         Ok(())
     }
 }
-impl SetPath for crate::de::CargoNewConfig {
-    fn set_path(&mut self, path: &Path) {
-        self.vcs.set_path(path);
+impl GetValue for crate::de::CargoNewConfig {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        let Some((first, rest)) = key.split_first() else {
+            return Some(ValueRef::Table);
+        };
+        match *first {
+            "vcs" => self.vcs.get_value(rest),
+            _ => None,
+        }
+    }
+}
+impl SetDefinition for crate::de::CargoNewConfig {
+    fn set_definition(&mut self, def: &Definition) {
+        self.vcs.set_definition(def);
     }
 }
 impl Merge for crate::de::HttpConfig {
     fn merge(&mut self, low: Self, force: bool) -> Result<()> {
-        self.debug.merge(low.debug, force)?;
-        self.proxy.merge(low.proxy, force)?;
-        self.timeout.merge(low.timeout, force)?;
-        self.cainfo.merge(low.cainfo, force)?;
-        self.check_revoke.merge(low.check_revoke, force)?;
-        self.low_speed_limit.merge(low.low_speed_limit, force)?;
-        self.multiplexing.merge(low.multiplexing, force)?;
-        self.user_agent.merge(low.user_agent, force)?;
+        self.debug.merge(low.debug, force).map_err(|e| e.merge_key("debug"))?;
+        self.proxy.merge(low.proxy, force).map_err(|e| e.merge_key("proxy"))?;
+        self.timeout.merge(low.timeout, force).map_err(|e| e.merge_key("timeout"))?;
+        self.cainfo.merge(low.cainfo, force).map_err(|e| e.merge_key("cainfo"))?;
+        self.check_revoke
+            .merge(low.check_revoke, force)
+            .map_err(|e| e.merge_key("check-revoke"))?;
+        self.low_speed_limit
+            .merge(low.low_speed_limit, force)
+            .map_err(|e| e.merge_key("low-speed-limit"))?;
+        self.multiplexing
+            .merge(low.multiplexing, force)
+            .map_err(|e| e.merge_key("multiplexing"))?;
+        self.user_agent
+            .merge(low.user_agent, force)
+            .map_err(|e| e.merge_key("user-agent"))?;
         Ok(())
     }
 }
-impl SetPath for crate::de::HttpConfig {
-    fn set_path(&mut self, path: &Path) {
-        self.debug.set_path(path);
-        self.proxy.set_path(path);
-        self.timeout.set_path(path);
-        self.cainfo.set_path(path);
-        self.check_revoke.set_path(path);
-        self.low_speed_limit.set_path(path);
-        self.multiplexing.set_path(path);
-        self.user_agent.set_path(path);
+impl GetValue for crate::de::HttpConfig {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        let Some((first, rest)) = key.split_first() else {
+            return Some(ValueRef::Table);
+        };
+        match *first {
+            "debug" => self.debug.get_value(rest),
+            "proxy" => self.proxy.get_value(rest),
+            "timeout" => self.timeout.get_value(rest),
+            "cainfo" => self.cainfo.get_value(rest),
+            "check-revoke" => self.check_revoke.get_value(rest),
+            "low-speed-limit" => self.low_speed_limit.get_value(rest),
+            "multiplexing" => self.multiplexing.get_value(rest),
+            "user-agent" => self.user_agent.get_value(rest),
+            _ => None,
+        }
+    }
+}
+impl SetDefinition for crate::de::HttpConfig {
+    fn set_definition(&mut self, def: &Definition) {
+        self.debug.set_definition(def);
+        self.proxy.set_definition(def);
+        self.timeout.set_definition(def);
+        self.cainfo.set_definition(def);
+        self.check_revoke.set_definition(def);
+        self.low_speed_limit.set_definition(def);
+        self.multiplexing.set_definition(def);
+        self.user_agent.set_definition(def);
     }
 }
 impl Merge for crate::de::NetConfig {
     fn merge(&mut self, low: Self, force: bool) -> Result<()> {
-        self.retry.merge(low.retry, force)?;
-        self.git_fetch_with_cli.merge(low.git_fetch_with_cli, force)?;
-        self.offline.merge(low.offline, force)?;
+        self.retry.merge(low.retry, force).map_err(|e| e.merge_key("retry"))?;
+        self.git_fetch_with_cli
+            .merge(low.git_fetch_with_cli, force)
+            .map_err(|e| e.merge_key("git-fetch-with-cli"))?;
+        self.offline.merge(low.offline, force).map_err(|e| e.merge_key("offline"))?;
+        self.ssh.merge(low.ssh, force).map_err(|e| e.merge_key("ssh"))?;
+        Ok(())
+    }
+}
+impl GetValue for crate::de::NetConfig {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        let Some((first, rest)) = key.split_first() else {
+            return Some(ValueRef::Table);
+        };
+        match *first {
+            "retry" => self.retry.get_value(rest),
+            "git-fetch-with-cli" => self.git_fetch_with_cli.get_value(rest),
+            "offline" => self.offline.get_value(rest),
+            "ssh" => self.ssh.get_value(rest),
+            _ => None,
+        }
+    }
+}
+impl SetDefinition for crate::de::NetConfig {
+    fn set_definition(&mut self, def: &Definition) {
+        self.retry.set_definition(def);
+        self.git_fetch_with_cli.set_definition(def);
+        self.offline.set_definition(def);
+        self.ssh.set_definition(def);
+    }
+}
+impl Merge for crate::de::NetSshConfig {
+    fn merge(&mut self, low: Self, force: bool) -> Result<()> {
+        self.known_hosts
+            .merge(low.known_hosts, force)
+            .map_err(|e| e.merge_key("known-hosts"))?;
         Ok(())
     }
 }
-impl SetPath for crate::de::NetConfig {
-    fn set_path(&mut self, path: &Path) {
-        self.retry.set_path(path);
-        self.git_fetch_with_cli.set_path(path);
-        self.offline.set_path(path);
+impl GetValue for crate::de::NetSshConfig {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        let Some((first, rest)) = key.split_first() else {
+            return Some(ValueRef::Table);
+        };
+        match *first {
+            "known-hosts" => self.known_hosts.get_value(rest),
+            _ => None,
+        }
+    }
+}
+impl SetDefinition for crate::de::NetSshConfig {
+    fn set_definition(&mut self, def: &Definition) {
+        self.known_hosts.set_definition(def);
     }
 }
 impl Merge for crate::de::RegistriesConfigValue {
     fn merge(&mut self, low: Self, force: bool) -> Result<()> {
-        self.index.merge(low.index, force)?;
-        self.token.merge(low.token, force)?;
-        self.protocol.merge(low.protocol, force)?;
+        self.index.merge(low.index, force).map_err(|e| e.merge_key("index"))?;
+        self.token.merge(low.token, force).map_err(|e| e.merge_key("token"))?;
+        self.protocol.merge(low.protocol, force).map_err(|e| e.merge_key("protocol"))?;
         Ok(())
     }
 }
-impl SetPath for crate::de::RegistriesConfigValue {
-    fn set_path(&mut self, path: &Path) {
-        self.index.set_path(path);
-        self.token.set_path(path);
-        self.protocol.set_path(path);
+impl GetValue for crate::de::RegistriesConfigValue {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        let Some((first, rest)) = key.split_first() else {
+            return Some(ValueRef::Table);
+        };
+        match *first {
+            "index" => self.index.get_value(rest),
+            "token" => self.token.get_value(rest),
+            "protocol" => self.protocol.get_value(rest),
+            _ => None,
+        }
+    }
+}
+impl SetDefinition for crate::de::RegistriesConfigValue {
+    fn set_definition(&mut self, def: &Definition) {
+        self.index.set_definition(def);
+        self.token.set_definition(def);
+        self.protocol.set_definition(def);
     }
 }
 impl Merge for crate::de::RegistryConfig {
     fn merge(&mut self, low: Self, force: bool) -> Result<()> {
-        self.default.merge(low.default, force)?;
-        self.token.merge(low.token, force)?;
+        self.default.merge(low.default, force).map_err(|e| e.merge_key("default"))?;
+        self.token.merge(low.token, force).map_err(|e| e.merge_key("token"))?;
         Ok(())
     }
 }
-impl SetPath for crate::de::RegistryConfig {
-    fn set_path(&mut self, path: &Path) {
-        self.default.set_path(path);
-        self.token.set_path(path);
+impl GetValue for crate::de::RegistryConfig {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        let Some((first, rest)) = key.split_first() else {
+            return Some(ValueRef::Table);
+        };
+        match *first {
+            "default" => self.default.get_value(rest),
+            "token" => self.token.get_value(rest),
+            _ => None,
+        }
+    }
+}
+impl SetDefinition for crate::de::RegistryConfig {
+    fn set_definition(&mut self, def: &Definition) {
+        self.default.set_definition(def);
+        self.token.set_definition(def);
     }
 }
 impl Merge for crate::de::TermConfig {
     fn merge(&mut self, low: Self, force: bool) -> Result<()> {
-        self.quiet.merge(low.quiet, force)?;
-        self.verbose.merge(low.verbose, force)?;
-        self.color.merge(low.color, force)?;
-        self.progress.merge(low.progress, force)?;
+        self.quiet.merge(low.quiet, force).map_err(|e| e.merge_key("quiet"))?;
+        self.verbose.merge(low.verbose, force).map_err(|e| e.merge_key("verbose"))?;
+        self.color.merge(low.color, force).map_err(|e| e.merge_key("color"))?;
+        self.progress.merge(low.progress, force).map_err(|e| e.merge_key("progress"))?;
         Ok(())
     }
 }
-impl SetPath for crate::de::TermConfig {
-    fn set_path(&mut self, path: &Path) {
-        self.quiet.set_path(path);
-        self.verbose.set_path(path);
-        self.color.set_path(path);
-        self.progress.set_path(path);
+impl GetValue for crate::de::TermConfig {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        let Some((first, rest)) = key.split_first() else {
+            return Some(ValueRef::Table);
+        };
+        match *first {
+            "quiet" => self.quiet.get_value(rest),
+            "verbose" => self.verbose.get_value(rest),
+            "color" => self.color.get_value(rest),
+            "progress" => self.progress.get_value(rest),
+            _ => None,
+        }
+    }
+}
+impl SetDefinition for crate::de::TermConfig {
+    fn set_definition(&mut self, def: &Definition) {
+        self.quiet.set_definition(def);
+        self.verbose.set_definition(def);
+        self.color.set_definition(def);
+        self.progress.set_definition(def);
     }
 }
 impl Merge for crate::de::TermProgress {
     fn merge(&mut self, low: Self, force: bool) -> Result<()> {
-        self.when.merge(low.when, force)?;
-        self.width.merge(low.width, force)?;
+        self.when.merge(low.when, force).map_err(|e| e.merge_key("when"))?;
+        self.width.merge(low.width, force).map_err(|e| e.merge_key("width"))?;
         Ok(())
     }
 }
-impl SetPath for crate::de::TermProgress {
-    fn set_path(&mut self, path: &Path) {
-        self.when.set_path(path);
-        self.width.set_path(path);
+impl GetValue for crate::de::TermProgress {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        let Some((first, rest)) = key.split_first() else {
+            return Some(ValueRef::Table);
+        };
+        match *first {
+            "when" => self.when.get_value(rest),
+            "width" => self.width.get_value(rest),
+            _ => None,
+        }
+    }
+}
+impl SetDefinition for crate::de::TermProgress {
+    fn set_definition(&mut self, def: &Definition) {
+        self.when.set_definition(def);
+        self.width.set_definition(def);
     }
 }
-impl SetPath for crate::de::Flags {
-    fn set_path(&mut self, path: &Path) {
-        self.flags.set_path(path);
+impl SetDefinition for crate::de::Flags {
+    fn set_definition(&mut self, def: &Definition) {
+        self.flags.set_definition(def);
     }
 }
-impl SetPath for crate::de::ConfigRelativePath {
-    fn set_path(&mut self, path: &Path) {
-        self.0.set_path(path);
+impl SetDefinition for crate::de::ConfigRelativePath {
+    fn set_definition(&mut self, def: &Definition) {
+        self.0.set_definition(def);
     }
 }
-impl SetPath for crate::de::PathAndArgs {
-    fn set_path(&mut self, path: &Path) {
-        self.path.set_path(path);
-        self.args.set_path(path);
+impl SetDefinition for crate::de::PathAndArgs {
+    fn set_definition(&mut self, def: &Definition) {
+        self.path.set_definition(def);
+        self.args.set_definition(def);
     }
 }
-impl SetPath for crate::de::StringList {
-    fn set_path(&mut self, path: &Path) {
-        self.list.set_path(path);
+impl SetDefinition for crate::de::StringList {
+    fn set_definition(&mut self, def: &Definition) {
+        self.list.set_definition(def);
     }
 }
-impl SetPath for crate::de::StringOrArray {
-    fn set_path(&mut self, path: &Path) {
+impl SetDefinition for crate::de::StringOrArray {
+    fn set_definition(&mut self, def: &Definition) {
         match self {
             Self::String(v) => {
-                v.set_path(path);
+                v.set_definition(def);
             }
             Self::Array(v) => {
-                v.set_path(path);
+                v.set_definition(def);
             }
         }
     }
@@ -5,31 +5,42 @@
 
 #![cfg_attr(rustfmt, rustfmt::skip)]
 impl crate::easy::BuildConfig {
-    pub(crate) fn is_none(&self) -> bool {
+    /// Returns `true` if no values are set in this table.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
         self.jobs.is_none() && self.rustc.is_none() && self.rustc_wrapper.is_none()
             && self.rustc_workspace_wrapper.is_none() && self.rustdoc.is_none()
             && self.target.is_none() && self.target_dir.is_none()
-            && self.rustflags.is_none() && self.rustdocflags.is_none()
-            && self.incremental.is_none() && self.dep_info_basedir.is_none()
+            && self.build_dir.is_none() && self.rustflags.is_none()
+            && self.rustdocflags.is_none() && self.incremental.is_none()
+            && self.dep_info_basedir.is_none() && self.warnings.is_none()
     }
 }
 impl crate::easy::DocConfig {
-    pub(crate) fn is_none(&self) -> bool {
+    /// Returns `true` if no values are set in this table.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
         self.browser.is_none()
     }
 }
 impl crate::easy::FutureIncompatReportConfig {
-    pub(crate) fn is_none(&self) -> bool {
+    /// Returns `true` if no values are set in this table.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
         self.frequency.is_none()
     }
 }
 impl crate::easy::CargoNewConfig {
-    pub(crate) fn is_none(&self) -> bool {
+    /// Returns `true` if no values are set in this table.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
         self.vcs.is_none()
     }
 }
 impl crate::easy::HttpConfig {
-    pub(crate) fn is_none(&self) -> bool {
+    /// Returns `true` if no values are set in this table.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
         self.debug.is_none() && self.proxy.is_none() && self.timeout.is_none()
             && self.cainfo.is_none() && self.check_revoke.is_none()
             && self.low_speed_limit.is_none() && self.multiplexing.is_none()
@@ -37,53 +48,79 @@ impl crate::easy::HttpConfig {
     }
 }
 impl crate::easy::NetConfig {
-    pub(crate) fn is_none(&self) -> bool {
+    /// Returns `true` if no values are set in this table.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
         self.retry.is_none() && self.git_fetch_with_cli.is_none()
-            && self.offline.is_none()
+            && self.offline.is_none() && self.ssh.is_empty()
+    }
+}
+impl crate::easy::NetSshConfig {
+    /// Returns `true` if no values are set in this table.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.known_hosts.is_none()
     }
 }
 impl crate::easy::RegistryConfig {
-    pub(crate) fn is_none(&self) -> bool {
+    /// Returns `true` if no values are set in this table.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
         self.default.is_none() && self.token.is_none()
     }
 }
 impl crate::easy::TermConfig {
-    pub(crate) fn is_none(&self) -> bool {
+    /// Returns `true` if no values are set in this table.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
         self.quiet.is_none() && self.verbose.is_none() && self.color.is_none()
-            && self.progress.is_none()
+            && self.progress.is_empty()
     }
 }
 impl crate::easy::TermProgressConfig {
-    pub(crate) fn is_none(&self) -> bool {
+    /// Returns `true` if no values are set in this table.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
         self.when.is_none() && self.width.is_none()
     }
 }
 impl crate::de::BuildConfig {
-    pub(crate) fn is_none(&self) -> bool {
+    /// Returns `true` if no values are set in this table.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
         self.jobs.is_none() && self.rustc.is_none() && self.rustc_wrapper.is_none()
             && self.rustc_workspace_wrapper.is_none() && self.rustdoc.is_none()
             && self.target.is_none() && self.target_dir.is_none()
-            && self.rustflags.is_none() && self.rustdocflags.is_none()
-            && self.incremental.is_none() && self.dep_info_basedir.is_none()
+            && self.build_dir.is_none() && self.rustflags.is_none()
+            && self.rustdocflags.is_none() && self.incremental.is_none()
+            && self.dep_info_basedir.is_none() && self.warnings.is_none()
     }
 }
 impl crate::de::DocConfig {
-    pub(crate) fn is_none(&self) -> bool {
+    /// Returns `true` if no values are set in this table.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
         self.browser.is_none()
     }
 }
 impl crate::de::FutureIncompatReportConfig {
-    pub(crate) fn is_none(&self) -> bool {
+    /// Returns `true` if no values are set in this table.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
         self.frequency.is_none()
     }
 }
 impl crate::de::CargoNewConfig {
-    pub(crate) fn is_none(&self) -> bool {
+    /// Returns `true` if no values are set in this table.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
         self.vcs.is_none()
     }
 }
 impl crate::de::HttpConfig {
-    pub(crate) fn is_none(&self) -> bool {
+    /// Returns `true` if no values are set in this table.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
         self.debug.is_none() && self.proxy.is_none() && self.timeout.is_none()
             && self.cainfo.is_none() && self.check_revoke.is_none()
             && self.low_speed_limit.is_none() && self.multiplexing.is_none()
@@ -91,24 +128,39 @@ impl crate::de::HttpConfig {
     }
 }
 impl crate::de::NetConfig {
-    pub(crate) fn is_none(&self) -> bool {
+    /// Returns `true` if no values are set in this table.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
         self.retry.is_none() && self.git_fetch_with_cli.is_none()
-            && self.offline.is_none()
+            && self.offline.is_none() && self.ssh.is_empty()
+    }
+}
+impl crate::de::NetSshConfig {
+    /// Returns `true` if no values are set in this table.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.known_hosts.is_none()
     }
 }
 impl crate::de::RegistryConfig {
-    pub(crate) fn is_none(&self) -> bool {
+    /// Returns `true` if no values are set in this table.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
         self.default.is_none() && self.token.is_none()
     }
 }
 impl crate::de::TermConfig {
-    pub(crate) fn is_none(&self) -> bool {
+    /// Returns `true` if no values are set in this table.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
         self.quiet.is_none() && self.verbose.is_none() && self.color.is_none()
-            && self.progress.is_none()
+            && self.progress.is_empty()
     }
 }
 impl crate::de::TermProgress {
-    pub(crate) fn is_none(&self) -> bool {
+    /// Returns `true` if no values are set in this table.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
         self.when.is_none() && self.width.is_none()
     }
 }
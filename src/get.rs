@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::collections::BTreeMap;
+
+use crate::{
+    de::{self, Jobs, RegistriesProtocol},
+    value::{Definition, Value},
+    Color, Frequency, VersionControlSoftware, Warnings, When,
+};
+
+/// A reference to a config value found by a dotted key.
+pub(crate) enum ValueRef<'a> {
+    Boolean(bool, Option<&'a Definition>),
+    Integer(i64, Option<&'a Definition>),
+    String(&'a str, Option<&'a Definition>),
+    List(Vec<&'a str>, Option<&'a Definition>),
+    Table,
+}
+
+impl<'a> ValueRef<'a> {
+    pub(crate) const fn kind(&self) -> &'static str {
+        match self {
+            Self::Boolean(..) => "a boolean",
+            Self::Integer(..) => "an integer",
+            Self::String(..) => "a string",
+            Self::List(..) => "a list",
+            Self::Table => "a table",
+        }
+    }
+    pub(crate) const fn definition(&self) -> Option<&'a Definition> {
+        match self {
+            Self::Boolean(_, def)
+            | Self::Integer(_, def)
+            | Self::String(_, def)
+            | Self::List(_, def) => *def,
+            Self::Table => None,
+        }
+    }
+}
+
+pub(crate) trait GetValue {
+    /// Returns the value of the given key, which has already been split by `.`.
+    ///
+    /// An empty key refers to the value itself.
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>>;
+}
+
+macro_rules! get_scalar {
+    ($ty:ty, |$v:ident, $def:ident| $expr:expr) => {
+        impl GetValue for Value<$ty> {
+            fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+                if !key.is_empty() {
+                    return None;
+                }
+                let $v = &self.val;
+                let $def = self.definition.as_ref();
+                Some($expr)
+            }
+        }
+    };
+}
+get_scalar!(bool, |v, def| ValueRef::Boolean(*v, def));
+get_scalar!(u32, |v, def| ValueRef::Integer(i64::from(*v), def));
+get_scalar!(String, |v, def| ValueRef::String(v, def));
+get_scalar!(Jobs, |v, def| match *v {
+    Jobs::Default => ValueRef::String("default", def),
+    Jobs::Count(n) => ValueRef::Integer(i64::from(n), def),
+});
+get_scalar!(Color, |v, def| ValueRef::String(v.as_str(), def));
+get_scalar!(VersionControlSoftware, |v, def| ValueRef::String(v.as_str(), def));
+get_scalar!(Frequency, |v, def| ValueRef::String(v.as_str(), def));
+get_scalar!(When, |v, def| ValueRef::String(v.as_str(), def));
+get_scalar!(Warnings, |v, def| ValueRef::String(v.as_str(), def));
+get_scalar!(RegistriesProtocol, |v, def| ValueRef::String(
+    match v {
+        RegistriesProtocol::Git => "git",
+        RegistriesProtocol::Sparse => "sparse",
+    },
+    def
+));
+
+fn list(list: &[Value<String>]) -> ValueRef<'_> {
+    ValueRef::List(
+        list.iter().map(|v| v.val.as_str()).collect(),
+        list.first().and_then(|v| v.definition.as_ref()),
+    )
+}
+
+impl<T: GetValue> GetValue for Option<T> {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        self.as_ref()?.get_value(key)
+    }
+}
+impl<V: GetValue> GetValue for BTreeMap<String, V> {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        match key.split_first() {
+            None => Some(ValueRef::Table),
+            Some((first, rest)) => self.get(*first)?.get_value(rest),
+        }
+    }
+}
+impl GetValue for Vec<Value<String>> {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        if !key.is_empty() {
+            return None;
+        }
+        Some(list(self))
+    }
+}
+impl GetValue for de::StringOrArray {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        match self {
+            Self::String(v) => v.get_value(key),
+            Self::Array(v) if key.is_empty() => Some(list(v)),
+            Self::Array(_) => None,
+        }
+    }
+}
+impl GetValue for de::Flags {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        if !key.is_empty() {
+            return None;
+        }
+        Some(list(&self.flags))
+    }
+}
+impl GetValue for de::StringList {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        if !key.is_empty() {
+            return None;
+        }
+        Some(list(&self.list))
+    }
+}
+impl GetValue for de::PathAndArgs {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        if !key.is_empty() {
+            return None;
+        }
+        Some(ValueRef::List(
+            Some(self.path.0.val.as_str())
+                .into_iter()
+                .chain(self.args.iter().map(|v| v.val.as_str()))
+                .collect(),
+            self.path.0.definition.as_ref(),
+        ))
+    }
+}
+impl GetValue for de::EnvConfigValue {
+    fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+        match self {
+            Self::Value(v) => v.get_value(key),
+            Self::Table { value, force, relative } => match key.split_first() {
+                None => Some(ValueRef::Table),
+                Some((&"value", rest)) => value.get_value(rest),
+                Some((&"force", rest)) => force.get_value(rest),
+                Some((&"relative", rest)) => relative.get_value(rest),
+                Some(_) => None,
+            },
+        }
+    }
+}
@@ -26,6 +26,16 @@ println!("{rustflags:?}");
 # Ok(()) }
 ```
 
+## Optional features
+
+- **`serde`** *(enabled by default)* — Load and resolve Cargo configuration. If disabled, only [`Walk`] and functions to get home directories (e.g., [`cargo_home_with_cwd`]) are available, and serde and toml_edit are not used.
+
+- **`color`** — Implement conversion from [`Color`] to [`anstream::ColorChoice`](https://docs.rs/anstream/latest/anstream/enum.ColorChoice.html).
+
+- **`toml_edit`** — Add [`de::Config::to_document`] to convert config to [`toml_edit::DocumentMut`](https://docs.rs/toml_edit/latest/toml_edit/struct.DocumentMut.html) for format-preserving edits. Also add [`Config::get_table`] and [`de::Config::get_table`] to get raw tables, including the tables not modeled by this crate.
+
+- **`serde_json`** — Add [`de::Config::from_json_str`] to read the output of `cargo config get --format=json`.
+
 See also the [`get` example](https://github.com/taiki-e/cargo-config2/blob/HEAD/examples/get.rs) that partial re-implementation of `cargo config get` using cargo-config2.
 */
 
@@ -55,36 +65,73 @@ See also the [`get` example](https://github.com/taiki-e/cargo-config2/blob/HEAD/
 // Refs:
 // - https://doc.rust-lang.org/nightly/cargo/reference/config.html
 
-#[cfg(test)]
+#[cfg(all(test, feature = "serde"))]
 #[path = "gen/assert_impl.rs"]
 mod assert_impl;
+#[cfg(feature = "serde")]
 #[path = "gen/is_none.rs"]
 mod is_none_impl;
 
+#[cfg(feature = "serde")]
 #[macro_use]
 mod error;
 
+#[cfg(feature = "serde")]
 #[macro_use]
 mod process;
 
+#[cfg(feature = "serde")]
 mod cfg_expr;
+#[cfg(feature = "serde")]
 pub mod de;
+#[cfg(feature = "serde")]
 mod easy;
+#[cfg(feature = "serde")]
 mod env;
+#[cfg(feature = "serde")]
+mod get;
+#[cfg(feature = "serde")]
 mod merge;
+#[cfg(feature = "serde")]
 mod resolve;
+#[cfg(feature = "serde")]
+mod validate;
+#[cfg(feature = "serde")]
 mod value;
 mod walk;
 
+#[cfg(feature = "serde")]
 #[doc(no_inline)]
-pub use crate::de::{Color, Frequency, RegistriesProtocol, VersionControlSoftware, When};
+pub use crate::de::{
+    split_encoded, split_space_separated, Color, Frequency, Jobs, RegistriesProtocol,
+    VersionControlSoftware, Warnings, When,
+};
+pub use crate::walk::{cargo_home_with_cwd, home_dir, rustup_home_with_cwd, Walk};
+#[cfg(feature = "serde")]
 pub use crate::{
     easy::{
         BuildConfig, Config, DocConfig, EnvConfigValue, Flags, FutureIncompatReportConfig,
-        NetConfig, PathAndArgs, RegistriesConfigValue, RegistryConfig, StringList, TargetConfig,
-        TermConfig, TermProgressConfig,
+        LinkOverride, NetConfig, NetSshConfig, PathAndArgs, RegistriesConfigValue, RegistryConfig,
+        RustflagsSource, StringList, TargetConfig, TermConfig, TermProgressConfig, Verbosity,
     },
     error::Error,
-    resolve::{CargoVersion, ResolveOptions, RustcVersion, TargetTriple, TargetTripleRef},
-    walk::{cargo_home_with_cwd, home_dir, rustup_home_with_cwd, Walk},
+    resolve::{
+        CargoVersion, ResolveContext, ResolveOptions, RustcVersion, TargetTriple, TargetTripleRef,
+    },
 };
+
+/// Re-exports of commonly used types.
+///
+/// ```
+/// # fn main() -> anyhow::Result<()> {
+/// use cargo_config2::prelude::*;
+///
+/// let config = Config::load_with_options(".", ResolveOptions::default())?;
+/// let rustflags: Option<Flags> = config.rustflags("x86_64-unknown-linux-gnu")?;
+/// # Ok(()) }
+/// ```
+#[cfg(feature = "serde")]
+pub mod prelude {
+    #[doc(no_inline)]
+    pub use crate::{Config, Flags, PathAndArgs, ResolveOptions, TargetTriple, TargetTripleRef};
+}
@@ -3,10 +3,10 @@
 use std::collections::{btree_map, BTreeMap};
 
 use crate::{
-    de::{self, RegistriesProtocol, VersionControlSoftware},
-    error::{Context as _, Result},
-    value::Value,
-    Color, Frequency, When,
+    de::{self, Jobs, RegistriesProtocol, VersionControlSoftware},
+    error::{Error, Result},
+    value::{Definition, Value},
+    Color, Frequency, Warnings, When,
 };
 
 // https://github.com/rust-lang/cargo/blob/0.74.0/src/cargo/util/config/mod.rs#L2107-L2115
@@ -47,12 +47,14 @@ macro_rules! merge_non_container {
 }
 merge_non_container!(bool);
 merge_non_container!(i32);
+merge_non_container!(Jobs);
 merge_non_container!(u32);
 merge_non_container!(String);
 merge_non_container!(Color);
 merge_non_container!(VersionControlSoftware);
 merge_non_container!(Frequency);
 merge_non_container!(When);
+merge_non_container!(Warnings);
 merge_non_container!(RegistriesProtocol);
 
 impl<T: Merge> Merge for Option<T> {
@@ -65,6 +67,15 @@ impl<T: Merge> Merge for Option<T> {
         Ok(())
     }
 }
+impl<T> Merge for Vec<T> {
+    fn merge(&mut self, mut low: Self, _force: bool) -> Result<()> {
+        // https://doc.rust-lang.org/nightly/cargo/reference/config.html#hierarchical-structure
+        // > Arrays will be joined together with higher precedence items being placed later in the merged array.
+        low.append(self);
+        *self = low;
+        Ok(())
+    }
+}
 impl Merge for de::StringOrArray {
     fn merge(&mut self, low: Self, force: bool) -> Result<()> {
         match (self, low) {
@@ -80,7 +91,12 @@ impl Merge for de::StringOrArray {
                 *this = low;
             }
             (expected, actual) => {
-                bail!("expected {}, but found {}", expected.kind(), actual.kind());
+                return Err(kind_mismatch(
+                    expected.kind(),
+                    expected.definition(),
+                    actual.kind(),
+                    actual.definition(),
+                ));
             }
         }
         Ok(())
@@ -109,7 +125,12 @@ impl Merge for de::PathAndArgs {
                 self.args.append(&mut low.args);
             }
             (expected, actual) => {
-                bail!("expected {}, but found {}", expected.as_str(), actual.as_str());
+                return Err(kind_mismatch(
+                    expected.as_str(),
+                    self.path.0.definition.as_ref(),
+                    actual.as_str(),
+                    low.path.0.definition.as_ref(),
+                ));
             }
         }
         Ok(())
@@ -130,7 +151,12 @@ impl Merge for de::StringList {
                 self.list = low.list;
             }
             (expected, actual) => {
-                bail!("expected {}, but found {}", expected.as_str(), actual.as_str());
+                return Err(kind_mismatch(
+                    expected.as_str(),
+                    first_definition(&self.list),
+                    actual.as_str(),
+                    first_definition(&low.list),
+                ));
             }
         }
         Ok(())
@@ -153,7 +179,12 @@ impl Merge for de::EnvConfigValue {
                 this_relative.merge(low_relative, force)?;
             }
             (expected, actual) => {
-                bail!("expected {}, but found {}", expected.kind(), actual.kind());
+                return Err(kind_mismatch(
+                    expected.kind(),
+                    expected.definition(),
+                    actual.kind(),
+                    actual.definition(),
+                ));
             }
         }
         Ok(())
@@ -174,7 +205,12 @@ impl Merge for de::Flags {
                 self.flags = low.flags;
             }
             (expected, actual) => {
-                bail!("expected {}, but found {}", expected.as_str(), actual.as_str());
+                return Err(kind_mismatch(
+                    expected.as_str(),
+                    first_definition(&self.flags),
+                    actual.as_str(),
+                    first_definition(&low.flags),
+                ));
             }
         }
         Ok(())
@@ -186,12 +222,7 @@ impl<V: Merge + Clone + core::fmt::Debug> Merge for BTreeMap<String, V> {
             match self.entry(key.clone()) {
                 btree_map::Entry::Occupied(mut entry) => {
                     let entry = entry.get_mut();
-                    entry.merge(value.clone(), force).with_context(|| {
-                        format!(
-                            "failed to merge key `{key}` between \
-                             {entry:?} and {value:?}", /* TODO: do not use debug output */
-                        )
-                    })?;
+                    entry.merge(value.clone(), force).map_err(|e| e.merge_key(&key))?;
                 }
                 btree_map::Entry::Vacant(entry) => {
                     entry.insert(value);
@@ -201,3 +232,86 @@ impl<V: Merge + Clone + core::fmt::Debug> Merge for BTreeMap<String, V> {
         Ok(())
     }
 }
+
+// Raw tables do not have definitions, so only the kinds of the values are
+// checked here. Mismatches in the keys modeled by de::Config are reported by
+// the merge of the typed config with the definitions.
+#[cfg(feature = "toml_edit")]
+impl Merge for toml_edit::Table {
+    fn merge(&mut self, low: Self, force: bool) -> Result<()> {
+        merge_table(self, low, force)
+    }
+}
+#[cfg(feature = "toml_edit")]
+impl Merge for toml_edit::Item {
+    fn merge(&mut self, low: Self, force: bool) -> Result<()> {
+        fn into_array(item: toml_edit::Item) -> toml_edit::Array {
+            match item {
+                toml_edit::Item::Value(toml_edit::Value::Array(array)) => array,
+                toml_edit::Item::ArrayOfTables(array) => array.into_array(),
+                _ => toml_edit::Array::new(),
+            }
+        }
+        let is_array = |item: &toml_edit::Item| item.is_array() || item.is_array_of_tables();
+        if let (Some(this), true) = (self.as_table_like_mut(), low.is_table_like()) {
+            merge_table(this, low.into_table().unwrap_or_default(), force)?;
+        } else if is_array(self) && is_array(&low) {
+            // https://doc.rust-lang.org/nightly/cargo/reference/config.html#hierarchical-structure
+            // > Arrays will be joined together with higher precedence items being placed later in the merged array.
+            let this = into_array(core::mem::take(self));
+            let mut low = into_array(low);
+            low.extend(this);
+            *self = toml_edit::value(low);
+        } else if !self.is_table_like()
+            && !is_array(self)
+            && !low.is_table_like()
+            && !is_array(&low)
+        {
+            if force {
+                *self = low;
+            }
+        } else {
+            return Err(kind_mismatch(self.type_name(), None, low.type_name(), None));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "toml_edit")]
+fn merge_table(
+    this: &mut dyn toml_edit::TableLike,
+    low: toml_edit::Table,
+    force: bool,
+) -> Result<()> {
+    for (key, value) in low {
+        match this.get_mut(&key) {
+            Some(this) => {
+                this.merge(value, force).map_err(|e| e.merge_key(&key))?;
+            }
+            None => {
+                this.insert(&key, value);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn first_definition(list: &[Value<String>]) -> Option<&Definition> {
+    list.first().and_then(|v| v.definition.as_ref())
+}
+
+/// Creates an error for merging a string into an array or vice versa.
+fn kind_mismatch(
+    expected: &str,
+    expected_definition: Option<&Definition>,
+    actual: &str,
+    actual_definition: Option<&Definition>,
+) -> Error {
+    let defined_in =
+        |definition: Option<&Definition>| definition.map(|d| format!(" (defined in {d})"));
+    format_err!(
+        "expected {expected}{}, but found {actual}{}",
+        defined_in(expected_definition).unwrap_or_default(),
+        defined_in(actual_definition).unwrap_or_default()
+    )
+}
@@ -1,17 +1,13 @@
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
-use core::{
-    cell::{OnceCell, RefCell},
-    cmp,
-    hash::Hash,
-    iter,
-    str::FromStr,
-};
+use core::{cmp, fmt, hash::Hash, iter, str::FromStr};
 use std::{
     borrow::Cow,
     collections::{HashMap, HashSet},
     ffi::{OsStr, OsString},
+    fs,
     path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard, OnceLock, PoisonError},
 };
 
 use serde::{
@@ -38,11 +34,23 @@ pub struct ResolveOptions {
     #[allow(clippy::option_option)]
     cargo_home: Option<Option<PathBuf>>,
     host_triple: Option<String>,
+    host_cfgs: Option<String>,
+    current_dir: Option<PathBuf>,
+    include_cargo_home: Option<bool>,
+    require_cargo_home: Option<bool>,
+    canonicalize_cwd: Option<bool>,
+    disable_rustc: Option<bool>,
+    combine_rustflags: Option<bool>,
 }
 
 impl ResolveOptions {
     /// Sets `rustc` path and args.
     ///
+    /// This is also the rustc used to get the rustc version and the target
+    /// information (`rustc --print cfg`) when evaluating `cfg(...)` in
+    /// `[target.<cfg>]` tables. When set, it takes precedence over
+    /// `build.rustc` and the rustc wrappers.
+    ///
     /// # Default value
     ///
     /// [`Config::rustc`](crate::Config::rustc)
@@ -55,6 +63,14 @@ impl ResolveOptions {
     /// # Default value
     ///
     /// The value of the `CARGO` environment variable if it is set. Otherwise, "cargo".
+    ///
+    /// # Rustup proxy
+    ///
+    /// If `rustc` is not set, the `rustc` binary next to `cargo` is used.
+    /// When `cargo` is a rustup proxy (e.g., `~/.cargo/bin/cargo`), that
+    /// `rustc` is also a rustup proxy, which doesn't know the toolchain
+    /// override shorthand (`+toolchain`) passed to cargo. In that case, the
+    /// rustc version is obtained from `cargo -vV` instead.
     pub fn cargo<S: Into<OsString>>(mut self, cargo: S) -> Self {
         self.cargo = Some(cargo.into());
         self
@@ -72,15 +88,128 @@ impl ResolveOptions {
         self.cargo_home = Some(cargo_home.into());
         self
     }
+    /// Sets whether to read the config in `CARGO_HOME` (`$CARGO_HOME/config.toml`).
+    ///
+    /// If `false`, only project-local config files are read. This is useful
+    /// for reproducible builds that should not be affected by user-global config.
+    /// The config in `CARGO_HOME` is skipped even if `CARGO_HOME` is in an
+    /// ancestor of the current directory.
+    ///
+    /// # Default value
+    ///
+    /// `true`
+    pub fn include_cargo_home(mut self, include: bool) -> Self {
+        self.include_cargo_home = Some(include);
+        self
+    }
+    /// Sets whether to return an error if `CARGO_HOME` cannot be determined.
+    ///
+    /// By default, if neither the `CARGO_HOME` environment variable nor the
+    /// home directory is available, the config in `CARGO_HOME` is silently
+    /// skipped. If `true`, loading config returns an error instead.
+    ///
+    /// This has no effect if `CARGO_HOME` path is set by [`Self::cargo_home`]
+    /// or the config in `CARGO_HOME` is not read (see [`Self::include_cargo_home`]).
+    ///
+    /// # Default value
+    ///
+    /// `false`
+    pub fn require_cargo_home(mut self, require: bool) -> Self {
+        self.require_cargo_home = Some(require);
+        self
+    }
+    /// Sets whether to canonicalize the current directory before searching
+    /// config files.
+    ///
+    /// Config files are searched in the ancestors of the current directory.
+    /// If the given directory contains symlinks, its ancestors may differ from
+    /// the ancestors of the directory cargo sees, because cargo uses
+    /// [`std::env::current_dir`], which returns a path with symlinks resolved
+    /// on most platforms. If `true`, the current directory is canonicalized
+    /// with [`std::fs::canonicalize`] so config files are searched in the same
+    /// directories as cargo. The canonicalized directory is also used to
+    /// resolve relative paths defined in environment variables.
+    ///
+    /// This is applied by [`Config::load_with_options`](crate::Config::load_with_options)
+    /// and [`resolve_context`](Self::resolve_context).
+    ///
+    /// # Default value
+    ///
+    /// `false`
+    pub fn canonicalize_cwd(mut self, canonicalize: bool) -> Self {
+        self.canonicalize_cwd = Some(canonicalize);
+        self
+    }
     /// Sets host target triple.
     ///
     /// # Default value
     ///
     /// Parse the version output of `cargo` specified by [`Self::cargo`].
+    ///
+    /// Note that this does not prevent spawning rustc to get the target
+    /// information of the host when evaluating `cfg(...)` in `[target.<cfg>]`
+    /// tables. Use [`Self::host_cfgs`] to set it as well.
     pub fn host_triple<S: Into<String>>(mut self, triple: S) -> Self {
         self.host_triple = Some(triple.into());
         self
     }
+    /// Sets the target information of the host, which is used to evaluate
+    /// `cfg(...)` in `[target.<cfg>]` tables for the host target.
+    ///
+    /// Each element is in the same format as a line of the output of
+    /// `rustc --print cfg`, e.g., `unix` or `target_os="linux"`.
+    ///
+    /// If the host triple is not set by [`Self::host_triple`], this is applied
+    /// to the host triple detected from `cargo`, so `cargo` is still spawned
+    /// (unless spawning is disabled by [`Self::disable_rustc`], in which case
+    /// this has no effect).
+    ///
+    /// # Default value
+    ///
+    /// Parse the output of `rustc --print cfg`.
+    pub fn host_cfgs<I: IntoIterator<Item = S>, S: AsRef<str>>(mut self, cfgs: I) -> Self {
+        let mut list = String::new();
+        for cfg in cfgs {
+            list.push_str(cfg.as_ref());
+            list.push('\n');
+        }
+        self.host_cfgs = Some(list);
+        self
+    }
+    /// Sets whether to prevent spawning `rustc` and `cargo` during config resolution.
+    ///
+    /// If `true`, operations that need information from `rustc` or `cargo`
+    /// (the host triple, the rustc and cargo versions, and the target
+    /// information used to evaluate `cfg(...)` in `[target.<cfg>]` tables)
+    /// return an error instead of spawning a process.
+    ///
+    /// The host triple is still available if it is set by [`Self::host_triple`].
+    ///
+    /// # Default value
+    ///
+    /// `false`
+    pub fn disable_rustc(mut self, disable: bool) -> Self {
+        self.disable_rustc = Some(disable);
+        self
+    }
+    /// Sets whether to combine `build.rustflags` with the target-specific rustflags.
+    ///
+    /// **Note:** This is not cargo-standard. Cargo uses the target-specific
+    /// rustflags (`target.<triple>.rustflags`, `CARGO_TARGET_<triple>_RUSTFLAGS`,
+    /// and `target.<cfg>.rustflags`) instead of `build.rustflags` if any of them
+    /// are set. If `true`, `build.rustflags` are appended to the target-specific
+    /// rustflags instead.
+    ///
+    /// This does not affect `CARGO_ENCODED_RUSTFLAGS` and `RUSTFLAGS`, which
+    /// override all other rustflags.
+    ///
+    /// # Default value
+    ///
+    /// `false`
+    pub fn combine_rustflags(mut self, combine: bool) -> Self {
+        self.combine_rustflags = Some(combine);
+        self
+    }
     /// Sets the specified key-values as environment variables to be read during
     /// config resolution.
     ///
@@ -105,60 +234,175 @@ impl ResolveOptions {
         self.env = Some(env);
         self
     }
+    /// Sets the current directory used to resolve relative paths in
+    /// [`resolve_context`](Self::resolve_context).
+    ///
+    /// This is ignored by [`Config::load_with_options`](crate::Config::load_with_options),
+    /// which uses the directory passed to it.
+    ///
+    /// # Default value
+    ///
+    /// [`std::env::current_dir`]
+    pub fn current_dir<P: Into<PathBuf>>(mut self, current_dir: P) -> Self {
+        self.current_dir = Some(current_dir.into());
+        self
+    }
 
-    #[doc(hidden)] // Not public API.
-    pub fn into_context(mut self, current_dir: PathBuf) -> ResolveContext {
+    /// Creates a [`ResolveContext`] from these options.
+    ///
+    /// This is useful to resolve [`de::Config`](crate::de::Config) manually.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use cargo_config2::{de, Jobs, ResolveOptions};
+    ///
+    /// let cx = ResolveOptions::default()
+    ///     .env([("CARGO_BUILD_JOBS", "2")])
+    ///     .current_dir(std::env::current_dir()?)
+    ///     .resolve_context()?;
+    /// let mut config = de::Config::default();
+    /// config.apply_env(&cx)?;
+    /// assert_eq!(config.build.jobs.unwrap().val, Jobs::Count(2));
+    /// # Ok(()) }
+    /// ```
+    pub fn resolve_context(mut self) -> Result<ResolveContext> {
+        let current_dir = match self.current_dir.take() {
+            Some(current_dir) => current_dir,
+            None => std::env::current_dir().context("failed to get current directory")?,
+        };
+        let current_dir = self.current_dir_for_load(current_dir)?;
+        Ok(self.into_context(current_dir))
+    }
+    pub(crate) fn current_dir_for_load(&self, current_dir: PathBuf) -> Result<PathBuf> {
+        if self.canonicalize_cwd.unwrap_or(false) {
+            fs::canonicalize(&current_dir).with_context(|| {
+                format!("failed to canonicalize current directory `{}`", current_dir.display())
+            })
+        } else {
+            Ok(current_dir)
+        }
+    }
+
+    /// Creates a [`ResolveContext`] from these options, using the given
+    /// directory as the current directory.
+    ///
+    /// Unlike [`resolve_context`](Self::resolve_context), this does not fail
+    /// because the current directory is given explicitly, and this ignores
+    /// the directory set by [`current_dir`](Self::current_dir).
+    ///
+    /// A context can be reused to resolve many [`de::Config`](crate::de::Config)s
+    /// with the same environment, without reading the environment variables again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// use cargo_config2::{de, Jobs, ResolveOptions};
+    ///
+    /// let cx = ResolveOptions::default()
+    ///     .env([("CARGO_BUILD_JOBS", "2"), ("CARGO_NET_OFFLINE", "true")])
+    ///     .into_resolve_context(std::env::current_dir()?);
+    ///
+    /// let mut a = de::Config::default();
+    /// a.apply_env(&cx)?;
+    /// let mut b: de::Config = toml::from_str("build.jobs = 1\nnet.retry = 3")?;
+    /// b.apply_env(&cx)?;
+    ///
+    /// assert_eq!(a.build.jobs.unwrap().val, Jobs::Count(2));
+    /// assert_eq!(b.build.jobs.unwrap().val, Jobs::Count(2));
+    /// assert_eq!(b.net.retry.unwrap().val, 3);
+    /// assert_eq!(b.net.offline.unwrap().val, true);
+    /// # Ok(()) }
+    /// ```
+    pub fn into_resolve_context<P: Into<PathBuf>>(self, current_dir: P) -> ResolveContext {
+        self.into_context(current_dir.into())
+    }
+
+    pub(crate) fn into_context(mut self, current_dir: PathBuf) -> ResolveContext {
         if self.env.is_none() {
             self = self.env(std::env::vars_os());
         }
         let env = self.env.unwrap();
+        let explicit_rustc = self.rustc.is_some();
         let rustc = match self.rustc {
-            Some(rustc) => OnceCell::from(rustc),
-            None => OnceCell::new(),
+            Some(rustc) => OnceLock::from(rustc),
+            None => OnceLock::new(),
         };
         let cargo = match self.cargo {
             Some(cargo) => cargo,
             None => env.get("CARGO").cloned().unwrap_or_else(|| "cargo".into()),
         };
+        let require_cargo_home = self.cargo_home.is_none()
+            && self.include_cargo_home.unwrap_or(true)
+            && self.require_cargo_home.unwrap_or(false);
         let cargo_home = match self.cargo_home {
-            Some(cargo_home) => OnceCell::from(cargo_home),
-            None => OnceCell::new(),
+            Some(cargo_home) => OnceLock::from(cargo_home),
+            None => OnceLock::new(),
         };
         let host_triple = match self.host_triple {
-            Some(host_triple) => OnceCell::from(host_triple),
-            None => OnceCell::new(),
+            Some(host_triple) => OnceLock::from(host_triple),
+            None => OnceLock::new(),
         };
 
         ResolveContext {
             env,
             rustc,
+            explicit_rustc,
             cargo,
             cargo_home,
             host_triple,
-            rustc_version: OnceCell::new(),
-            cargo_version: OnceCell::new(),
-            cfg: RefCell::default(),
+            rustc_version: OnceLock::new(),
+            cargo_version: OnceLock::new(),
+            cfg: Cache::default(),
+            host_cfg: self.host_cfgs.map(|l| Cfg::parse(&l)),
             current_dir,
+            include_cargo_home: self.include_cargo_home.unwrap_or(true),
+            require_cargo_home,
+            disable_rustc: self.disable_rustc.unwrap_or(false),
+            combine_rustflags: self.combine_rustflags.unwrap_or(false),
         }
     }
 }
 
-#[doc(hidden)] // Not public API.
+/// A context for resolving configuration.
+///
+/// This is created by [`ResolveOptions::resolve_context`].
 #[derive(Debug, Clone)]
 #[must_use]
 pub struct ResolveContext {
     pub(crate) env: HashMap<String, OsString>,
-    rustc: OnceCell<easy::PathAndArgs>,
+    rustc: OnceLock<easy::PathAndArgs>,
+    /// `true` if `rustc` was set by [`ResolveOptions::rustc`].
+    explicit_rustc: bool,
     pub(crate) cargo: OsString,
-    cargo_home: OnceCell<Option<PathBuf>>,
-    host_triple: OnceCell<String>,
-    rustc_version: OnceCell<RustcVersion>,
-    cargo_version: OnceCell<CargoVersion>,
-    cfg: RefCell<CfgMap>,
+    cargo_home: OnceLock<Option<PathBuf>>,
+    host_triple: OnceLock<String>,
+    rustc_version: OnceLock<RustcVersion>,
+    cargo_version: OnceLock<CargoVersion>,
+    cfg: Cache<CfgMap>,
+    host_cfg: Option<Cfg>,
     pub(crate) current_dir: PathBuf,
+    pub(crate) include_cargo_home: bool,
+    require_cargo_home: bool,
+    disable_rustc: bool,
+    pub(crate) combine_rustflags: bool,
 }
 
 impl ResolveContext {
+    /// Clones this context without the cached results of spawning `rustc` and `cargo`.
+    ///
+    /// The values set by [`ResolveOptions`] (and the rustc path and `CARGO_HOME`
+    /// that do not depend on the toolchain) are kept.
+    pub(crate) fn fresh_clone(&self) -> Self {
+        Self {
+            rustc_version: OnceLock::new(),
+            cargo_version: OnceLock::new(),
+            cfg: Cache::default(),
+            ..self.clone()
+        }
+    }
     pub(crate) fn rustc(&self, build_config: &easy::BuildConfig) -> &PathAndArgs {
         self.rustc.get_or_init(|| {
             // https://github.com/rust-lang/cargo/pull/10896
@@ -176,11 +420,29 @@ impl ResolveContext {
         })
     }
     pub(crate) fn rustc_for_version(&self, build_config: &easy::BuildConfig) -> PathAndArgs {
+        if self.explicit_rustc {
+            return self.rustc(build_config).clone();
+        }
         // Do not apply RUSTC_WORKSPACE_WRAPPER: https://github.com/cuviper/autocfg/issues/58#issuecomment-2067625980
+        self.rustc_for(build_config, false)
+    }
+    /// Returns the rustc that cargo uses to compile a crate.
+    ///
+    /// Like cargo, RUSTC_WORKSPACE_WRAPPER is applied only to workspace members,
+    /// and RUSTC_WRAPPER (if any) wraps it.
+    /// https://github.com/rust-lang/cargo/blob/0.80.0/src/cargo/util/rustc.rs
+    pub(crate) fn rustc_for(
+        &self,
+        build_config: &easy::BuildConfig,
+        is_workspace_member: bool,
+    ) -> PathAndArgs {
         let rustc =
             build_config.rustc.as_ref().map_or_else(|| rustc_path(&self.cargo), PathBuf::from);
         let rustc_wrapper = build_config.rustc_wrapper.clone();
-        let mut rustc = rustc_wrapper.into_iter().chain(iter::once(rustc));
+        let rustc_workspace_wrapper =
+            build_config.rustc_workspace_wrapper.clone().filter(|_| is_workspace_member);
+        let mut rustc =
+            rustc_wrapper.into_iter().chain(rustc_workspace_wrapper).chain(iter::once(rustc));
         PathAndArgs {
             path: rustc.next().unwrap(),
             args: rustc.map(PathBuf::into_os_string).collect(),
@@ -189,10 +451,23 @@ impl ResolveContext {
     pub(crate) fn cargo_home(&self, cwd: &Path) -> Option<&Path> {
         self.cargo_home.get_or_init(|| walk::cargo_home_with_cwd(cwd)).as_deref()
     }
+    /// Returns `CARGO_HOME` path to read config from, or an error if it is
+    /// required but cannot be determined.
+    pub(crate) fn cargo_home_for_load(&self, cwd: &Path) -> Result<Option<&Path>> {
+        let cargo_home = self.cargo_home(cwd);
+        if cargo_home.is_none() && self.require_cargo_home {
+            bail!(
+                "failed to determine CARGO_HOME; set the CARGO_HOME environment variable or \
+                 the path of the home directory"
+            );
+        }
+        Ok(cargo_home)
+    }
     pub(crate) fn host_triple(&self, build_config: &easy::BuildConfig) -> Result<&str> {
         if let Some(host) = self.host_triple.get() {
             return Ok(host);
         }
+        self.check_spawn_allowed("host triple")?;
         let cargo_host = verbose_version(cmd!(&self.cargo)).and_then(|ref vv| {
             let r = self.cargo_version.set(cargo_version(vv)?);
             debug_assert!(r.is_ok());
@@ -213,18 +488,29 @@ impl ResolveContext {
         if let Some(&rustc_version) = self.rustc_version.get() {
             return Ok(rustc_version);
         }
+        self.check_spawn_allowed("rustc version")?;
         let _ = self.host_triple(build_config);
         if let Some(&rustc_version) = self.rustc_version.get() {
             return Ok(rustc_version);
         }
-        let vv = &verbose_version((&self.rustc_for_version(build_config)).into())?;
-        let rustc_version = rustc_version(vv)?;
+        let rustc_version = if self.rustc_is_rustup_proxy(build_config) {
+            // The rustup proxy selects the toolchain by itself and ignores the
+            // toolchain override shorthand (`+toolchain`) passed to cargo, so
+            // it may report a different version than the rustc that cargo
+            // uses. The rustc and cargo in the same toolchain have the same
+            // version, so use the version of cargo instead.
+            let CargoVersion { major, minor, patch, nightly } = self.cargo_version(build_config)?;
+            RustcVersion { major, minor, patch: Some(patch), nightly: rustc_nightly(nightly) }
+        } else {
+            rustc_version(&verbose_version((&self.rustc_for_version(build_config)).into())?)?
+        };
         Ok(*self.rustc_version.get_or_init(|| rustc_version))
     }
     pub(crate) fn cargo_version(&self, build_config: &easy::BuildConfig) -> Result<CargoVersion> {
         if let Some(&cargo_version) = self.cargo_version.get() {
             return Ok(cargo_version);
         }
+        self.check_spawn_allowed("cargo version")?;
         let _ = self.host_triple(build_config);
         if let Some(&cargo_version) = self.cargo_version.get() {
             return Ok(cargo_version);
@@ -233,6 +519,23 @@ impl ResolveContext {
         let cargo_version = cargo_version(vv)?;
         Ok(*self.cargo_version.get_or_init(|| cargo_version))
     }
+    /// Returns `true` if rustc is not set explicitly and the rustc next to
+    /// the cargo binary (see [`rustc_path`]) is a rustup proxy.
+    fn rustc_is_rustup_proxy(&self, build_config: &easy::BuildConfig) -> bool {
+        !self.explicit_rustc
+            && build_config.rustc.is_none()
+            && build_config.rustc_wrapper.is_none()
+            && is_rustup_proxy(&rustc_path(&self.cargo))
+    }
+    fn check_spawn_allowed(&self, what: &str) -> Result<()> {
+        if self.disable_rustc {
+            bail!(
+                "failed to get {what}: spawning rustc and cargo is disabled by \
+                 `ResolveOptions::disable_rustc`"
+            );
+        }
+        Ok(())
+    }
 
     // micro-optimization for static name -- avoiding name allocation can speed up
     // de::Config::apply_env by up to 40% because most env var names we fetch are static.
@@ -287,9 +590,71 @@ impl ResolveContext {
         build_config: &easy::BuildConfig,
     ) -> Result<bool> {
         let expr = Expression::parse(expr).map_err(Error::new)?;
-        let mut cfg_map = self.cfg.borrow_mut();
+        let mut cfg_map = self.cfg_map(target, build_config)?;
         cfg_map.eval_cfg(&expr, target, || self.rustc(build_config).into())
     }
+    pub(crate) fn target_cfgs(
+        &self,
+        target: &TargetTripleRef<'_>,
+        build_config: &easy::BuildConfig,
+    ) -> Result<Vec<String>> {
+        let mut cfg_map = self.cfg_map(target, build_config)?;
+        Ok(cfg_map.get_or_init(target, || self.rustc(build_config).into())?.lines.clone())
+    }
+    /// Locks the cache of target information, after inserting the values
+    /// set by [`ResolveOptions::host_cfgs`] if the given target is the host.
+    fn cfg_map(
+        &self,
+        target: &TargetTripleRef<'_>,
+        build_config: &easy::BuildConfig,
+    ) -> Result<MutexGuard<'_, CfgMap>> {
+        let mut cfg_map = self.cfg.lock();
+        if !cfg_map.map.contains_key(target.cli_target()) {
+            if let Some(host_cfg) = &self.host_cfg {
+                if self.host_triple(build_config).is_ok_and(|host| target.cli_target() == host) {
+                    cfg_map
+                        .map
+                        .insert(TargetTripleBorrow(target.clone().into_owned()), host_cfg.clone());
+                    return Ok(cfg_map);
+                }
+            }
+            self.check_spawn_allowed(&format!(
+                "target information for `{}`",
+                target.cli_target_string()
+            ))?;
+        }
+        Ok(cfg_map)
+    }
+}
+
+/// A thread-safe cache that can be cloned.
+#[derive(Debug, Default)]
+pub(crate) struct Cache<T>(Mutex<T>);
+
+impl<T> Cache<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        Self(Mutex::new(value))
+    }
+    pub(crate) fn lock(&self) -> MutexGuard<'_, T> {
+        // The cache is always in a consistent state, even if a thread panicked
+        // while holding the lock.
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T: Clone> Clone for Cache<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.lock().clone())
+    }
+}
+
+impl<T: Serialize> Serialize for Cache<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.lock().serialize(serializer)
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -304,14 +669,7 @@ impl CfgMap {
         target: &TargetTripleRef<'_>,
         rustc: impl FnOnce() -> ProcessBuilder,
     ) -> Result<bool> {
-        let cfg = match self.map.get(target.cli_target()) {
-            Some(cfg) => cfg,
-            None => {
-                let cfg = Cfg::from_rustc(rustc(), target)?;
-                self.map.insert(TargetTripleBorrow(target.clone().into_owned()), cfg);
-                &self.map[target.cli_target()]
-            }
-        };
+        let cfg = self.get_or_init(target, rustc)?;
         Ok(expr.eval(|pred| match pred {
             Predicate::Flag(flag) => {
                 match *flag {
@@ -329,10 +687,23 @@ impl CfgMap {
             }
         }))
     }
+    fn get_or_init(
+        &mut self,
+        target: &TargetTripleRef<'_>,
+        rustc: impl FnOnce() -> ProcessBuilder,
+    ) -> Result<&Cfg> {
+        if !self.map.contains_key(target.cli_target()) {
+            let cfg = Cfg::from_rustc(rustc(), target)?;
+            self.map.insert(TargetTripleBorrow(target.clone().into_owned()), cfg);
+        }
+        Ok(&self.map[target.cli_target()])
+    }
 }
 
 #[derive(Debug, Clone)]
 struct Cfg {
+    /// Non-empty lines of the output of `rustc --print cfg`.
+    lines: Vec<String>,
     flags: HashSet<String>,
     key_values: HashMap<String, HashSet<String>>,
 }
@@ -345,6 +716,7 @@ impl Cfg {
     }
 
     fn parse(list: &str) -> Self {
+        let mut lines = vec![];
         let mut flags = HashSet::default();
         let mut key_values = HashMap::<String, HashSet<String>>::default();
 
@@ -353,6 +725,7 @@ impl Cfg {
             if line.is_empty() {
                 continue;
             }
+            lines.push(line.to_owned());
             match line.split_once('=') {
                 None => {
                     flags.insert(line.to_owned());
@@ -379,10 +752,17 @@ impl Cfg {
             }
         }
 
-        Self { flags, key_values }
+        Self { lines, flags, key_values }
     }
 }
 
+/// A target triple or a path to a custom target spec file.
+///
+/// Equality, ordering, and hashing are based on the value passed to cargo's
+/// `--target` option (see [`cli_target_string`](Self::cli_target_string)).
+/// Therefore, a target triple and a spec path are always treated as different
+/// targets, even if the file stem of the spec path is the same as the triple.
+/// This matches cargo's behavior.
 #[derive(Debug, Clone)]
 pub struct TargetTripleRef<'a> {
     triple: Cow<'a, str>,
@@ -430,6 +810,9 @@ fn is_spec_path(triple_or_spec_path: &str) -> bool {
         || triple_or_spec_path.contains('/')
         || triple_or_spec_path.contains('\\')
 }
+fn spec_path_file_stem(spec_path: &str) -> &str {
+    Path::new(spec_path).file_stem().and_then(OsStr::to_str).unwrap_or_default()
+}
 fn resolve_spec_path(
     spec_path: &str,
     def: Option<&Definition>,
@@ -451,12 +834,11 @@ impl<'a> TargetTripleRef<'a> {
     ) -> Self {
         // Handles custom target
         if is_spec_path(&triple_or_spec_path) {
+            // `triple_or_spec_path` is valid UTF-8, so the file stem is also valid UTF-8.
+            // If the path has no file stem (e.g., `/` or `a/..`), the triple is empty.
             let triple = match &triple_or_spec_path {
-                // `triple_or_spec_path` is valid UTF-8, so unwrap here will never panic.
-                &Cow::Borrowed(v) => Path::new(v).file_stem().unwrap().to_str().unwrap().into(),
-                Cow::Owned(v) => {
-                    Path::new(v).file_stem().unwrap().to_str().unwrap().to_owned().into()
-                }
+                &Cow::Borrowed(v) => spec_path_file_stem(v).into(),
+                Cow::Owned(v) => spec_path_file_stem(v).to_owned().into(),
             };
             Self {
                 triple,
@@ -486,7 +868,44 @@ impl<'a> TargetTripleRef<'a> {
     pub fn spec_path(&self) -> Option<&Path> {
         self.spec_path.as_deref()
     }
-    pub(crate) fn cli_target_string(&self) -> Cow<'_, str> {
+    /// Checks that the target spec path exists and is a file.
+    ///
+    /// If this is not a custom target, this does nothing. Relative spec paths
+    /// are resolved relative to `current_dir`.
+    ///
+    /// Cargo reports an error when the spec path is invalid only when passing
+    /// it to rustc, so this is useful to detect the error earlier.
+    pub fn validate_spec_path(&self, current_dir: &Path) -> Result<()> {
+        let Some(spec_path) = self.spec_path() else { return Ok(()) };
+        let spec_path = current_dir.join(spec_path);
+        let metadata = fs::metadata(&spec_path).with_context(|| {
+            format!("target path \"{}\" is not a valid file", spec_path.display())
+        })?;
+        if !metadata.is_file() {
+            bail!("target path \"{}\" is not a valid file", spec_path.display());
+        }
+        Ok(())
+    }
+    /// Returns the value to pass to cargo's `--target` option.
+    ///
+    /// This is the spec path if this is a custom target, otherwise the target triple.
+    /// Like cargo, a spec path that is not valid UTF-8 is converted lossily.
+    /// This is also the value that cargo passes to rustc's `--target` option.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use cargo_config2::TargetTriple;
+    ///
+    /// let t = TargetTriple::from("x86_64-unknown-linux-gnu");
+    /// assert_eq!(t.cli_target_string(), "x86_64-unknown-linux-gnu");
+    ///
+    /// let t = TargetTriple::from("target-specs/avr-unknown-gnu-atmega2560.json");
+    /// assert_eq!(t.triple(), "avr-unknown-gnu-atmega2560");
+    /// assert_eq!(t.cli_target_string(), "target-specs/avr-unknown-gnu-atmega2560.json");
+    /// assert_eq!(t.as_cli_os_str(), t.spec_path().unwrap().as_os_str());
+    /// ```
+    pub fn cli_target_string(&self) -> Cow<'_, str> {
         // Cargo converts spec path containing non-UTF8 byte to string with
         // to_string_lossy before passing it to rustc.
         // This is not good behavior but we just follow the behavior of cargo for now.
@@ -506,12 +925,69 @@ impl<'a> TargetTripleRef<'a> {
         // ```
         self.cli_target().to_string_lossy()
     }
-    pub(crate) fn cli_target(&self) -> &OsStr {
+    /// Returns the value to pass to cargo's `--target` option, without lossy
+    /// conversion.
+    ///
+    /// This is the spec path if this is a custom target, otherwise the target
+    /// triple. See [`cli_target_string`](Self::cli_target_string) for the value
+    /// that cargo actually passes to rustc.
+    pub fn as_cli_os_str(&self) -> &OsStr {
         match self.spec_path() {
             Some(v) => v.as_os_str(),
             None => OsStr::new(self.triple()),
         }
     }
+    pub(crate) fn cli_target(&self) -> &OsStr {
+        self.as_cli_os_str()
+    }
+}
+
+impl TargetTriple {
+    /// Creates a custom target from the given target spec path.
+    ///
+    /// Unlike conversion from a string, this always treats the given path as a
+    /// spec path, even if it does not have the `.json` extension or does not
+    /// contain path separators. The target triple is derived from the file stem.
+    ///
+    /// If `current_dir` is specified, a relative path is resolved relative to it.
+    pub fn from_spec_path<P: Into<PathBuf>>(spec_path: P, current_dir: Option<&Path>) -> Self {
+        let mut spec_path: PathBuf = spec_path.into();
+        if let Some(current_dir) = current_dir {
+            if spec_path.is_relative() {
+                spec_path = current_dir.join(spec_path);
+            }
+        }
+        let triple = spec_path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+        Self { triple: triple.into(), spec_path: Some(spec_path.into()) }
+    }
+    /// Parses the given string as a value of cargo's `--target` option.
+    ///
+    /// Unlike conversion from a string, this returns an error if the given
+    /// string is a `cfg(...)` expression, is empty, or is a target triple that
+    /// contains characters that cannot be used in target triples. This is
+    /// useful to validate `--target` before resolving config.
+    ///
+    /// Spec paths (strings ending with `.json` or containing path separators)
+    /// are accepted as is. Use [`validate_spec_path`](Self::validate_spec_path)
+    /// to check that the file exists.
+    pub fn parse(s: &str) -> Result<Self> {
+        if s.is_empty() {
+            bail!("target triple cannot be empty");
+        }
+        if s.starts_with("cfg(") {
+            bail!("'{s}' is not valid target triple");
+        }
+        if is_spec_path(s) {
+            if spec_path_file_stem(s).is_empty() {
+                bail!("target spec path `{s}` has no file name");
+            }
+        } else if let Some(ch) =
+            s.chars().find(|&ch| !ch.is_ascii_alphanumeric() && !matches!(ch, '-' | '_' | '.'))
+        {
+            bail!("invalid character `{ch}` in target triple: `{s}`");
+        }
+        Ok(Self::new(s.to_owned().into(), None, None))
+    }
 }
 
 impl<'a> From<&'a TargetTripleRef<'_>> for TargetTripleRef<'a> {
@@ -589,6 +1065,56 @@ impl CargoVersion {
     }
 }
 
+/// Formats the version in the form of `<major>.<minor>[.<patch>][-nightly]`,
+/// e.g., `1.74.0-nightly`.
+impl fmt::Display for RustcVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)?;
+        if let Some(patch) = self.patch {
+            write!(f, ".{patch}")?;
+        }
+        if self.nightly {
+            f.write_str("-nightly")?;
+        }
+        Ok(())
+    }
+}
+/// Formats the version in the form of `<major>.<minor>.<patch>[-nightly]`,
+/// e.g., `1.74.0-nightly`.
+impl fmt::Display for CargoVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if self.nightly {
+            f.write_str("-nightly")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the version in the same format as the `release` line of `rustc -vV`
+/// (e.g., `1.74.0-nightly`), which is also the format of [`Display`](fmt::Display).
+impl FromStr for RustcVersion {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (major, minor, patch, nightly) =
+            parse_release(s).ok_or_else(|| format_err!("invalid rustc version `{s}`"))?;
+        Ok(Self { major, minor, patch, nightly })
+    }
+}
+/// Parses the version in the same format as the `release` line of `cargo -vV`
+/// (e.g., `1.74.0-nightly`), which is also the format of [`Display`](fmt::Display).
+impl FromStr for CargoVersion {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (major, minor, patch, nightly) = parse_release(s)
+            .and_then(|(major, minor, patch, nightly)| Some((major, minor, patch?, nightly)))
+            .ok_or_else(|| format_err!("invalid cargo version `{s}`"))?;
+        Ok(Self { major, minor, patch, nightly })
+    }
+}
+
 fn verbose_version(mut rustc_or_cargo: ProcessBuilder) -> Result<(String, ProcessBuilder)> {
     // Use verbose version output because the packagers add extra strings to the normal version output.
     // Do not use long flags (--version --verbose) because clippy-deriver doesn't handle them properly.
@@ -600,6 +1126,9 @@ fn verbose_version(mut rustc_or_cargo: ProcessBuilder) -> Result<(String, Proces
 
 fn parse_version(verbose_version: &str) -> Option<(u32, u32, Option<u32>, bool)> {
     let release = verbose_version.lines().find_map(|line| line.strip_prefix("release: "))?;
+    parse_release(release)
+}
+fn parse_release(release: &str) -> Option<(u32, u32, Option<u32>, bool)> {
     let (version, channel) = release.split_once('-').unwrap_or((release, ""));
     let mut digits = version.splitn(3, '.');
     let major = digits.next()?.parse::<u32>().ok()?;
@@ -615,12 +1144,14 @@ fn parse_version(verbose_version: &str) -> Option<(u32, u32, Option<u32>, bool)>
 fn rustc_version((verbose_version, cmd): &(String, ProcessBuilder)) -> Result<RustcVersion> {
     let (major, minor, patch, nightly) = parse_version(verbose_version)
         .ok_or_else(|| format_err!("unexpected version output from {cmd}: {verbose_version}"))?;
-    let nightly = match std::env::var_os("RUSTC_BOOTSTRAP") {
+    Ok(RustcVersion { major, minor, patch, nightly: rustc_nightly(nightly) })
+}
+fn rustc_nightly(nightly: bool) -> bool {
+    match std::env::var_os("RUSTC_BOOTSTRAP") {
         // When -1 is passed rustc works like stable, e.g., cfg(target_feature = "unstable_target_feature") will never be set. https://github.com/rust-lang/rust/pull/132993
         Some(v) if v == "-1" => false,
         _ => nightly,
-    };
-    Ok(RustcVersion { major, minor, patch, nightly })
+    }
 }
 fn cargo_version((verbose_version, cmd): &(String, ProcessBuilder)) -> Result<CargoVersion> {
     let (major, minor, patch, nightly) = parse_version(verbose_version)
@@ -644,6 +1175,10 @@ fn rustc_path(cargo: &OsStr) -> PathBuf {
     // PATH and `CARGO` environment variable may be different toolchains.
     // When Rust was installed using rustup, the same toolchain's rustc
     // binary is in the same directory as the cargo binary, so we use it.
+    // However, if `CARGO` is a rustup proxy (e.g., `~/.cargo/bin/cargo`),
+    // the rustc binary next to it is also a rustup proxy, which doesn't know
+    // the toolchain override passed to cargo. In that case, the rustc version
+    // is obtained from cargo instead (see `ResolveContext::rustc_version`).
     let mut rustc = PathBuf::from(cargo);
     rustc.pop(); // cargo
     rustc.push(format!("rustc{}", std::env::consts::EXE_SUFFIX));
@@ -653,6 +1188,27 @@ fn rustc_path(cargo: &OsStr) -> PathBuf {
         "rustc".into()
     }
 }
+/// Returns `true` if the given path is a rustup proxy.
+///
+/// Rustup installs proxies as symlinks or hard links to the `rustup` binary
+/// in the same directory.
+fn is_rustup_proxy(path: &Path) -> bool {
+    let rustup = path.with_file_name(format!("rustup{}", std::env::consts::EXE_SUFFIX));
+    let (Ok(path), Ok(rustup)) = (fs::canonicalize(path), fs::canonicalize(rustup)) else {
+        return false;
+    };
+    if path == rustup {
+        return true;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt as _;
+        if let (Ok(path), Ok(rustup)) = (fs::metadata(path), fs::metadata(rustup)) {
+            return path.dev() == rustup.dev() && path.ino() == rustup.ino();
+        }
+    }
+    false
+}
 
 #[cfg(test)]
 mod tests {
@@ -695,6 +1251,58 @@ mod tests {
         assert!(cargo_version.major_minor() > (0, u32::MAX));
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)] // Miri doesn't support pipe2 (inside std::process::Command::output)
+    fn rustup_proxy_version() {
+        // The real cargo binary set by cargo is not a rustup proxy.
+        let cargo = std::env::var_os("CARGO").unwrap();
+        assert!(!is_rustup_proxy(Path::new(&cargo)));
+        assert!(!is_rustup_proxy(&rustc_path(&cargo)));
+
+        // The following requires Rust installed using rustup.
+        let current_dir = std::env::current_dir().unwrap();
+        let Some(cargo_home) = walk::cargo_home_with_cwd(&current_dir) else { return };
+        let proxy = cargo_home.join("bin").join(format!("cargo{}", std::env::consts::EXE_SUFFIX));
+        if !is_rustup_proxy(&proxy) {
+            return;
+        }
+        assert!(is_rustup_proxy(&rustc_path(proxy.as_os_str())));
+        let build_config = easy::BuildConfig::default();
+        let cx = ResolveOptions::default()
+            .cargo(&proxy)
+            .rustc(PathAndArgs::new("rustc"))
+            .into_context(current_dir.clone());
+        assert!(!cx.rustc_is_rustup_proxy(&build_config));
+        let cx = ResolveOptions::default().cargo(&proxy).into_context(current_dir);
+        assert!(cx.rustc_is_rustup_proxy(&build_config));
+        let cargo_version = cargo_version(&verbose_version(cmd!(&proxy)).unwrap()).unwrap();
+        let rustc_version = cx.rustc_version(&build_config).unwrap();
+        assert_eq!(
+            (rustc_version.major, rustc_version.minor, rustc_version.patch),
+            (cargo_version.major, cargo_version.minor, Some(cargo_version.patch))
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+    fn rustup_proxy_detection() {
+        let tmp = tempfile::tempdir().unwrap();
+        let exe = |name: &str| tmp.path().join(format!("{name}{}", std::env::consts::EXE_SUFFIX));
+        // No rustup binary.
+        fs::write(exe("cargo"), "rustup").unwrap();
+        assert!(!is_rustup_proxy(&exe("cargo")));
+        fs::write(exe("rustup"), "rustup").unwrap();
+        // A copy with the same size is not a proxy.
+        assert!(!is_rustup_proxy(&exe("cargo")));
+        fs::hard_link(exe("rustup"), exe("rustc")).unwrap();
+        assert!(is_rustup_proxy(&exe("rustc")));
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(exe("rustup"), exe("rustdoc")).unwrap();
+            assert!(is_rustup_proxy(&exe("rustdoc")));
+        }
+    }
+
     #[test]
     fn target_triple() {
         let t = TargetTripleRef::from("x86_64-unknown-linux-gnu");
@@ -719,6 +1327,170 @@ mod tests {
         }
     }
 
+    #[test]
+    fn target_triple_eq_hash() {
+        use core::hash::Hasher as _;
+        use std::collections::hash_map::DefaultHasher;
+
+        fn hash(t: &TargetTriple) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            t.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let triple = TargetTriple::from("avr-unknown-gnu-atmega2560");
+        let spec = TargetTriple::from("avr-unknown-gnu-atmega2560.json");
+        assert_eq!(triple.triple(), spec.triple());
+        assert_eq!(triple.cli_target_string(), "avr-unknown-gnu-atmega2560");
+        assert_eq!(spec.cli_target_string(), "avr-unknown-gnu-atmega2560.json");
+        assert_ne!(triple, spec);
+        assert_ne!(hash(&triple), hash(&spec));
+        assert_eq!(spec, TargetTriple::from("avr-unknown-gnu-atmega2560.json"));
+        assert_eq!(hash(&spec), hash(&TargetTriple::from("avr-unknown-gnu-atmega2560.json")));
+
+        let map: HashMap<_, _> = [(triple.clone(), 1), (spec.clone(), 2)].into_iter().collect();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map[&triple], 1);
+        assert_eq!(map[&spec], 2);
+    }
+
+    #[test]
+    fn from_spec_path() {
+        let t = TargetTriple::from_spec_path("target-specs/avr-unknown-gnu-atmega2560.json", None);
+        assert_eq!(t.triple(), "avr-unknown-gnu-atmega2560");
+        assert_eq!(t.spec_path(), Some(Path::new("target-specs/avr-unknown-gnu-atmega2560.json")));
+        assert_eq!(t, TargetTriple::from("target-specs/avr-unknown-gnu-atmega2560.json"));
+        // No heuristic is used.
+        let t = TargetTriple::from_spec_path("my-target", None);
+        assert_eq!(t.triple(), "my-target");
+        assert_eq!(t.spec_path(), Some(Path::new("my-target")));
+        assert_eq!(TargetTriple::from("my-target").spec_path(), None);
+
+        let current_dir = fixtures_path();
+        let t = TargetTriple::from_spec_path(
+            "target-specs/avr-unknown-gnu-atmega2560.json",
+            Some(current_dir),
+        );
+        assert_eq!(t.triple(), "avr-unknown-gnu-atmega2560");
+        assert_eq!(
+            t.spec_path(),
+            Some(&*current_dir.join("target-specs/avr-unknown-gnu-atmega2560.json"))
+        );
+        t.validate_spec_path(current_dir).unwrap();
+    }
+
+    #[test]
+    fn version_display_from_str() {
+        for (s, major, minor, patch, nightly) in [
+            ("1.74.0-nightly", 1, 74, Some(0), true),
+            ("1.70.1", 1, 70, Some(1), false),
+            ("1.80", 1, 80, None, false),
+        ] {
+            let v: RustcVersion = s.parse().unwrap();
+            assert_eq!(v, RustcVersion { major, minor, patch, nightly });
+            assert_eq!(v.to_string(), s);
+            assert_eq!(v.to_string().parse::<RustcVersion>().unwrap(), v);
+            if let Some(patch) = patch {
+                let v: CargoVersion = s.parse().unwrap();
+                assert_eq!(v, CargoVersion { major, minor, patch, nightly });
+                assert_eq!(v.to_string(), s);
+                assert_eq!(v.to_string().parse::<CargoVersion>().unwrap(), v);
+            }
+        }
+        // Channels other than nightly are not included in the output.
+        let v: RustcVersion = "1.75.0-beta.1".parse().unwrap();
+        assert!(!v.nightly);
+        assert_eq!(v.to_string(), "1.75.0");
+        let v: CargoVersion = "1.76.0-dev".parse().unwrap();
+        assert!(v.nightly);
+        assert_eq!(v.to_string(), "1.76.0-nightly");
+
+        "1.80".parse::<CargoVersion>().unwrap_err();
+        assert_eq!("".parse::<RustcVersion>().unwrap_err().to_string(), "invalid rustc version ``");
+        "1".parse::<RustcVersion>().unwrap_err();
+        "a.b.c".parse::<CargoVersion>().unwrap_err();
+    }
+
+    #[test]
+    fn parse_target_triple() {
+        let t = TargetTriple::parse("x86_64-unknown-linux-gnu").unwrap();
+        assert_eq!(t, TargetTriple::from("x86_64-unknown-linux-gnu"));
+        let t = TargetTriple::parse("thumbv8m.main-none-eabi").unwrap();
+        assert_eq!(t.triple(), "thumbv8m.main-none-eabi");
+        let t = TargetTriple::parse("target-specs/avr-unknown-gnu-atmega2560.json").unwrap();
+        assert_eq!(t.triple(), "avr-unknown-gnu-atmega2560");
+        assert!(t.spec_path().is_some());
+
+        assert_eq!(
+            TargetTriple::parse("cfg(unix)").unwrap_err().to_string(),
+            "'cfg(unix)' is not valid target triple"
+        );
+        TargetTriple::parse("").unwrap_err();
+        TargetTriple::parse("x86_64 unknown").unwrap_err();
+        for s in ["/", "a/..", "./.."] {
+            assert_eq!(
+                TargetTriple::parse(s).unwrap_err().to_string(),
+                format!("target spec path `{s}` has no file name")
+            );
+        }
+        // Infallible conversions do not panic either.
+        assert_eq!(TargetTriple::from("/").triple(), "");
+        assert_eq!(TargetTriple::from("a/..").triple(), "");
+    }
+
+    #[test]
+    fn require_cargo_home() {
+        let cwd = fixtures_path();
+        let options = || ResolveOptions::default().env::<_, OsString, OsString>([]);
+
+        // Simulate the case where CARGO_HOME cannot be determined.
+        let cx = options().require_cargo_home(true).into_context(cwd.to_owned());
+        let _ = cx.cargo_home.set(None);
+        assert_eq!(
+            cx.cargo_home_for_load(cwd).unwrap_err().to_string(),
+            "failed to determine CARGO_HOME; set the CARGO_HOME environment variable or the path \
+             of the home directory"
+        );
+        let cx = options().into_context(cwd.to_owned());
+        let _ = cx.cargo_home.set(None);
+        assert_eq!(cx.cargo_home_for_load(cwd).unwrap(), None);
+        let cx = options()
+            .require_cargo_home(true)
+            .include_cargo_home(false)
+            .into_context(cwd.to_owned());
+        let _ = cx.cargo_home.set(None);
+        assert_eq!(cx.cargo_home_for_load(cwd).unwrap(), None);
+
+        // Explicitly disabled.
+        let cx = options().require_cargo_home(true).cargo_home(None).into_context(cwd.to_owned());
+        assert_eq!(cx.cargo_home_for_load(cwd).unwrap(), None);
+        // Determined.
+        let home = &cwd.join("home");
+        let cx = options().require_cargo_home(true).into_context(cwd.to_owned());
+        let _ = cx.cargo_home.set(Some(home.clone()));
+        assert_eq!(cx.cargo_home_for_load(cwd).unwrap(), Some(&**home));
+    }
+
+    #[test]
+    fn validate_spec_path() {
+        let current_dir = fixtures_path();
+        let t = TargetTriple::from("x86_64-unknown-linux-gnu");
+        t.validate_spec_path(current_dir).unwrap();
+        let t = TargetTriple::from("target-specs/avr-unknown-gnu-atmega2560.json");
+        t.validate_spec_path(current_dir).unwrap();
+        let t = TargetTriple::from("target-specs/non-existent.json");
+        let e = t.validate_spec_path(current_dir).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            format!(
+                "target path \"{}\" is not a valid file",
+                current_dir.join("target-specs/non-existent.json").display()
+            )
+        );
+        let t = TargetTriple::from("target-specs/");
+        t.validate_spec_path(current_dir).unwrap_err();
+    }
+
     #[test]
     fn env_filter() {
         // NB: sync with bench in bench/benches/bench.rs
@@ -735,6 +1507,7 @@ mod tests {
             ("CARGO_BUILD_TARGET", "triple"),
             ("CARGO_TARGET_DIR", "target"),
             ("CARGO_BUILD_TARGET_DIR", "target"),
+            ("CARGO_BUILD_BUILD_DIR", "build"),
             ("CARGO_ENCODED_RUSTFLAGS", "1"),
             ("RUSTFLAGS", "1"),
             ("CARGO_BUILD_RUSTFLAGS", "1"),
@@ -744,6 +1517,7 @@ mod tests {
             ("CARGO_INCREMENTAL", "false"),
             ("CARGO_BUILD_INCREMENTAL", "1"),
             ("CARGO_BUILD_DEP_INFO_BASEDIR", "1"),
+            ("CARGO_BUILD_WARNINGS", "deny"),
             ("BROWSER", "1"),
             ("CARGO_FUTURE_INCOMPAT_REPORT_FREQUENCY", "always"),
             ("CARGO_CARGO_NEW_VCS", "git"),
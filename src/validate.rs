@@ -0,0 +1,172 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use core::fmt;
+use std::path::Path;
+
+use crate::{
+    de::{self, Jobs},
+    resolve::TargetTripleRef,
+    value::{Definition, Value},
+};
+
+/// A problem in the config found by [`de::Config::validate`].
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct Diagnostic {
+    /// The description of the problem.
+    pub message: String,
+    /// The location where the value that caused the problem was defined.
+    pub definition: Option<Definition>,
+}
+
+impl Diagnostic {
+    fn new(message: String, definition: Option<&Definition>) -> Self {
+        Self { message, definition: definition.cloned() }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.definition {
+            Some(def) => write!(f, "{} (defined in {def})", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+pub(crate) fn registry_protocol_not_allowed(name: &str) -> String {
+    format!("`registries.{name}.protocol` is not allowed for registries other than crates-io")
+}
+
+// Built-in subcommands of cargo (as of 1.85).
+// https://github.com/rust-lang/cargo/blob/0.86.0/src/bin/cargo/commands/mod.rs
+const BUILTIN_SUBCOMMANDS: &[&str] = &[
+    "add",
+    "bench",
+    "build",
+    "check",
+    "clean",
+    "config",
+    "doc",
+    "fetch",
+    "fix",
+    "generate-lockfile",
+    "help",
+    "info",
+    "init",
+    "install",
+    "locate-project",
+    "login",
+    "logout",
+    "metadata",
+    "new",
+    "owner",
+    "package",
+    "pkgid",
+    "publish",
+    "read-manifest",
+    "remove",
+    "report",
+    "run",
+    "rustc",
+    "rustdoc",
+    "search",
+    "test",
+    "tree",
+    "uninstall",
+    "update",
+    "vendor",
+    "verify-project",
+    "version",
+    "yank",
+];
+
+/// Returns `true` if an alias with the given name is ignored by cargo because
+/// it is shadowed by a built-in subcommand.
+pub(crate) fn is_shadowed_alias(name: &str) -> bool {
+    BUILTIN_SUBCOMMANDS.contains(&name)
+}
+
+impl de::Config {
+    /// Runs cheap consistency checks on this config and returns the problems
+    /// found.
+    ///
+    /// This does not spawn any processes and does not resolve environment
+    /// variables. Relative paths that are not defined in config files are
+    /// resolved relative to `current_dir`.
+    ///
+    /// Values that cargo rejects at parse time (e.g., unknown `term.color`
+    /// values) are already rejected when loading config, so they are not
+    /// checked here.
+    pub fn validate(&self, current_dir: &Path) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+
+        if let Some(Value { val: Jobs::Count(0), definition }) = &self.build.jobs {
+            diagnostics
+                .push(Diagnostic::new("`build.jobs` may not be 0".to_owned(), definition.as_ref()));
+        }
+        if let Some(target) = &self.build.target {
+            for v in target.as_array_no_split() {
+                let t = TargetTripleRef::new(
+                    v.val.clone().into(),
+                    v.definition.as_ref(),
+                    Some(current_dir),
+                );
+                if let Err(e) = t.validate_spec_path(current_dir) {
+                    diagnostics.push(Diagnostic::new(
+                        format!("invalid `build.target`: {e}"),
+                        v.definition.as_ref(),
+                    ));
+                }
+            }
+        }
+        // https://github.com/rust-lang/cargo/blob/0.80.0/src/cargo/util/context/target.rs
+        for (key, target) in &self.target {
+            if key.starts_with("cfg(") {
+                for (name, link) in &target.links {
+                    diagnostics.push(Diagnostic::new(
+                        format!(
+                            "unused key `{name}` in [target] config table `{key}`; overriding \
+                             build scripts is only supported in `[target.<triple>]` tables"
+                        ),
+                        link_override_definition(link),
+                    ));
+                }
+            }
+        }
+        for (name, alias) in &self.alias {
+            if is_shadowed_alias(name) {
+                diagnostics.push(Diagnostic::new(
+                    format!(
+                        "user-defined alias `{name}` is ignored, because it is shadowed by a \
+                         built-in command"
+                    ),
+                    alias.list.first().and_then(|v| v.definition.as_ref()),
+                ));
+            }
+        }
+        for (name, registry) in &self.registries {
+            if name == "crates-io" {
+                continue;
+            }
+            if let Some(protocol) = &registry.protocol {
+                diagnostics.push(Diagnostic::new(
+                    registry_protocol_not_allowed(name),
+                    protocol.definition.as_ref(),
+                ));
+            }
+        }
+
+        diagnostics
+    }
+}
+
+fn link_override_definition(link: &de::LinkOverride) -> Option<&Definition> {
+    [&link.rustc_link_lib, &link.rustc_link_search, &link.rustc_cfg]
+        .into_iter()
+        .flatten()
+        .flatten()
+        .chain(&link.rustc_flags)
+        .chain(link.rustc_env.values())
+        .find_map(|v| v.definition.as_ref())
+}
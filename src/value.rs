@@ -84,6 +84,34 @@ impl Definition {
         }
     }
 
+    /// Returns a string describing where this is defined, in the same format
+    /// as the origin comments printed by `cargo config get --show-origin`.
+    ///
+    /// - Path: `/path/to/.cargo/config.toml`
+    /// - Environment: ``environment variable `CARGO_BUILD_JOBS` ``
+    /// - CLI: `--config cli option` (or the path to the config file passed to `--config`)
+    ///
+    /// This is currently the same as the [`Display`](fmt::Display) implementation.
+    pub fn to_cargo_origin_string(&self) -> String {
+        self.to_string()
+    }
+
+    /// Returns `true` if `self` and `other` are the same source: the same
+    /// config file, the same environment variable, or the same `--config`
+    /// option.
+    ///
+    /// Unlike the [`PartialEq`] implementation, which only compares the kind of
+    /// the source like cargo, this also compares the path or the name of the
+    /// environment variable.
+    pub fn same_source(&self, other: &Definition) -> bool {
+        match (self, other) {
+            (Definition::Path(a), Definition::Path(b)) => a == b,
+            (Definition::Environment(a), Definition::Environment(b)) => a == b,
+            (Definition::Cli(a), Definition::Cli(b)) => a == b,
+            _ => false,
+        }
+    }
+
     // /// Returns `true` if self is a higher priority to other.
     // ///
     // /// CLI is preferred over environment, which is preferred over files.
@@ -116,32 +144,32 @@ impl PartialEq for Definition {
     }
 }
 
-pub(crate) trait SetPath {
-    fn set_path(&mut self, path: &Path);
+pub(crate) trait SetDefinition {
+    fn set_definition(&mut self, def: &Definition);
 }
-impl<T: SetPath> SetPath for Option<T> {
-    fn set_path(&mut self, path: &Path) {
+impl<T: SetDefinition> SetDefinition for Option<T> {
+    fn set_definition(&mut self, def: &Definition) {
         if let Some(v) = self {
-            v.set_path(path);
+            v.set_definition(def);
         }
     }
 }
-impl<T: SetPath> SetPath for Vec<T> {
-    fn set_path(&mut self, path: &Path) {
+impl<T: SetDefinition> SetDefinition for Vec<T> {
+    fn set_definition(&mut self, def: &Definition) {
         for v in self {
-            v.set_path(path);
+            v.set_definition(def);
         }
     }
 }
-impl<T: SetPath> SetPath for BTreeMap<String, T> {
-    fn set_path(&mut self, path: &Path) {
+impl<T: SetDefinition> SetDefinition for BTreeMap<String, T> {
+    fn set_definition(&mut self, def: &Definition) {
         for v in self.values_mut() {
-            v.set_path(path);
+            v.set_definition(def);
         }
     }
 }
-impl<T> SetPath for Value<T> {
-    fn set_path(&mut self, path: &Path) {
-        self.definition = Some(Definition::Path(path.to_owned()));
+impl<T> SetDefinition for Value<T> {
+    fn set_definition(&mut self, def: &Definition) {
+        self.definition = Some(def.clone());
     }
 }
@@ -20,22 +20,17 @@
 use core::ops;
 use std::path::{Path, PathBuf};
 
-fn config_path(path: &Path) -> Option<PathBuf> {
-    // https://doc.rust-lang.org/nightly/cargo/reference/config.html#hierarchical-structure
-    //
-    // > Cargo also reads config files without the `.toml` extension,
-    // > such as `.cargo/config`. Support for the `.toml` extension was
-    // > added in version 1.39 and is the preferred form. If both files
-    // > exist, Cargo will use the file without the extension.
-    let config = path.join("config");
-    if config.exists() {
-        return Some(config);
-    }
-    let config = path.join("config.toml");
-    if config.exists() {
-        return Some(config);
-    }
-    None
+// https://doc.rust-lang.org/nightly/cargo/reference/config.html#hierarchical-structure
+//
+// > Cargo also reads config files without the `.toml` extension,
+// > such as `.cargo/config`. Support for the `.toml` extension was
+// > added in version 1.39 and is the preferred form. If both files
+// > exist, Cargo will use the file without the extension.
+const DEFAULT_FILE_NAMES: &[&str] = &["config", "config.toml"];
+const DEFAULT_CONFIG_DIR_NAME: &str = ".cargo";
+
+fn config_path(path: &Path, file_names: &[&str]) -> Option<PathBuf> {
+    file_names.iter().map(|name| path.join(name)).find(|config| config.exists())
 }
 
 // Do not use std::env::home_dir only on Windows which std::env::home_dir is not correct.
@@ -135,13 +130,41 @@ pub fn rustup_home_with_cwd(cwd: &Path) -> Option<PathBuf> {
 pub(crate) struct WalkInner<'a, P> {
     ancestors: std::path::Ancestors<'a>,
     cargo_home: Option<P>,
+    exclude_cargo_home: bool,
+    file_names: &'a [&'a str],
+    config_dir_name: &'a str,
 }
 
 impl<'a, P: ops::Deref<Target = Path>> WalkInner<'a, P> {
     /// Creates an iterator over Cargo configuration file paths from the given path
     /// and `CARGO_HOME` path.
     pub(crate) fn with_cargo_home(current_dir: &'a Path, cargo_home: Option<P>) -> Self {
-        Self { ancestors: current_dir.ancestors(), cargo_home }
+        Self {
+            ancestors: current_dir.ancestors(),
+            cargo_home,
+            exclude_cargo_home: false,
+            file_names: DEFAULT_FILE_NAMES,
+            config_dir_name: DEFAULT_CONFIG_DIR_NAME,
+        }
+    }
+    /// Skips the config in `CARGO_HOME`, even if it is also found in the
+    /// ancestors of the current directory.
+    #[cfg(feature = "serde")]
+    pub(crate) fn exclude_cargo_home(mut self, exclude: bool) -> Self {
+        self.exclude_cargo_home = exclude;
+        self
+    }
+    /// Sets the file names to look for in each `.cargo` directory, in order
+    /// of precedence.
+    pub(crate) fn file_names(mut self, file_names: &'a [&'a str]) -> Self {
+        self.file_names = file_names;
+        self
+    }
+    /// Sets the name of the directory to look for config files in each
+    /// ancestor of the current directory.
+    pub(crate) fn config_dir_name(mut self, config_dir_name: &'a str) -> Self {
+        self.config_dir_name = config_dir_name;
+        self
     }
 }
 
@@ -149,16 +172,22 @@ impl<P: ops::Deref<Target = Path>> Iterator for WalkInner<'_, P> {
     type Item = PathBuf;
     fn next(&mut self) -> Option<Self::Item> {
         for p in self.ancestors.by_ref() {
-            let p = p.join(".cargo");
+            let p = p.join(self.config_dir_name);
             // dedup CARGO_HOME
             if self.cargo_home.as_deref() == Some(&p) {
                 self.cargo_home = None;
+                if self.exclude_cargo_home {
+                    continue;
+                }
             }
-            if let Some(p) = config_path(&p) {
+            if let Some(p) = config_path(&p, self.file_names) {
                 return Some(p);
             }
         }
-        config_path(&self.cargo_home.take()?)
+        if self.exclude_cargo_home {
+            return None;
+        }
+        config_path(&self.cargo_home.take()?, self.file_names)
     }
 }
 
@@ -166,7 +195,7 @@ impl<P: ops::Deref<Target = Path>> Iterator for WalkInner<'_, P> {
 /// An iterator over Cargo configuration file paths.
 #[derive(Debug)]
 #[must_use = "iterators are lazy and do nothing unless consumed"]
-pub struct Walk<'a>(WalkInner<'a, PathBuf>);
+pub struct Walk<'a>(pub(crate) WalkInner<'a, PathBuf>);
 
 impl<'a> Walk<'a> {
     /// Creates an iterator over Cargo configuration file paths from the given path.
@@ -179,6 +208,35 @@ impl<'a> Walk<'a> {
     pub fn with_cargo_home(current_dir: &'a Path, cargo_home: Option<PathBuf>) -> Self {
         Self(WalkInner::with_cargo_home(current_dir, cargo_home))
     }
+
+    /// Sets the file names to look for in each `.cargo` directory.
+    ///
+    /// In each directory, only the first file name in `file_names` that exists
+    /// is returned. The default is `["config", "config.toml"]`, which matches
+    /// the files cargo reads.
+    ///
+    /// **Note:** This is not cargo-standard. Cargo itself only reads the
+    /// default file names, so this is only useful for tools that use their own
+    /// config files with the same hierarchical structure.
+    pub fn with_file_names(mut self, file_names: &'a [&'a str]) -> Self {
+        self.0 = self.0.file_names(file_names);
+        self
+    }
+
+    /// Sets the name of the directory to look for config files in each
+    /// ancestor of the current directory.
+    ///
+    /// The default is `.cargo`. This does not affect the `CARGO_HOME` path,
+    /// which is a config directory itself.
+    ///
+    /// **Note:** This is not cargo-standard. Cargo itself only reads config
+    /// files in `.cargo` directories, so this is only useful for testing or for
+    /// tools that use their own config directories with the same hierarchical
+    /// structure.
+    pub fn with_config_dir_name(mut self, config_dir_name: &'a str) -> Self {
+        self.0 = self.0.config_dir_name(config_dir_name);
+        self
+    }
 }
 
 impl Iterator for Walk<'_> {
@@ -213,4 +271,75 @@ mod tests {
         assert_eq!(w.next(), Some(p.join("a/.cargo/config")));
         assert_eq!(w.next(), None);
     }
+
+    #[test]
+    fn walk_with_file_names() {
+        let tmp = tempfile::tempdir().unwrap();
+        let p = tmp.path();
+        let home = &p.join("home/.cargo");
+        let cwd = &p.join("a/b");
+        fs::create_dir_all(home).unwrap();
+        fs::write(home.join("config.toml"), "").unwrap();
+        fs::write(home.join("config.custom.toml"), "").unwrap();
+        fs::create_dir_all(p.join("a/b/.cargo")).unwrap();
+        fs::write(p.join("a/b/.cargo/config.toml"), "").unwrap();
+        fs::write(p.join("a/b/.cargo/config.custom.toml"), "").unwrap();
+        fs::create_dir_all(p.join("a/.cargo")).unwrap();
+        fs::write(p.join("a/.cargo/config.custom.toml"), "").unwrap();
+        fs::write(p.join("a/.cargo/config"), "").unwrap();
+
+        let names = &["config.custom.toml", "config.toml"];
+        let mut w = Walk::with_cargo_home(cwd, Some(home.clone())).with_file_names(names);
+        assert_eq!(w.next(), Some(p.join("a/b/.cargo/config.custom.toml")));
+        assert_eq!(w.next(), Some(p.join("a/.cargo/config.custom.toml")));
+        assert_eq!(w.next(), Some(home.join("config.custom.toml")));
+        assert_eq!(w.next(), None);
+
+        let names = &["config.toml", "config.custom.toml"];
+        let mut w = Walk::with_cargo_home(cwd, Some(home.clone())).with_file_names(names);
+        assert_eq!(w.next(), Some(p.join("a/b/.cargo/config.toml")));
+        assert_eq!(w.next(), Some(p.join("a/.cargo/config.custom.toml")));
+        assert_eq!(w.next(), Some(home.join("config.toml")));
+        assert_eq!(w.next(), None);
+
+        // Default behavior is unchanged.
+        let mut w = Walk::with_cargo_home(cwd, Some(home.clone()));
+        assert_eq!(w.next(), Some(p.join("a/b/.cargo/config.toml")));
+        assert_eq!(w.next(), Some(p.join("a/.cargo/config")));
+        assert_eq!(w.next(), Some(home.join("config.toml")));
+        assert_eq!(w.next(), None);
+    }
+
+    #[test]
+    fn walk_with_config_dir_name() {
+        let tmp = tempfile::tempdir().unwrap();
+        let p = tmp.path();
+        let home = &p.join("home/.cargo");
+        let cwd = &p.join("a/b");
+        fs::create_dir_all(home).unwrap();
+        fs::write(home.join("config.toml"), "").unwrap();
+        fs::create_dir_all(cwd).unwrap();
+        fs::create_dir_all(p.join("a/.config-cargo")).unwrap();
+        fs::write(p.join("a/.config-cargo/config.toml"), "").unwrap();
+        fs::create_dir_all(p.join(".cargo")).unwrap();
+        fs::write(p.join(".cargo/config.toml"), "").unwrap();
+
+        let mut w =
+            Walk::with_cargo_home(cwd, Some(home.clone())).with_config_dir_name(".config-cargo");
+        assert_eq!(w.next(), Some(p.join("a/.config-cargo/config.toml")));
+        assert_eq!(w.next(), Some(home.join("config.toml")));
+        assert_eq!(w.next(), None);
+
+        // CARGO_HOME in the ancestors is still deduplicated.
+        let home = &p.join("a/.config-cargo");
+        let mut w =
+            Walk::with_cargo_home(cwd, Some(home.clone())).with_config_dir_name(".config-cargo");
+        assert_eq!(w.next(), Some(p.join("a/.config-cargo/config.toml")));
+        assert_eq!(w.next(), None);
+
+        // Default behavior is unchanged.
+        let mut w = Walk::with_cargo_home(cwd, None);
+        assert_eq!(w.next(), Some(p.join(".cargo/config.toml")));
+        assert_eq!(w.next(), None);
+    }
 }
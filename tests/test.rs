@@ -181,6 +181,21 @@ fn easy() {
     assert_reference_example(de);
 }
 
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn configured_targets() {
+    let (_tmp, root) = test_project("reference");
+    let config = easy_load(&root, test_options()).unwrap();
+    assert_eq!(config.configured_targets(), [
+        "cfg(target_arch = \"x86_64\")",
+        "x86_64-unknown-linux-gnu"
+    ]);
+
+    let (_tmp, root) = test_project("empty");
+    let config = easy_load(&root, test_options()).unwrap();
+    assert!(config.configured_targets().is_empty());
+}
+
 #[test]
 fn no_manifest_dir() {
     let tmpdir = tempfile::tempdir().unwrap();
@@ -209,6 +224,73 @@ fn de() {
     assert_eq!("", toml::to_string(&de::Config::default()).unwrap());
 }
 
+#[test]
+fn de_merge() {
+    fn de(s: &str) -> de::Config {
+        toml::from_str(s).unwrap()
+    }
+    fn rustflags(config: &de::Config) -> Vec<&str> {
+        config.build.rustflags.as_ref().unwrap().flags.iter().map(|v| &*v.val).collect()
+    }
+
+    // Merges configs in order of precedence, like configs in the parent directories.
+    let mut config = de("build.jobs = 1\nbuild.rustflags = [\"a\"]\n");
+    config.merge(de("build.jobs = 2\nbuild.rustflags = [\"b\"]\n"), false).unwrap();
+    config
+        .merge(de("build.jobs = 3\nbuild.rustflags = [\"c\"]\nbuild.incremental = true\n"), false)
+        .unwrap();
+    assert_eq!(config.build.jobs.as_ref().unwrap().val, Jobs::Count(1));
+    assert_eq!(config.build.incremental.as_ref().unwrap().val, true);
+    assert_eq!(rustflags(&config), ["c", "b", "a"]);
+
+    // Overrides non-container values, like --config CLI option.
+    config.merge(de("build.jobs = 4\nbuild.rustflags = [\"d\"]\n"), true).unwrap();
+    assert_eq!(config.build.jobs.as_ref().unwrap().val, Jobs::Count(4));
+    assert_eq!(rustflags(&config), ["d", "c", "b", "a"]);
+
+    // Different kinds of values cannot be merged.
+    config.merge(de("build.rustflags = \"e\"\n"), false).unwrap_err();
+}
+
+#[cfg(feature = "serde_json")]
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn de_from_json_str() {
+    let (_tmp, root) = test_project("reference");
+    let dir = &root;
+    let config = de_load(dir, test_options()).unwrap();
+
+    let json = serde_json::to_string(&config).unwrap();
+    let from_json = de::Config::from_json_str(&json).unwrap();
+    assert_eq!(json, serde_json::to_string(&from_json).unwrap());
+    assert_eq!(toml::to_string(&config).unwrap(), toml::to_string(&from_json).unwrap());
+    // JSON does not contain definitions.
+    assert!(from_json.build.jobs.unwrap().definition.is_none());
+
+    assert_eq!("{}", serde_json::to_string(&de::Config::from_json_str("{}").unwrap()).unwrap());
+    de::Config::from_json_str(r#"{"build":{"jobs":"1"}}"#).unwrap_err();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn easy_eq() {
+    let (_tmp, root) = test_project("reference");
+    let dir = &root;
+    let a = easy_load(dir, test_options()).unwrap();
+    let b = easy_load(dir, test_options()).unwrap();
+    assert_eq!(a, b);
+    assert_eq!(a.build, b.build);
+    // The lazily resolved [target] table is not compared.
+    let target = a.target("x86_64-unknown-linux-gnu").unwrap();
+    assert_eq!(a, b);
+    assert_eq!(target, b.target("x86_64-unknown-linux-gnu").unwrap());
+
+    let c = easy_load(dir, test_options().env([("CARGO_BUILD_JOBS", "2")])).unwrap();
+    assert_ne!(a, c);
+    assert_ne!(a.build, c.build);
+    assert_eq!(a.net, c.net);
+}
+
 #[test]
 #[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
 fn custom_target() {
@@ -265,6 +347,2596 @@ fn custom_target() {
     t("avr-unknown-gnu-atmega2560", IsBuiltin(false));
 }
 
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn env_target_spec_path() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+
+    // Relative spec path from environment variables is resolved relative to the current directory.
+    let config = Config::load_with_options(
+        dir,
+        test_options().env([("CARGO_BUILD_TARGET", "./specs/foo.json")]),
+    )
+    .unwrap();
+    let targets = config.build_target_for_config::<_, &str>([]).unwrap();
+    assert_eq!(targets.len(), 1);
+    assert_eq!(targets[0].triple(), "foo");
+    let spec_path = targets[0].spec_path().unwrap();
+    assert!(spec_path.is_absolute());
+    assert_eq!(spec_path, dir.join("specs/foo.json"));
+    assert_eq!(config.build_target_for_cli::<_, &str>([]).unwrap(), vec![spec_path
+        .to_str()
+        .unwrap()
+        .to_owned()]);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn build_target_for_cli_dedup() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+
+    let x = "x86_64-unknown-linux-gnu";
+    let y = "aarch64-unknown-linux-gnu";
+    assert_eq!(config.build_target_for_cli([x, x]).unwrap(), vec![x.to_owned()]);
+    assert_eq!(config.build_target_for_cli([y, x, y, x]).unwrap(), vec![
+        y.to_owned(),
+        x.to_owned()
+    ]);
+    // Spec path and triple name are different to cargo.
+    let spec = "target-specs/x86_64-unknown-linux-gnu.json";
+    assert_eq!(config.build_target_for_cli([spec, x, spec]).unwrap(), vec![
+        spec.to_owned(),
+        x.to_owned()
+    ]);
+
+    fs::write(
+        root.join(".cargo/config.toml"),
+        format!("build.target = [\"{y}\", \"{x}\", \"{y}\"]\n"),
+    )
+    .unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.build_target_for_cli::<_, &str>([]).unwrap(), vec![
+        y.to_owned(),
+        x.to_owned()
+    ]);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn build_target_empty() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    let host = "x86_64-unknown-linux-gnu";
+    let x = "aarch64-unknown-linux-gnu";
+    fs::write(root.join(".cargo/config.toml"), "build.target = []\n").unwrap();
+    let options = || test_options().host_triple(host);
+
+    // Empty build.target is treated as unset.
+    let config = Config::load_with_options(dir, options()).unwrap();
+    assert_eq!(config.config_build_target(), Some(&[][..]));
+    assert_eq!(config.build_target_for_config::<_, &str>([]).unwrap(), vec![host.into()]);
+    assert_eq!(config.build_target_for_cli::<_, &str>([]).unwrap(), Vec::<String>::new());
+
+    // --target option and CARGO_BUILD_TARGET still take precedence.
+    assert_eq!(config.build_target_for_config([x]).unwrap(), vec![x.into()]);
+    assert_eq!(config.build_target_for_cli([x]).unwrap(), vec![x.to_owned()]);
+    let config =
+        Config::load_with_options(dir, options().env([("CARGO_BUILD_TARGET", x)])).unwrap();
+    assert_eq!(config.build_target_for_config::<_, &str>([]).unwrap(), vec![x.into()]);
+    assert_eq!(config.build_target_for_cli::<_, &str>([]).unwrap(), vec![x.to_owned()]);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn config_build_target() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.config_build_target(), None);
+    let config = Config::load_with_options(
+        dir,
+        test_options().env([("CARGO_BUILD_TARGET", "aarch64-unknown-linux-gnu")]),
+    )
+    .unwrap();
+    assert_eq!(config.config_build_target(), None);
+
+    fs::write(root.join(".cargo/config.toml"), "build.target = \"x86_64-unknown-linux-gnu\"\n")
+        .unwrap();
+    let config = Config::load_with_options(
+        dir,
+        test_options().env([("CARGO_BUILD_TARGET", "aarch64-unknown-linux-gnu")]),
+    )
+    .unwrap();
+    assert_eq!(config.config_build_target(), Some(&["x86_64-unknown-linux-gnu".into()][..]));
+    assert_eq!(config.build_target_for_cli::<_, &str>([]).unwrap(), vec![
+        "aarch64-unknown-linux-gnu".to_owned()
+    ]);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn env_expand() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    fs::write(
+        root.join(".cargo/config.toml"),
+        r#"
+            [env]
+            RESOLVED = "${A}/b"
+            UNKNOWN = "${UNKNOWN}/b"
+            ESCAPED = "$${A}/$b"
+            UNCLOSED = "${A"
+            "#,
+    )
+    .unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    let lookup = |name: &str| (name == "A").then(|| "a".into());
+
+    // Not expanded by default, like cargo.
+    assert_eq!(config.env["RESOLVED"].value, "${A}/b");
+    assert_eq!(config.env["RESOLVED"].expand(lookup), "a/b");
+    assert_eq!(config.env["UNKNOWN"].expand(lookup), "${UNKNOWN}/b");
+    assert_eq!(config.env["ESCAPED"].expand(lookup), "${A}/$b");
+    assert_eq!(config.env["UNCLOSED"].expand(lookup), "${A");
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn doc_browser_command() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert!(config.doc_browser_command().is_none());
+    let config =
+        Config::load_with_options(dir, test_options().env([("BROWSER", "firefox --new-window")]))
+            .unwrap();
+    let cmd = config.doc_browser_command().unwrap();
+    assert_eq!(cmd.get_program(), "firefox");
+    assert_eq!(cmd.get_args().collect::<Vec<_>>(), ["--new-window"]);
+    // Empty BROWSER environment variable is treated as unset.
+    for browser in ["", " "] {
+        let config =
+            Config::load_with_options(dir, test_options().env([("BROWSER", browser)])).unwrap();
+        assert_eq!(config.doc.browser, None);
+        assert!(config.doc_browser_command().is_none());
+    }
+
+    // doc.browser config value is prefer over BROWSER environment variable.
+    fs::write(root.join(".cargo/config.toml"), "doc.browser = [\"chromium\"]\n").unwrap();
+    let config =
+        Config::load_with_options(dir, test_options().env([("BROWSER", "firefox --new-window")]))
+            .unwrap();
+    let cmd = config.doc_browser_command().unwrap();
+    assert_eq!(cmd.get_program(), "chromium");
+    assert_eq!(cmd.get_args().count(), 0);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn registry_index() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(
+        config.registry_index("crates-io").unwrap(),
+        Some("sparse+https://index.crates.io/")
+    );
+    assert_eq!(config.registry_index("my-registry").unwrap(), None);
+    config.registry_index("").unwrap_err();
+    config.registry_index("my.registry").unwrap_err();
+    let config = Config::load_with_options(
+        dir,
+        test_options().env([("CARGO_REGISTRIES_CRATES_IO_PROTOCOL", "git")]),
+    )
+    .unwrap();
+    assert_eq!(
+        config.registry_index("crates-io").unwrap(),
+        Some("https://github.com/rust-lang/crates.io-index")
+    );
+
+    fs::write(
+        root.join(".cargo/config.toml"),
+        r#"
+            [registries.my-registry]
+            index = "sparse+https://my-intranet:8080/index/"
+            "#,
+    )
+    .unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(
+        config.registry_index("my-registry").unwrap(),
+        Some("sparse+https://my-intranet:8080/index/")
+    );
+    assert_eq!(
+        config.registry_index("crates-io").unwrap(),
+        Some("sparse+https://index.crates.io/")
+    );
+    // The registry can also be referred to by the name used in the
+    // environment variables, like `registry_token`.
+    for name in ["my_registry", "MY_REGISTRY"] {
+        assert_eq!(
+            config.registry_index(name).unwrap(),
+            Some("sparse+https://my-intranet:8080/index/"),
+            "{name}"
+        );
+        assert_eq!(
+            config.registry_index_url(name).unwrap().as_deref(),
+            Some("sparse+https://my-intranet:8080/index/"),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn registry_index_url() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+
+    fs::write(
+        root.join(".cargo/config.toml"),
+        r#"
+            registries.git.index = "https://example.com/git-index"
+            registries.git-prefixed.index = "registry+ssh://git@example.com/index.git"
+            registries.sparse.index = "sparse+https://example.com/index/"
+            registries.no-scheme.index = "example.com/index"
+            registries.bad-scheme.index = "sparse+ssh://example.com/index/"
+            registries.no-host.index = "https:///index"
+            registries.space.index = "https://example.com/my index"
+            "#,
+    )
+    .unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(
+        config.registry_index_url("git").unwrap().as_deref(),
+        Some("registry+https://example.com/git-index")
+    );
+    assert_eq!(
+        config.registry_index_url("git-prefixed").unwrap().as_deref(),
+        Some("registry+ssh://git@example.com/index.git")
+    );
+    assert_eq!(
+        config.registry_index_url("sparse").unwrap().as_deref(),
+        Some("sparse+https://example.com/index/")
+    );
+    assert_eq!(
+        config.registry_index_url("crates-io").unwrap().as_deref(),
+        Some("sparse+https://index.crates.io/")
+    );
+    assert_eq!(config.registry_index_url("unknown").unwrap(), None);
+    assert_eq!(
+        config.registry_index_url("no-scheme").unwrap_err().to_string(),
+        "invalid index URL `example.com/index` for registry `no-scheme`: missing scheme"
+    );
+    assert_eq!(
+        config.registry_index_url("bad-scheme").unwrap_err().to_string(),
+        "invalid index URL `sparse+ssh://example.com/index/` for registry `bad-scheme`: \
+         unsupported scheme `ssh`"
+    );
+    config.registry_index_url("no-host").unwrap_err();
+    config.registry_index_url("space").unwrap_err();
+
+    let config = Config::load_with_options(
+        dir,
+        test_options().env([("CARGO_REGISTRIES_CRATES_IO_PROTOCOL", "git")]),
+    )
+    .unwrap();
+    assert_eq!(
+        config.registry_index_url("crates-io").unwrap().as_deref(),
+        Some("registry+https://github.com/rust-lang/crates.io-index")
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn effective_color() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.effective_color(), Color::Auto);
+    let config =
+        Config::load_with_options(dir, test_options().env([("CARGO_TERM_COLOR", "never")]))
+            .unwrap();
+    assert_eq!(config.effective_color(), Color::Never);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn progress_width() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.progress_width(None), None);
+    assert_eq!(config.progress_width(Some(120)), Some(120));
+
+    fs::write(root.join(".cargo/config.toml"), "term.progress.width = 60\n").unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.progress_width(None), Some(60));
+    assert_eq!(config.progress_width(Some(120)), Some(60));
+    let config =
+        Config::load_with_options(dir, test_options().env([("CARGO_TERM_PROGRESS_WIDTH", "40")]))
+            .unwrap();
+    assert_eq!(config.progress_width(Some(120)), Some(40));
+}
+
+#[cfg(feature = "color")]
+#[test]
+fn color_choice() {
+    use colorchoice::ColorChoice;
+
+    assert_eq!(ColorChoice::from(Color::Auto), ColorChoice::Auto);
+    assert_eq!(ColorChoice::from(Color::Always), ColorChoice::Always);
+    assert_eq!(ColorChoice::from(Color::Never), ColorChoice::Never);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn build_jobs() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+
+    fs::write(root.join(".cargo/config.toml"), "build.jobs = 4\n").unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.build.jobs, Some(Jobs::Count(4)));
+    assert_eq!(toml::to_string(&config).unwrap(), "[build]\njobs = 4\n");
+
+    fs::write(root.join(".cargo/config.toml"), "build.jobs = -1\n").unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.build.jobs, Some(Jobs::Count(-1)));
+
+    fs::write(root.join(".cargo/config.toml"), "build.jobs = \"default\"\n").unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.build.jobs, Some(Jobs::Default));
+    assert_eq!(toml::to_string(&config).unwrap(), "[build]\njobs = \"default\"\n");
+
+    fs::write(root.join(".cargo/config.toml"), "build.jobs = \"4\"\n").unwrap();
+    Config::load_with_options(dir, test_options()).unwrap_err();
+
+    fs::write(root.join(".cargo/config.toml"), "").unwrap();
+    let config =
+        Config::load_with_options(dir, test_options().env([("CARGO_BUILD_JOBS", "default")]))
+            .unwrap();
+    assert_eq!(config.build.jobs, Some(Jobs::Default));
+    let config =
+        Config::load_with_options(dir, test_options().env([("CARGO_BUILD_JOBS", "4")])).unwrap();
+    assert_eq!(config.build.jobs, Some(Jobs::Count(4)));
+    Config::load_with_options(dir, test_options().env([("CARGO_BUILD_JOBS", "a")])).unwrap_err();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn term_progress_bool() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+
+    fs::write(root.join(".cargo/config.toml"), "term.progress = false\n").unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.term.progress.when, Some(When::Never));
+    assert_eq!(config.term.progress.width, None);
+    let config = de::Config::load_file(dir.join(".cargo/config.toml")).unwrap();
+    assert_eq!(
+        config.term.progress.when.unwrap().definition,
+        Some(de::Definition::Path(dir.join(".cargo/config.toml")))
+    );
+
+    fs::write(root.join(".cargo/config.toml"), "term.progress = true\n").unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.term.progress.when, Some(When::Auto));
+
+    fs::write(
+        root.join(".cargo/config.toml"),
+        "term.progress = { when = \"always\", width = 80 }\n",
+    )
+    .unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.term.progress.when, Some(When::Always));
+    assert_eq!(config.term.progress.width, Some(80));
+
+    fs::write(root.join(".cargo/config.toml"), "term.progress = \"never\"\n").unwrap();
+    Config::load_with_options(dir, test_options()).unwrap_err();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn encoded_rustflags_in_config() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+
+    fs::write(
+        root.join(".cargo/config.toml"),
+        "target.x86_64-unknown-linux-gnu.rustflags = \"-C\\u001fopt-level=3\\u001f--cfg a b\"\n",
+    )
+    .unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(
+        config.rustflags("x86_64-unknown-linux-gnu").unwrap(),
+        Some(["-C", "opt-level=3", "--cfg a b"].into())
+    );
+
+    let config: de::Config = toml::from_str("build.rustflags = \"-C opt-level=3\"\n").unwrap();
+    assert_eq!(config.build.rustflags.unwrap().flags.len(), 2);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn linker_precedence() {
+    #[track_caller]
+    fn t(config: &str, env: &[(&str, &str)], expected: Option<&str>) {
+        let (_tmp, root) = test_project("empty");
+        let dir = &root;
+        fs::write(root.join(".cargo/config.toml"), config).unwrap();
+        let config =
+            Config::load_with_options(dir, test_options().env(env.iter().copied())).unwrap();
+        assert_eq!(
+            config.linker("x86_64-unknown-linux-gnu").unwrap().as_deref(),
+            expected.map(Path::new)
+        );
+    }
+    const TRIPLE: &str = "target.x86_64-unknown-linux-gnu.linker = \"triple-cc\"\n";
+    const CFG: &str = "target.'cfg(target_arch = \"x86_64\")'.linker = \"cfg-cc\"\n";
+    const ENV: &[(&str, &str)] = &[("CARGO_TARGET_X86_64_UNKNOWN_LINUX_GNU_LINKER", "env-cc")];
+
+    t("", &[], None);
+    // triple-only
+    t(TRIPLE, &[], Some("triple-cc"));
+    // env-only
+    t("", ENV, Some("env-cc"));
+    // cfg-only
+    t(CFG, &[], Some("cfg-cc"));
+    // triple wins over cfg
+    t(&format!("{TRIPLE}{CFG}"), &[], Some("triple-cc"));
+    // env wins over triple and cfg
+    t(TRIPLE, ENV, Some("env-cc"));
+    t(CFG, ENV, Some("env-cc"));
+    t(&format!("{TRIPLE}{CFG}"), ENV, Some("env-cc"));
+    // cfg that does not match is ignored
+    t("target.'cfg(target_arch = \"aarch64\")'.linker = \"cfg-cc\"\n", &[], None);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn aliases() {
+    let (_tmp, root) = test_project("reference");
+    let dir = &root;
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+
+    let aliases: Vec<_> = config.aliases().map(|(k, v)| (k, v.list.clone())).collect();
+    assert_eq!(aliases.len(), config.alias.len());
+    assert!(aliases.contains(&("b", vec!["build".to_owned()])));
+    assert_eq!(config.alias_argv("rr").unwrap(), ["run", "--release"]);
+    // Only a single level of expansion is performed.
+    assert_eq!(config.alias_argv("recursive_example").unwrap(), ["rr", "--example", "recursions"]);
+    assert_eq!(config.alias_argv("space_example").unwrap(), [
+        "run",
+        "--release",
+        "--",
+        "\"command list\""
+    ]);
+    assert_eq!(config.alias_argv("build"), None);
+    assert_eq!(config.shadowed_aliases().count(), 0);
+
+    fs::write(root.join(".cargo/config.toml"), "alias.build = \"test\"\nalias.b = \"test\"\n")
+        .unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.shadowed_aliases().collect::<Vec<_>>(), ["build"]);
+    assert_eq!(config.alias_argv("build"), None);
+    // Built-in aliases are not built-in subcommands, so they can be overridden.
+    assert_eq!(config.alias_argv("b").unwrap(), ["test"]);
+}
+
+#[test]
+fn definition_origin_string() {
+    let path = Path::new("/path/to/.cargo/config.toml");
+    assert_eq!(
+        de::Definition::Path(path.to_owned()).to_cargo_origin_string(),
+        path.display().to_string()
+    );
+    assert_eq!(
+        de::Definition::Environment("CARGO_BUILD_JOBS".into()).to_cargo_origin_string(),
+        "environment variable `CARGO_BUILD_JOBS`"
+    );
+    assert_eq!(de::Definition::Cli(None).to_cargo_origin_string(), "--config cli option");
+}
+
+#[test]
+fn definition_same_source() {
+    let a = &de::Definition::Path("/a/.cargo/config.toml".into());
+    let b = &de::Definition::Path("/b/.cargo/config.toml".into());
+    // `==` only compares the kind of the source, like cargo.
+    assert_eq!(a, b);
+    assert!(!a.same_source(b));
+    assert!(a.same_source(&a.clone()));
+
+    let jobs = &de::Definition::Environment("CARGO_BUILD_JOBS".into());
+    let offline = &de::Definition::Environment("CARGO_NET_OFFLINE".into());
+    assert_eq!(jobs, offline);
+    assert!(!jobs.same_source(offline));
+    assert!(jobs.same_source(&de::Definition::Environment("CARGO_BUILD_JOBS".into())));
+
+    let cli = &de::Definition::Cli(None);
+    let cli_file = &de::Definition::Cli(Some("/a/.cargo/config.toml".into()));
+    assert!(cli.same_source(cli));
+    assert!(!cli.same_source(cli_file));
+    assert!(!cli_file.same_source(a));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn empty_string_wrapper_config() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    // Do not use test_options() here because it overrides rustc.
+    let options =
+        || ResolveOptions::default().env(HashMap::<String, String>::default()).cargo_home(None);
+
+    fs::write(
+        root.join(".cargo/config.toml"),
+        "build.rustc = \"rustc\"\nbuild.rustc-wrapper = \"\"\nbuild.rustc-workspace-wrapper = \"\"\n",
+    )
+    .unwrap();
+    let config = Config::load_with_options(dir, options()).unwrap();
+    assert_eq!(config.build.rustc_wrapper, None);
+    assert_eq!(config.build.rustc_workspace_wrapper, None);
+    assert_eq!(*config.rustc(), PathAndArgs::new("rustc"));
+}
+
+#[cfg(unix)]
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support pipe2 (inside std::process::Command::output)
+fn toolchain_versions() {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    let log = root.join("spawn.log");
+    let write_script = |name: &str, program: &str| {
+        let path = root.join(name);
+        fs::write(
+            &path,
+            format!("#!/bin/sh\necho {program} >> \"{}\"\nexec {program} \"$@\"\n", log.display()),
+        )
+        .unwrap();
+        fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    };
+    let rustc = write_script("rustc.sh", "rustc");
+    let cargo = write_script("cargo.sh", "cargo");
+    fs::write(root.join(".cargo/config.toml"), format!("build.rustc = \"{}\"\n", rustc.display()))
+        .unwrap();
+    let options = ResolveOptions::default()
+        .env(HashMap::<String, String>::default())
+        .cargo_home(None)
+        .cargo(cargo.as_os_str());
+    let config = Config::load_with_options(dir, options).unwrap();
+
+    let (rustc_version, cargo_version) = config.toolchain_versions().unwrap();
+    assert_eq!(fs::read_to_string(&log).unwrap(), "cargo\nrustc\n");
+    assert_eq!(config.toolchain_versions().unwrap(), (rustc_version, cargo_version));
+    assert_eq!(config.rustc_version().unwrap(), rustc_version);
+    assert_eq!(config.cargo_version().unwrap(), cargo_version);
+    config.host_triple().unwrap();
+    assert_eq!(fs::read_to_string(&log).unwrap(), "cargo\nrustc\n");
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn merge_error() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root.join("a");
+    fs::create_dir_all(dir.join(".cargo")).unwrap();
+    fs::write(dir.join(".cargo/config.toml"), "build.rustflags = [\"--cfg\", \"a\"]\n").unwrap();
+    fs::write(root.join(".cargo/config.toml"), "build.rustflags = \"--cfg b\"\n").unwrap();
+
+    let e = Config::load_with_options(dir, test_options()).unwrap_err();
+    let mut chain = vec![e.to_string()];
+    let mut source = std::error::Error::source(&e);
+    while let Some(e) = source {
+        chain.push(e.to_string());
+        source = e.source();
+    }
+    assert_eq!(chain, [
+        format!(
+            "failed to merge config from `{}` into `{}`",
+            root.join(".cargo/config.toml").display(),
+            dir.join(".cargo/config.toml").display()
+        ),
+        "failed to merge key `build.rustflags`".to_owned(),
+        format!(
+            "expected array (defined in {}), but found string (defined in {})",
+            dir.join(".cargo/config.toml").display(),
+            root.join(".cargo/config.toml").display()
+        ),
+    ]);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn include_cargo_home() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root.join("a");
+    let home = &root.join("home");
+    fs::create_dir_all(dir.join(".cargo")).unwrap();
+    fs::create_dir_all(home).unwrap();
+    fs::write(home.join("config.toml"), "build.jobs = 3\n").unwrap();
+    fs::write(root.join(".cargo/config.toml"), "build.incremental = true\n").unwrap();
+
+    let options = || test_options().cargo_home(Some(home.clone()));
+    let config = Config::load_with_options(dir, options()).unwrap();
+    assert_eq!(config.build.jobs, Some(Jobs::Count(3)));
+    assert_eq!(config.build.incremental, Some(true));
+    let config = Config::load_with_options(dir, options().include_cargo_home(false)).unwrap();
+    assert_eq!(config.build.jobs, None);
+    assert_eq!(config.build.incremental, Some(true));
+
+    // CARGO_HOME in the ancestors of the current directory is also skipped.
+    let options = || test_options().cargo_home(Some(root.join(".cargo")));
+    let config = Config::load_with_options(dir, options()).unwrap();
+    assert_eq!(config.build.incremental, Some(true));
+    let config = Config::load_with_options(dir, options().include_cargo_home(false)).unwrap();
+    assert_eq!(config.build.incremental, None);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn empty_runner_env() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    let target = "x86_64-unknown-linux-gnu";
+    let env = "CARGO_TARGET_X86_64_UNKNOWN_LINUX_GNU_RUNNER";
+
+    let config = Config::load_with_options(dir, test_options().env([(env, "")])).unwrap();
+    assert_eq!(config.runner(target).unwrap(), None);
+
+    fs::write(root.join(".cargo/config.toml"), format!("target.{target}.runner = \"qemu\"\n"))
+        .unwrap();
+    let config = Config::load_with_options(dir, test_options().env([(env, " ")])).unwrap();
+    assert_eq!(config.runner(target).unwrap(), Some(PathAndArgs::new("qemu")));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support pipe2 (inside std::process::Command::output)
+fn runner_precedence() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    let target = "x86_64-unknown-linux-gnu";
+    let env = [("CARGO_TARGET_X86_64_UNKNOWN_LINUX_GNU_RUNNER", "env-runner")];
+    let config_path = &root.join(".cargo/config.toml");
+    let runner = |env: &[(&str, &str)]| {
+        let config =
+            Config::load_with_options(dir, test_options().env(env.iter().copied())).unwrap();
+        config.runner(target).unwrap()
+    };
+
+    // 3. target.<cfg>.runner
+    fs::write(config_path, "[target.'cfg(target_os = \"linux\")']\nrunner = \"cfg-runner\"\n")
+        .unwrap();
+    assert_eq!(runner(&[]), Some(PathAndArgs::new("cfg-runner")));
+    assert_eq!(runner(&env), Some(PathAndArgs::new("env-runner")));
+
+    // 2. target.<triple>.runner
+    fs::write(
+        config_path,
+        "[target.'cfg(target_os = \"linux\")']\nrunner = \"cfg-runner\"\n\
+         [target.x86_64-unknown-linux-gnu]\nrunner = \"triple-runner\"\n",
+    )
+    .unwrap();
+    assert_eq!(runner(&[]), Some(PathAndArgs::new("triple-runner")));
+
+    // 1. CARGO_TARGET_<triple>_RUNNER
+    assert_eq!(runner(&env), Some(PathAndArgs::new("env-runner")));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support pipe2 (inside std::process::Command::output)
+fn multiple_cfg_rustflags() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    let target = "x86_64-unknown-linux-gnu";
+    // Matching `target.<cfg>` tables are applied in the lexicographical order
+    // of the keys, regardless of the order in the config file.
+    fs::write(
+        root.join(".cargo/config.toml"),
+        "[target.'cfg(unix)']\nrustflags = [\"--unix\"]\n\
+         [target.'cfg(target_os = \"linux\")']\nrustflags = [\"--linux\"]\n\
+         [target.'cfg(windows)']\nrustflags = [\"--windows\"]\n\
+         [target.x86_64-unknown-linux-gnu]\nrustflags = [\"--triple\"]\n",
+    )
+    .unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.rustflags(target).unwrap(), Some(["--triple", "--linux", "--unix"].into()));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn de_get() {
+    let (_tmp, root) = test_project("reference");
+    let dir = &root;
+    let config = de_load(dir, test_options()).unwrap();
+    let config_path = root.join(".cargo/config.toml");
+
+    assert_eq!(config.get_bool("net.offline").unwrap(), Some(true));
+    assert_eq!(config.get_definition("net.offline"), Some(&de::Definition::Path(config_path)));
+    assert_eq!(config.get_list("build.rustflags").unwrap(), Some(vec!["…", "…"]));
+    assert_eq!(
+        config.get_list("target.x86_64-unknown-linux-gnu.rustflags").unwrap(),
+        Some(vec!["b", "bb"])
+    );
+    assert_eq!(config.get_list("alias.rr").unwrap(), Some(vec!["run", "--release"]));
+    assert_eq!(config.get_integer("build.jobs").unwrap(), Some(1));
+    assert_eq!(config.get_string("build.rustc").unwrap(), Some("rustc"));
+    assert_eq!(config.get_string("term.color").unwrap(), Some("auto"));
+    // unset or unknown keys
+    assert_eq!(config.get_bool("http.debug-unknown").unwrap(), None);
+    assert_eq!(config.get_list("target.aarch64-unknown-linux-gnu.rustflags").unwrap(), None);
+    assert_eq!(config.get_definition("build"), None);
+    // type mismatch
+    assert_eq!(
+        config.get_bool("build.jobs").unwrap_err().to_string(),
+        "invalid type for `build.jobs`: expected a boolean, but found an integer"
+    );
+    config.get_string("build").unwrap_err();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn current_dir() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root.join("a");
+    fs::create_dir_all(dir).unwrap();
+
+    let config = Config::load_with_cwd(dir).unwrap();
+    assert_eq!(config.current_dir(), dir);
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.current_dir(), dir);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn link_override() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root.join("a");
+    fs::create_dir_all(dir).unwrap();
+    fs::write(
+        root.join(".cargo/config.toml"),
+        r#"
+[target.x86_64-unknown-linux-gnu]
+linker = "cc"
+[target.x86_64-unknown-linux-gnu.foo]
+rustc-link-lib = ["foo"]
+rustc-link-search = ["lib", "native=lib2", "/abs"]
+rustc-flags = "-L /some/path"
+rustc-cfg = ['key="value"']
+rustc-env = { FOO = "bar" }
+"#,
+    )
+    .unwrap();
+
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    let target = config.target("x86_64-unknown-linux-gnu").unwrap();
+    assert_eq!(target.linker, Some("cc".into()));
+    let foo = &target.links["foo"];
+    assert_eq!(foo.rustc_link_lib, ["foo"]);
+    assert_eq!(foo.rustc_link_search, [
+        root.join("lib"),
+        format!("native={}", root.join("lib2").display()).into(),
+        "/abs".into()
+    ]);
+    assert_eq!(foo.rustc_flags.as_deref(), Some("-L /some/path"));
+    assert_eq!(foo.rustc_cfg, ["key=\"value\""]);
+    assert_eq!(foo.rustc_env["FOO"], "bar");
+    assert!(config.target("aarch64-unknown-linux-gnu").unwrap().links.is_empty());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support pipe2 (inside std::process::Command::output)
+fn link_override_unknown_keys() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    let config_path = &root.join(".cargo/config.toml");
+    let target = "x86_64-unknown-linux-gnu";
+
+    // Unknown non-table keys are ignored.
+    fs::write(
+        config_path,
+        r#"
+[target.x86_64-unknown-linux-gnu]
+foo = "bar"
+bar = 1
+baz = [1]
+qux = true
+[target.x86_64-unknown-linux-gnu.lib]
+rustc-link-lib = ["lib"]
+[target.'cfg(unix)']
+foo = 1
+"#,
+    )
+    .unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.target(target).unwrap().links.keys().collect::<Vec<_>>(), ["lib"]);
+    let config = de_load(dir, test_options()).unwrap();
+    assert!(config.target["cfg(unix)"].links.is_empty());
+    assert_eq!(config.validate(dir), []);
+
+    // Link overrides in `[target.<cfg>]` tables are ignored.
+    fs::write(config_path, "[target.'cfg(unix)'.lib]\nrustc-link-lib = [\"lib\"]\n").unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert!(config.target(target).unwrap().links.is_empty());
+    let config = de_load(dir, test_options()).unwrap();
+    let diagnostics = config.validate(dir);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "unused key `lib` in [target] config table `cfg(unix)`; overriding build scripts is only \
+         supported in `[target.<triple>]` tables"
+    );
+    assert_eq!(diagnostics[0].definition, Some(de::Definition::Path(config_path.clone())));
+}
+
+#[cfg(unix)] // On Windows, backslashes in the spec path are escaped in TOML.
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn serialize_target_order() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    fs::write(
+        root.join(".cargo/config.toml"),
+        r#"
+[target.x86_64-unknown-linux-gnu]
+linker = "x"
+[target.aarch64-unknown-linux-gnu]
+linker = "a"
+"#,
+    )
+    .unwrap();
+    let spec_path = root.join("spec/custom.json");
+
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    // Query targets in an order different from the serialized order.
+    config.target("x86_64-unknown-linux-gnu").unwrap();
+    config.target(TargetTriple::from_spec_path(&spec_path, None)).unwrap();
+    config.target("aarch64-unknown-linux-gnu").unwrap();
+    // Targets are sorted by the value passed to cargo's `--target` option,
+    // so the spec path (starting with `/`) comes before the target triples.
+    let expected = format!(
+        "[target.\"{}\"]\n\n\
+         [target.aarch64-unknown-linux-gnu]\nlinker = \"a\"\n\n\
+         [target.x86_64-unknown-linux-gnu]\nlinker = \"x\"\n",
+        spec_path.display()
+    );
+    assert_eq!(toml::to_string(&config).unwrap(), expected);
+    // Querying targets again does not change the output.
+    config.target("aarch64-unknown-linux-gnu").unwrap();
+    config.target("x86_64-unknown-linux-gnu").unwrap();
+    assert_eq!(toml::to_string(&config).unwrap(), expected);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn flags_encoded() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    let target = "x86_64-unknown-linux-gnu";
+
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.rustflags_encoded(target).unwrap(), None);
+    assert_eq!(config.rustdocflags_encoded(target).unwrap(), None);
+
+    fs::write(
+        root.join(".cargo/config.toml"),
+        "build.rustflags = [\"--cfg\", \"a b\"]\nbuild.rustdocflags = \"-D warnings\"\n",
+    )
+    .unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    let rustflags = config.rustflags_encoded(target).unwrap().unwrap();
+    assert_eq!(rustflags, "--cfg\x1fa b");
+    assert_eq!(Flags::from_encoded(&rustflags), config.rustflags(target).unwrap().unwrap());
+    let rustdocflags = config.rustdocflags_encoded(target).unwrap().unwrap();
+    assert_eq!(rustdocflags, "-D\x1fwarnings");
+    assert_eq!(Flags::from_encoded(&rustdocflags), config.rustdocflags(target).unwrap().unwrap());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn de_load_files() {
+    let (_tmp, root) = test_project("empty");
+    let a = &root.join("a.toml");
+    let b = &root.join("b.toml");
+    fs::write(a, "build.jobs = 1\nbuild.rustflags = [\"a\"]\n").unwrap();
+    fs::write(b, "build.jobs = 2\nbuild.rustflags = [\"b\"]\nbuild.incremental = true\n").unwrap();
+
+    let config = de::Config::load_files([a, b]).unwrap();
+    assert_eq!(config.build.jobs.as_ref().unwrap().val, Jobs::Count(1));
+    assert_eq!(config.build.jobs.unwrap().definition, Some(de::Definition::Path(a.clone())));
+    assert!(config.build.incremental.unwrap().val);
+    assert_eq!(
+        config.build.rustflags.unwrap().flags.iter().map(|v| &*v.val).collect::<Vec<_>>(),
+        ["b", "a"]
+    );
+
+    let config = de::Config::load_files([b, a]).unwrap();
+    assert_eq!(config.build.jobs.unwrap().val, Jobs::Count(2));
+
+    let config = de::Config::load_files::<_, &Path>([]).unwrap();
+    assert!(config.build.jobs.is_none());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn is_empty() {
+    assert!(NetConfig::default().is_empty());
+    assert!(TermConfig::default().is_empty());
+    assert!(BuildConfig::default().is_empty());
+
+    let (_tmp, root) = test_project("empty");
+    let dir = &root.join("a");
+    fs::create_dir_all(dir).unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert!(config.net.is_empty());
+    assert!(config.term.is_empty());
+    fs::write(root.join(".cargo/config.toml"), "net.offline = true\nterm.progress.width = 80\n")
+        .unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert!(!config.net.is_empty());
+    assert!(!config.term.is_empty());
+    assert!(!config.term.progress.is_empty());
+    assert!(config.build.is_empty());
+}
+
+#[cfg(feature = "toml_edit")]
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn to_document() {
+    let (_tmp, root) = test_project("reference");
+    let config = de_load(&root, test_options()).unwrap();
+
+    let doc = config.to_document().unwrap();
+    let s = doc.to_string();
+    // Round-tripping an unmodified document is a no-op.
+    assert_eq!(s.parse::<toml_edit::DocumentMut>().unwrap().to_string(), s);
+    let roundtrip: de::Config = toml::from_str(&s).unwrap();
+    assert_eq!(toml::to_string(&roundtrip).unwrap(), toml::to_string(&config).unwrap());
+
+    let mut doc = doc;
+    doc["build"]["jobs"] = toml_edit::value(4);
+    let edited: de::Config = toml::from_str(&doc.to_string()).unwrap();
+    assert_eq!(edited.build.jobs.unwrap().val, Jobs::Count(4));
+    assert_eq!(toml::to_string(&edited.net).unwrap(), toml::to_string(&config.net).unwrap());
+}
+
+#[cfg(feature = "toml_edit")]
+#[test]
+fn to_document_all_tables() {
+    let config: de::Config = toml::from_str(
+        r#"
+        alias.b = "build"
+        alias.r = ["run", "--release"]
+        build.jobs = "default"
+        build.rustc = "rustc"
+        build.rustc-wrapper = "w"
+        build.rustc-workspace-wrapper = "ww"
+        build.rustdoc = "rustdoc"
+        build.target = ["a", "b"]
+        build.target-dir = "target"
+        build.build-dir = "{workspace-root}/build"
+        build.rustflags = "-C opt-level=1"
+        build.rustdocflags = ["--cfg", "a"]
+        build.incremental = true
+        build.dep-info-basedir = "."
+        build.warnings = "deny"
+        doc.browser = ["firefox", "--new-tab"]
+        env.A = "a"
+        env.B = { value = "b", force = true, relative = true }
+        future-incompat-report.frequency = "never"
+        cargo-new.vcs = "git"
+        http.debug = false
+        http.proxy = "host:port"
+        http.timeout = 30
+        http.cainfo = "cert.pem"
+        http.check-revoke = true
+        http.low-speed-limit = 10
+        http.multiplexing = true
+        http.user-agent = "ua"
+        net.retry = 2
+        net.git-fetch-with-cli = true
+        net.offline = true
+        net.ssh.known-hosts = ["github.com ssh-ed25519 AAAA"]
+        registries.my-reg = { index = "sparse+https://example.com/", token = "t", protocol = "sparse" }
+        registry.default = "my-reg"
+        registry.token = "t"
+        target.x86_64-unknown-linux-gnu.linker = "cc"
+        target.x86_64-unknown-linux-gnu.runner = "r"
+        target.x86_64-unknown-linux-gnu.rustflags = ["-C", "a"]
+        target.x86_64-unknown-linux-gnu.rustdocflags = ["-C", "b"]
+        target.x86_64-unknown-linux-gnu.foo.rustc-link-lib = ["foo"]
+        target.x86_64-unknown-linux-gnu.foo.rustc-link-search = ["/path"]
+        target.x86_64-unknown-linux-gnu.foo.rustc-flags = "-L /path"
+        target.x86_64-unknown-linux-gnu.foo.rustc-cfg = ["key=\"value\""]
+        target.x86_64-unknown-linux-gnu.foo.rustc-env = { A = "a" }
+        target.'cfg(unix)'.runner = ["r", "a"]
+        term.quiet = false
+        term.verbose = false
+        term.color = "always"
+        term.progress.when = "always"
+        term.progress.width = 80
+        "#,
+    )
+    .unwrap();
+    for (table, is_empty) in [
+        ("build", config.build.is_empty()),
+        ("doc", config.doc.is_empty()),
+        ("future-incompat-report", config.future_incompat_report.is_empty()),
+        ("cargo-new", config.cargo_new.is_empty()),
+        ("http", config.http.is_empty()),
+        ("net", config.net.is_empty()),
+        ("registry", config.registry.is_empty()),
+        ("term", config.term.is_empty()),
+    ] {
+        assert!(!is_empty, "{table}");
+    }
+
+    let s = config.to_document().unwrap().to_string();
+    let roundtrip: de::Config = toml::from_str(&s).unwrap();
+    assert_eq!(toml::to_string(&roundtrip).unwrap(), toml::to_string(&config).unwrap());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn target_runner_env() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root.join("a");
+    fs::create_dir_all(dir).unwrap();
+    let target = "aarch64-unknown-linux-gnu";
+
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.target_runner_env(target).unwrap(), None);
+
+    fs::write(
+        root.join(".cargo/config.toml"),
+        format!(
+            "target.{target}.runner = [\"qemu-aarch64\", \"-L\", \"/usr/aarch64-linux-gnu\"]\n"
+        ),
+    )
+    .unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(
+        config.target_runner_env(target).unwrap(),
+        Some((
+            "CARGO_TARGET_AARCH64_UNKNOWN_LINUX_GNU_RUNNER".to_owned(),
+            "qemu-aarch64 -L /usr/aarch64-linux-gnu".to_owned()
+        ))
+    );
+
+    fs::write(
+        root.join(".cargo/config.toml"),
+        format!("target.{target}.runner = [\"qemu-aarch64\", \"a b\"]\n"),
+    )
+    .unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    config.target_runner_env(target).unwrap_err();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn target_linker_env() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root.join("a");
+    fs::create_dir_all(dir).unwrap();
+    let target = "aarch64-unknown-linux-gnu";
+
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.target_linker_env(target).unwrap(), None);
+
+    fs::write(
+        root.join(".cargo/config.toml"),
+        format!("target.{target}.linker = \"aarch64-linux-gnu-gcc\"\n"),
+    )
+    .unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(
+        config.target_linker_env(target).unwrap(),
+        Some((
+            "CARGO_TARGET_AARCH64_UNKNOWN_LINUX_GNU_LINKER".to_owned(),
+            "aarch64-linux-gnu-gcc".to_owned()
+        ))
+    );
+
+    // Config-relative paths are resolved, and whitespace is allowed.
+    fs::write(
+        root.join(".cargo/config.toml"),
+        format!("target.{target}.linker = \"tools/my linker\"\n"),
+    )
+    .unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(
+        config.target_linker_env(target).unwrap(),
+        Some((
+            "CARGO_TARGET_AARCH64_UNKNOWN_LINUX_GNU_LINKER".to_owned(),
+            root.join("tools/my linker").to_str().unwrap().to_owned()
+        ))
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn duplicate_key() {
+    fn error_chain(e: &Error) -> Vec<String> {
+        let mut chain = vec![e.to_string()];
+        let mut source = std::error::Error::source(e);
+        while let Some(e) = source {
+            chain.push(e.to_string());
+            source = e.source();
+        }
+        chain
+    }
+
+    // Duplicate tables.
+    let (_tmp, root) = test_project("duplicate-key");
+    let path = &root.join(".cargo/config.toml");
+    let e = de::Config::load_file(path).unwrap_err();
+    let chain = error_chain(&e);
+    assert_eq!(chain[0], format!("failed to parse `{}` as cargo configuration", path.display()));
+    assert!(chain[1].contains("duplicate key"), "{chain:?}");
+    let e = Config::load_with_options(&root, test_options()).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        format!("failed to parse `{}` as cargo configuration", path.display())
+    );
+
+    // Duplicate scalars.
+    fs::write(path, "[build]\njobs = 1\njobs = 2\n").unwrap();
+    let e = de::Config::load_file(path).unwrap_err();
+    let chain = error_chain(&e);
+    assert_eq!(chain[0], format!("failed to parse `{}` as cargo configuration", path.display()));
+    assert!(chain[1].contains("duplicate key"), "{chain:?}");
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn invalid_rustflags() {
+    let (_tmp, root) = test_project("invalid-rustflags");
+    let path = &root.join(".cargo/config.toml");
+    let e = de::Config::load_file(path).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        format!("failed to parse `{}` as cargo configuration", path.display())
+    );
+    let source = std::error::Error::source(&e).unwrap().to_string();
+    assert!(source.contains("expected an array of strings, found integer at index 0"), "{source}");
+    assert!(source.contains("rustflags = [1, 2]"), "{source}");
+
+    fs::write(path, "[target.x86_64-unknown-linux-gnu]\nrustdocflags = [\"--cfg\", true]\n")
+        .unwrap();
+    let e = de::Config::load_file(path).unwrap_err();
+    let source = std::error::Error::source(&e).unwrap().to_string();
+    assert!(source.contains("expected an array of strings, found boolean at index 1"), "{source}");
+
+    // Strings and arrays of strings are still accepted.
+    fs::write(path, "build.rustflags = \"--cfg a\"\nbuild.rustdocflags = [\"--cfg\", \"b\"]\n")
+        .unwrap();
+    let config = de::Config::load_file(path).unwrap();
+    assert_eq!(config.build.rustflags.unwrap().flags.len(), 2);
+    assert_eq!(config.build.rustdocflags.unwrap().flags.len(), 2);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn parse_error_span() {
+    let (_tmp, root) = test_project("empty");
+    let path = &root.join(".cargo/config.toml");
+    let content = "[build]\njobs = 1\n[net]\noffline = \"yes\"\n";
+    fs::write(path, content).unwrap();
+
+    let e = de::Config::load_file(path).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        format!("failed to parse `{}` as cargo configuration", path.display())
+    );
+    assert_eq!(e.config_path(), Some(&**path));
+    let span = e.span().unwrap();
+    assert!(!span.is_empty());
+    assert_eq!(&content[span], "\"yes\"");
+
+    let e = Config::load_with_options(&root, test_options()).unwrap_err();
+    assert_eq!(e.config_path(), Some(&**path));
+    assert_eq!(&content[e.span().unwrap()], "\"yes\"");
+
+    // Errors other than parse errors have no span.
+    fs::write(path, "registries.my-registry.protocol = \"sparse\"\n").unwrap();
+    let e = Config::load_with_options(&root, test_options()).unwrap_err();
+    assert_eq!(e.span(), None);
+    assert_eq!(e.config_path(), None);
+}
+
+#[cfg(unix)]
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support pipe2 (inside std::process::Command::output)
+fn rustc_option_eval_cfg() {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    let log = root.join("spawn.log");
+    let rustc = root.join("rustc.sh");
+    fs::write(
+        &rustc,
+        format!("#!/bin/sh\necho \"$@\" >> \"{}\"\nexec rustc \"$@\"\n", log.display()),
+    )
+    .unwrap();
+    fs::set_permissions(&rustc, std::fs::Permissions::from_mode(0o755)).unwrap();
+    fs::write(
+        root.join(".cargo/config.toml"),
+        "[target.'cfg(all())']\nrustflags = [\"--cfg\", \"a\"]\n",
+    )
+    .unwrap();
+
+    let options = ResolveOptions::default()
+        .env(HashMap::<String, String>::default())
+        .cargo_home(None)
+        .rustc(PathAndArgs::new(&rustc));
+    let config = Config::load_with_options(dir, options).unwrap();
+    assert_eq!(config.rustflags(TARGET).unwrap(), Some(Flags::from_space_separated("--cfg a")));
+    assert_eq!(fs::read_to_string(&log).unwrap(), format!("--print cfg --target {TARGET}\n"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn net_ssh() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root.join("a");
+    fs::create_dir_all(dir).unwrap();
+    fs::write(
+        root.join(".cargo/config.toml"),
+        r#"
+[net.ssh]
+known-hosts = [
+    "example.com ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIAaaa",
+    "@revoked example.org ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIBbbb",
+]
+"#,
+    )
+    .unwrap();
+
+    let config = de_load(dir, test_options()).unwrap();
+    let known_hosts = config.net.ssh.known_hosts.as_ref().unwrap();
+    assert_eq!(known_hosts.list.len(), 2);
+    assert_eq!(
+        known_hosts.list[0].definition,
+        Some(de::Definition::Path(root.join(".cargo/config.toml")))
+    );
+
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.net.ssh.known_hosts.as_ref().unwrap().list, [
+        "example.com ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIAaaa",
+        "@revoked example.org ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIBbbb",
+    ]);
+    assert!(!config.net.is_empty());
+    assert_eq!(
+        toml::to_string(&config.net).unwrap(),
+        "[ssh]\nknown-hosts = [\"example.com ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIAaaa\", \"@revoked example.org ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIBbbb\"]\n"
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn build_jobs_for_cli() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root.join("a");
+    fs::create_dir_all(dir).unwrap();
+
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.build_jobs_for_cli(), None);
+    for (jobs, expected) in [("4", Some("4")), ("-2", Some("-2")), ("\"default\"", None)] {
+        fs::write(root.join(".cargo/config.toml"), format!("build.jobs = {jobs}\n")).unwrap();
+        let config = Config::load_with_options(dir, test_options()).unwrap();
+        assert_eq!(config.build_jobs_for_cli().as_deref(), expected);
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn should_show_future_incompat() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root.join("a");
+    fs::create_dir_all(dir).unwrap();
+
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert!(config.should_show_future_incompat());
+    for (frequency, expected) in [("always", true), ("never", false)] {
+        fs::write(
+            root.join(".cargo/config.toml"),
+            format!("future-incompat-report.frequency = \"{frequency}\"\n"),
+        )
+        .unwrap();
+        let config = Config::load_with_options(dir, test_options()).unwrap();
+        assert_eq!(config.should_show_future_incompat(), expected);
+    }
+
+    // Environment variable takes precedence over config.
+    let options = test_options().env(HashMap::from([(
+        "CARGO_FUTURE_INCOMPAT_REPORT_FREQUENCY".to_owned(),
+        "always".to_owned(),
+    )]));
+    let config = Config::load_with_options(dir, options).unwrap();
+    assert!(config.should_show_future_incompat());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn rustc_command_for() {
+    fn argv(cmd: &Command) -> Vec<&std::ffi::OsStr> {
+        Some(cmd.get_program()).into_iter().chain(cmd.get_args()).collect()
+    }
+
+    let (_tmp, root) = test_project("empty");
+    let dir = &root.join("a");
+    fs::create_dir_all(dir).unwrap();
+    let options =
+        || ResolveOptions::default().env(HashMap::<String, String>::default()).cargo_home(None);
+
+    for (config, member, non_member) in [
+        ("build.rustc = \"rustc\"\n", &["rustc"][..], &["rustc"][..]),
+        ("build.rustc = \"rustc\"\nbuild.rustc-wrapper = \"w\"\n", &["w", "rustc"], &["w", "rustc"]),
+        (
+            "build.rustc = \"rustc\"\nbuild.rustc-workspace-wrapper = \"ww\"\n",
+            &["ww", "rustc"],
+            &["rustc"],
+        ),
+        (
+            "build.rustc = \"rustc\"\nbuild.rustc-wrapper = \"w\"\nbuild.rustc-workspace-wrapper = \"ww\"\n",
+            &["w", "ww", "rustc"],
+            &["w", "rustc"],
+        ),
+    ] {
+        fs::write(root.join(".cargo/config.toml"), config).unwrap();
+        let config = Config::load_with_options(dir, options()).unwrap();
+        assert_eq!(argv(&config.rustc_command_for(true)), member);
+        assert_eq!(argv(&config.rustc_command_for(false)), non_member);
+    }
+}
+
+#[test]
+fn default_config() {
+    let config = Config::default();
+    assert!(config.build.is_empty());
+    assert!(config.alias.is_empty());
+    assert_eq!(config.current_dir(), Path::new("."));
+    assert!(config.configured_targets().is_empty());
+    assert_eq!(config.rustflags("x86_64-unknown-linux-gnu").unwrap(), None);
+    assert_eq!(config, Config::default());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn verbosity() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root.join("a");
+    fs::create_dir_all(dir).unwrap();
+
+    // Config only.
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.verbosity(false, 0).unwrap(), Verbosity::Normal);
+    fs::write(root.join(".cargo/config.toml"), "term.quiet = true\n").unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.verbosity(false, 0).unwrap(), Verbosity::Quiet);
+    // CLI overrides config.
+    assert_eq!(config.verbosity(false, 1).unwrap(), Verbosity::Verbose);
+    fs::write(root.join(".cargo/config.toml"), "term.verbose = true\n").unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.verbosity(false, 0).unwrap(), Verbosity::Verbose);
+    assert_eq!(config.verbosity(true, 0).unwrap(), Verbosity::Quiet);
+    // Conflicts.
+    assert_eq!(
+        config.verbosity(true, 2).unwrap_err().to_string(),
+        "cannot set both --verbose and --quiet"
+    );
+    fs::write(root.join(".cargo/config.toml"), "term.verbose = true\nterm.quiet = true\n").unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(
+        config.verbosity(false, 0).unwrap_err().to_string(),
+        "cannot set both `term.verbose` and `term.quiet`"
+    );
+    assert_eq!(config.verbosity(true, 0).unwrap(), Verbosity::Quiet);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn registry_token() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root.join("a");
+    fs::create_dir_all(dir).unwrap();
+    fs::write(
+        root.join(".cargo/config.toml"),
+        "registry.token = \"a\"\nregistries.my-registry.token = \"b\"\nregistries.crates-io.token = \"c\"\n",
+    )
+    .unwrap();
+
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.registry_token("crates-io"), Some("a"));
+    assert_eq!(config.registry_token("my-registry"), Some("b"));
+    assert_eq!(config.registry_token("other"), None);
+
+    let env = [
+        ("CARGO_REGISTRY_TOKEN", "d"),
+        ("CARGO_REGISTRIES_MY_REGISTRY_TOKEN", "e"),
+        ("CARGO_REGISTRIES_OTHER_TOKEN", "f"),
+    ];
+    let config = Config::load_with_options(dir, test_options().env(env)).unwrap();
+    assert_eq!(config.registry_token("crates-io"), Some("d"));
+    assert_eq!(config.registry_token("my-registry"), Some("e"));
+    assert_eq!(config.registry_token("other"), Some("f"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn registries_env_name() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    let config_path = &root.join(".cargo/config.toml");
+    fs::write(
+        config_path,
+        "[registries.my-reg]\nindex = \"https://example.com/config\"\ntoken = \"a\"\n",
+    )
+    .unwrap();
+    let env = [
+        ("CARGO_REGISTRIES_MY_REG_INDEX", "https://example.com/env"),
+        ("CARGO_REGISTRIES_MY_REG_TOKEN", "b"),
+    ];
+
+    let config = Config::load_with_options(dir, test_options().env(env)).unwrap();
+    // The environment variables refer to the registry defined in config.
+    assert_eq!(config.registries.keys().collect::<Vec<_>>(), ["my-reg"]);
+    assert_eq!(config.registries["my-reg"].index.as_deref(), Some("https://example.com/env"));
+    assert_eq!(config.registries["my-reg"].token.as_deref(), Some("b"));
+    assert_eq!(config.registry_token("my-reg"), Some("b"));
+    assert_eq!(
+        config.registry_index_url("my-reg").unwrap().as_deref(),
+        Some("registry+https://example.com/env")
+    );
+    let mut de_config = de_load(dir, test_options()).unwrap();
+    de_config.apply_env(&test_options().env(env).into_resolve_context(dir.clone())).unwrap();
+    assert_eq!(
+        de_config.registries["my-reg"].index.as_ref().unwrap().definition,
+        Some(de::Definition::Environment("CARGO_REGISTRIES_MY_REG_INDEX".into()))
+    );
+
+    // If the registry is not defined in config, the registry name is derived
+    // from the name of the environment variable.
+    fs::write(config_path, "").unwrap();
+    let config = Config::load_with_options(dir, test_options().env(env)).unwrap();
+    assert_eq!(config.registries.keys().collect::<Vec<_>>(), ["my-reg"]);
+    assert_eq!(config.registries["my-reg"].index.as_deref(), Some("https://example.com/env"));
+    assert_eq!(config.registries["my-reg"].token.as_deref(), Some("b"));
+    assert_eq!(config.registry_token("my-reg"), Some("b"));
+    assert_eq!(config.registry_token("my_reg"), Some("b"));
+    assert_eq!(config.registry_token("other-reg"), None);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn validate() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root.join("a");
+    fs::create_dir_all(dir).unwrap();
+    let path = &root.join(".cargo/config.toml");
+    let def = Some(de::Definition::Path(path.clone()));
+
+    let config = de_load(dir, test_options()).unwrap();
+    assert_eq!(config.validate(dir), []);
+
+    fs::write(path, "build.jobs = 0\n").unwrap();
+    let config = de_load(dir, test_options()).unwrap();
+    let diagnostics = config.validate(dir);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].message, "`build.jobs` may not be 0");
+    assert_eq!(diagnostics[0].definition, def);
+    assert_eq!(
+        diagnostics[0].to_string(),
+        format!("`build.jobs` may not be 0 (defined in {})", path.display())
+    );
+
+    // An empty wrapper explicitly unsets the wrapper.
+    fs::write(path, "build.rustc-wrapper = \"\"\nbuild.rustc-workspace-wrapper = \"\"\n").unwrap();
+    let config = de_load(dir, test_options()).unwrap();
+    assert_eq!(config.validate(dir), []);
+
+    fs::write(path, "build.target = [\"x86_64-unknown-linux-gnu\", \"specs/missing.json\"]\n")
+        .unwrap();
+    let config = de_load(dir, test_options()).unwrap();
+    let diagnostics = config.validate(dir);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        format!(
+            "invalid `build.target`: target path \"{}\" is not a valid file",
+            root.join("specs/missing.json").display()
+        )
+    );
+    assert_eq!(diagnostics[0].definition, def);
+    fs::create_dir_all(root.join("specs")).unwrap();
+    fs::write(root.join("specs/missing.json"), "{}").unwrap();
+    assert_eq!(config.validate(dir), []);
+
+    fs::write(
+        path,
+        "registries.crates-io.protocol = \"git\"\nregistries.my-registry.protocol = \"sparse\"\n",
+    )
+    .unwrap();
+    let config = de_load(dir, test_options()).unwrap();
+    let diagnostics = config.validate(dir);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "`registries.my-registry.protocol` is not allowed for registries other than crates-io"
+    );
+    assert_eq!(diagnostics[0].definition, def);
+
+    fs::write(path, "alias.build = \"test\"\nalias.b = \"test\"\n").unwrap();
+    let config = de_load(dir, test_options()).unwrap();
+    let diagnostics = config.validate(dir);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "user-defined alias `build` is ignored, because it is shadowed by a built-in command"
+    );
+    assert_eq!(diagnostics[0].definition, def);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn registry_protocol() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root.join("a");
+    fs::create_dir_all(dir).unwrap();
+    let path = &root.join(".cargo/config.toml");
+
+    fs::write(path, "registries.crates-io.protocol = \"git\"\n").unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.registries["crates-io"].protocol, Some(RegistriesProtocol::Git));
+
+    fs::write(path, "registries.my-registry.protocol = \"sparse\"\n").unwrap();
+    let e = Config::load_with_options(dir, test_options()).unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        format!(
+            "`registries.my-registry.protocol` is not allowed for registries other than crates-io \
+             (defined in {})",
+            path.display()
+        )
+    );
+}
+
+#[test]
+fn resolve() {
+    let dir = Path::new("/tmp/a");
+    let mut de = de::Config::default();
+    de.build.jobs = Some(de::Value { val: Jobs::Count(2), definition: None });
+    de.build.target_dir =
+        Some(de::Value { val: "target".to_owned(), definition: Some(de::Definition::Cli(None)) });
+    de.build.build_dir = Some(de::Value { val: "build".to_owned(), definition: None });
+    de.net.offline = Some(de::Value { val: true, definition: None });
+    let options = test_options().env([("CARGO_BUILD_JOBS", "4"), ("CARGO_TERM_COLOR", "always")]);
+
+    let config = Config::resolve(de, options, dir).unwrap();
+    assert_eq!(config.build.jobs, Some(Jobs::Count(4)));
+    assert_eq!(config.build.target_dir, Some(dir.join("target")));
+    // Relative paths without definition are kept as is.
+    assert_eq!(config.build.build_dir, Some("build".into()));
+    assert_eq!(config.term.color, Some(Color::Always));
+    assert_eq!(config.net.offline, Some(true));
+    assert_eq!(config.current_dir(), dir);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn build_target_spec_path_relative_to_config() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root.join("a/b");
+    fs::create_dir_all(dir).unwrap();
+    fs::create_dir_all(root.join("a/.cargo")).unwrap();
+    fs::write(root.join("a/.cargo/config.toml"), "build.target = \"specs/foo.json\"\n").unwrap();
+
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    let targets = config.build.target.as_ref().unwrap();
+    assert_eq!(targets.len(), 1);
+    assert_eq!(targets[0].triple(), "foo");
+    // Resolved against the directory containing `.cargo`, not the current directory.
+    assert_eq!(targets[0].spec_path(), Some(&*root.join("a/specs/foo.json")));
+    let targets = config.build_target_for_config([] as [&str; 0]).unwrap();
+    assert_eq!(targets[0].spec_path(), Some(&*root.join("a/specs/foo.json")));
+
+    // Spec paths from environment variables are resolved against the current directory.
+    let options = test_options().env([("CARGO_BUILD_TARGET", "specs/bar.json")]);
+    let config = Config::load_with_options(dir, options).unwrap();
+    assert_eq!(
+        config.build.target.as_ref().unwrap()[0].spec_path(),
+        Some(&*dir.join("specs/bar.json"))
+    );
+}
+
+#[cfg(unix)]
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support pipe2 (inside std::process::Command::output)
+fn lazy_target_resolution() {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    let log = root.join("spawn.log");
+    let rustc = root.join("rustc.sh");
+    fs::write(
+        &rustc,
+        format!("#!/bin/sh\necho rustc >> \"{}\"\nexec rustc \"$@\"\n", log.display()),
+    )
+    .unwrap();
+    fs::set_permissions(&rustc, std::fs::Permissions::from_mode(0o755)).unwrap();
+    fs::write(
+        root.join(".cargo/config.toml"),
+        "net.offline = true\n[target.'cfg(all())']\nrunner = \"r\"\n",
+    )
+    .unwrap();
+
+    let config =
+        Config::load_with_options(dir, test_options().rustc(PathAndArgs::new(&rustc))).unwrap();
+    assert_eq!(config.net.offline, Some(true));
+    assert!(!config.net.is_empty());
+    assert!(!log.exists());
+
+    // Resolving target-specific config evaluates cfg.
+    assert_eq!(config.runner(TARGET).unwrap(), Some(PathAndArgs::new("r")));
+    assert_eq!(fs::read_to_string(&log).unwrap(), "rustc\n");
+}
+
+#[cfg(unix)]
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support pipe2 (inside std::process::Command::output)
+fn fresh_clone() {
+    use std::os::unix::fs::PermissionsExt as _;
+
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    let log = root.join("spawn.log");
+    let rustc = root.join("rustc.sh");
+    fs::write(
+        &rustc,
+        format!("#!/bin/sh\necho rustc >> \"{}\"\nexec rustc \"$@\"\n", log.display()),
+    )
+    .unwrap();
+    fs::set_permissions(&rustc, std::fs::Permissions::from_mode(0o755)).unwrap();
+    fs::write(root.join(".cargo/config.toml"), "[target.'cfg(all())']\nrunner = \"r\"\n").unwrap();
+
+    let config =
+        Config::load_with_options(dir, test_options().rustc(PathAndArgs::new(&rustc))).unwrap();
+    assert_eq!(config.runner(TARGET).unwrap(), Some(PathAndArgs::new("r")));
+    assert_eq!(fs::read_to_string(&log).unwrap(), "rustc\n");
+
+    // A normal clone shares the resolved results.
+    let clone = config.clone();
+    assert_eq!(clone.runner(TARGET).unwrap(), Some(PathAndArgs::new("r")));
+    assert_eq!(fs::read_to_string(&log).unwrap(), "rustc\n");
+
+    // A fresh clone resolves them again, independently of the original.
+    let fresh = config.fresh_clone();
+    assert_eq!(fresh, config);
+    assert_eq!(fresh.rustc(), config.rustc());
+    let fresh = std::thread::spawn(move || {
+        assert_eq!(fresh.runner(TARGET).unwrap(), Some(PathAndArgs::new("r")));
+        fresh
+    })
+    .join()
+    .unwrap();
+    assert_eq!(fs::read_to_string(&log).unwrap(), "rustc\nrustc\n");
+    assert_eq!(fresh.runner(TARGET).unwrap(), Some(PathAndArgs::new("r")));
+    assert_eq!(config.runner(TARGET).unwrap(), Some(PathAndArgs::new("r")));
+    assert_eq!(fs::read_to_string(&log).unwrap(), "rustc\nrustc\n");
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn shared_across_threads() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    fs::write(
+        root.join(".cargo/config.toml"),
+        "[target.x86_64-unknown-linux-gnu]\nrustflags = [\"--cfg\", \"a\"]\n\
+         [target.aarch64-unknown-linux-gnu]\nrustflags = [\"--cfg\", \"b\"]\n",
+    )
+    .unwrap();
+    let config = &Config::load_with_options(dir, test_options()).unwrap();
+
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(move || {
+                for (target, cfg) in
+                    [("x86_64-unknown-linux-gnu", "a"), ("aarch64-unknown-linux-gnu", "b")]
+                {
+                    assert_eq!(
+                        config.rustflags(target).unwrap(),
+                        Some(Flags::from(["--cfg", cfg]))
+                    );
+                }
+            });
+        }
+    });
+    assert_eq!(config.configured_targets().len(), 2);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn target_dir_or_default() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root.join("a");
+    fs::create_dir_all(dir).unwrap();
+    let workspace_root = &root.join("ws");
+
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.target_dir_or_default(workspace_root), workspace_root.join("target"));
+
+    fs::write(root.join(".cargo/config.toml"), "build.target-dir = \"out\"\n").unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.target_dir_or_default(workspace_root), root.join("out"));
+
+    let options = test_options().env([("CARGO_TARGET_DIR", "/tmp/env-target")]);
+    let config = Config::load_with_options(dir, options).unwrap();
+    assert_eq!(config.target_dir_or_default(workspace_root), Path::new("/tmp/env-target"));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support pipe2 (inside std::process::Command::output)
+fn target_cfgs() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+
+    let cfgs = config.target_cfgs("x86_64-unknown-linux-gnu").unwrap();
+    assert!(cfgs.iter().any(|c| c == "unix"), "{cfgs:?}");
+    assert!(cfgs.iter().any(|c| c == "target_os=\"linux\""), "{cfgs:?}");
+    assert!(cfgs.iter().any(|c| c == "target_arch=\"x86_64\""), "{cfgs:?}");
+    let cfgs = config.target_cfgs("x86_64-pc-windows-msvc").unwrap();
+    assert!(cfgs.iter().any(|c| c == "windows"), "{cfgs:?}");
+    assert!(!cfgs.iter().any(|c| c == "unix"), "{cfgs:?}");
+
+    let config = Config::load_with_options(
+        dir,
+        test_options()
+            .disable_rustc(true)
+            .host_triple("x86_64-unknown-linux-gnu")
+            .host_cfgs(["unix", "target_os=\"linux\""]),
+    )
+    .unwrap();
+    assert_eq!(config.target_cfgs("x86_64-unknown-linux-gnu").unwrap(), [
+        "unix",
+        "target_os=\"linux\""
+    ]);
+    assert!(config.target_cfgs("x86_64-pc-windows-msvc").is_err());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn disable_rustc() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    fs::write(
+        root.join(".cargo/config.toml"),
+        "[target.x86_64-unknown-linux-gnu]\nrustflags = [\"-C\", \"opt-level=1\"]\n\
+         [target.'cfg(unix)']\nrustflags = [\"-C\", \"opt-level=2\"]\n",
+    )
+    .unwrap();
+    let options = || test_options().rustc(PathAndArgs::new("no-such-rustc")).disable_rustc(true);
+
+    let config = Config::load_with_options(dir, options()).unwrap();
+    let e = config.rustflags("x86_64-unknown-linux-gnu").unwrap_err();
+    assert!(
+        format!("{e:#}").contains(
+            "failed to get target information for `x86_64-unknown-linux-gnu`: spawning rustc \
+             and cargo is disabled by `ResolveOptions::disable_rustc`"
+        ),
+        "{e:#}"
+    );
+    assert_eq!(
+        config.host_triple().unwrap_err().to_string(),
+        "failed to get host triple: spawning rustc and cargo is disabled by \
+         `ResolveOptions::disable_rustc`"
+    );
+    assert!(config.rustc_version().is_err());
+
+    // Pre-seeded values are available.
+    let config =
+        Config::load_with_options(dir, options().host_triple("x86_64-unknown-linux-gnu")).unwrap();
+    assert_eq!(config.host_triple().unwrap(), "x86_64-unknown-linux-gnu");
+    assert!(config.rustflags("x86_64-unknown-linux-gnu").is_err());
+    let options = || {
+        options().host_triple("x86_64-unknown-linux-gnu").host_cfgs(["unix", "target_os=\"linux\""])
+    };
+    let config = Config::load_with_options(dir, options()).unwrap();
+    assert_eq!(
+        config.rustflags("x86_64-unknown-linux-gnu").unwrap(),
+        Some(["-C", "opt-level=1", "-C", "opt-level=2"].into())
+    );
+    // Only the host is pre-seeded.
+    assert!(config.rustflags("aarch64-unknown-linux-gnu").is_err());
+    // The given values are used as is.
+    let config = Config::load_with_options(dir, options().host_cfgs(["windows"])).unwrap();
+    assert_eq!(
+        config.rustflags("x86_64-unknown-linux-gnu").unwrap(),
+        Some(["-C", "opt-level=1"].into())
+    );
+    // No effect if the host triple cannot be detected.
+    let config =
+        Config::load_with_options(dir, test_options().disable_rustc(true).host_cfgs(["unix"]))
+            .unwrap();
+    assert!(config.rustflags("x86_64-unknown-linux-gnu").is_err());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support pipe2 (inside std::process::Command::output)
+fn host_cfgs_detected_host() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    fs::write(
+        root.join(".cargo/config.toml"),
+        "[target.'cfg(windows)']\nrustflags = [\"--cfg\", \"given\"]\n",
+    )
+    .unwrap();
+    // The given values are applied to the host triple detected from cargo,
+    // without spawning rustc.
+    let options = test_options().rustc(PathAndArgs::new("no-such-rustc")).host_cfgs(["windows"]);
+    let config = Config::load_with_options(dir, options).unwrap();
+    let host = config.host_triple().unwrap().to_owned();
+    assert_eq!(config.rustflags(&*host).unwrap(), Some(["--cfg", "given"].into()));
+    assert_eq!(config.target_cfgs(&*host).unwrap(), ["windows"]);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn env_definition() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root.join("a/b");
+    fs::create_dir_all(root.join("a/.cargo")).unwrap();
+    fs::create_dir_all(dir).unwrap();
+    let root_config = root.join(".cargo/config.toml");
+    let nested_config = root.join("a/.cargo/config.toml");
+    fs::write(&root_config, "[env]\nA = \"root\"\nB = { value = \"root\" }\n").unwrap();
+    fs::write(&nested_config, "[env]\nB = { value = \"nested\", force = true }\n").unwrap();
+
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.env["A"].value, "root");
+    // Note: PartialEq for Definition only compares the kind of the definition.
+    assert!(
+        matches!(&config.env["A"].definition, Some(de::Definition::Path(p)) if *p == root_config)
+    );
+    assert_eq!(config.env["B"].value, "nested");
+    assert!(config.env["B"].force);
+    assert!(
+        matches!(&config.env["B"].definition, Some(de::Definition::Path(p)) if *p == nested_config)
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn walk() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root.join("a/b");
+    let home = &root.join("home/.cargo");
+    fs::create_dir_all(root.join("a/.cargo")).unwrap();
+    fs::create_dir_all(dir).unwrap();
+    fs::create_dir_all(home).unwrap();
+    fs::write(root.join(".cargo/config.toml"), "build.jobs = 1\n").unwrap();
+    fs::write(root.join("a/.cargo/config"), "build.jobs = 2\n").unwrap();
+    fs::write(home.join("config.toml"), "build.jobs = 3\n").unwrap();
+
+    let config = Config::load_with_options(dir, test_options().cargo_home(home.clone())).unwrap();
+    assert_eq!(config.build.jobs, Some(Jobs::Count(2)));
+    let paths: Vec<_> = config.walk().collect();
+    assert_eq!(paths, [
+        root.join("a/.cargo/config"),
+        root.join(".cargo/config.toml"),
+        home.join("config.toml"),
+    ]);
+    // The same files are loaded by de::Config::load_files.
+    let de = de::Config::load_files(&paths).unwrap();
+    assert_eq!(de.build.jobs.unwrap().val, Jobs::Count(2));
+
+    let config = Config::load_with_options(
+        dir,
+        test_options().cargo_home(home.clone()).include_cargo_home(false),
+    )
+    .unwrap();
+    assert_eq!(config.walk().collect::<Vec<_>>(), [
+        root.join("a/.cargo/config"),
+        root.join(".cargo/config.toml"),
+    ]);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn serialize_deserialize() {
+    let (_tmp, root) = test_project("reference");
+    let dir = &root;
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    let target = "x86_64-unknown-linux-gnu";
+    config.target(target).unwrap();
+
+    let s = toml::to_string(&config).unwrap();
+    let deserialized: Config = toml::from_str(&s).unwrap();
+    assert_eq!(deserialized, config);
+    assert_eq!(deserialized.target(target).unwrap(), config.target(target).unwrap());
+    assert_eq!(toml::to_string(&deserialized).unwrap(), s);
+
+    let s = serde_json::to_string(&config).unwrap();
+    let deserialized: Config = serde_json::from_str(&s).unwrap();
+    assert_eq!(deserialized, config);
+    // The [target] table is resolved again lazily.
+    assert_ne!(serde_json::to_string(&deserialized).unwrap(), s);
+    deserialized.target(target).unwrap();
+    assert_eq!(serde_json::to_string(&deserialized).unwrap(), s);
+
+    let deserialized: Config = toml::from_str("").unwrap();
+    assert_eq!(deserialized, Config::default());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn rustc_wrapper() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    let config_path = &root.join(".cargo/config.toml");
+    let load = |env: &[(&str, &str)]| {
+        Config::load_with_options(dir, test_options().env(env.iter().copied())).unwrap()
+    };
+
+    // Not set.
+    let config = load(&[]);
+    assert_eq!(config.rustc_wrapper(), None);
+    assert_eq!(config.rustc_workspace_wrapper(), None);
+
+    // Set by config.
+    fs::write(
+        config_path,
+        "[build]\nrustc-wrapper = \"sccache\"\nrustc-workspace-wrapper = \"clippy-driver\"\n",
+    )
+    .unwrap();
+    let config = load(&[]);
+    assert_eq!(config.rustc_wrapper(), Some(Path::new("sccache")));
+    assert_eq!(config.rustc_workspace_wrapper(), Some(Path::new("clippy-driver")));
+
+    // Set by environment variables.
+    for (wrapper, workspace_wrapper) in [
+        ("RUSTC_WRAPPER", "RUSTC_WORKSPACE_WRAPPER"),
+        ("CARGO_BUILD_RUSTC_WRAPPER", "CARGO_BUILD_RUSTC_WORKSPACE_WRAPPER"),
+    ] {
+        let config = load(&[(wrapper, "a"), (workspace_wrapper, "b")]);
+        assert_eq!(config.rustc_wrapper(), Some(Path::new("a")));
+        assert_eq!(config.rustc_workspace_wrapper(), Some(Path::new("b")));
+
+        // Empty string disables the wrapper set by config.
+        let config = load(&[(wrapper, ""), (workspace_wrapper, "")]);
+        assert_eq!(config.rustc_wrapper(), None);
+        assert_eq!(config.rustc_workspace_wrapper(), None);
+    }
+    // RUSTC_WRAPPER takes precedence over CARGO_BUILD_RUSTC_WRAPPER.
+    let config = load(&[("RUSTC_WRAPPER", ""), ("CARGO_BUILD_RUSTC_WRAPPER", "a")]);
+    assert_eq!(config.rustc_wrapper(), None);
+
+    // Empty string in config also disables the wrapper.
+    fs::write(config_path, "[build]\nrustc-wrapper = \"\"\nrustc-workspace-wrapper = \"\"\n")
+        .unwrap();
+    let config = load(&[]);
+    assert_eq!(config.rustc_wrapper(), None);
+    assert_eq!(config.rustc_workspace_wrapper(), None);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support pipe2 (inside std::process::Command::output)
+fn is_nightly_rustc() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+
+    let output = Command::new("rustc").arg("-vV").output().unwrap();
+    assert!(output.status.success());
+    let release = str::from_utf8(&output.stdout)
+        .unwrap()
+        .lines()
+        .find_map(|line| line.strip_prefix("release: "))
+        .unwrap();
+    let nightly = release.ends_with("-nightly") || release.ends_with("-dev");
+    assert_eq!(config.is_nightly_rustc().unwrap(), nightly, "{release}");
+}
+
+#[test]
+fn flags_eq_normalized() {
+    #[track_caller]
+    fn eq(a: &[&str], b: &[&str]) {
+        let (a, b) = (Flags::from(a), Flags::from(b));
+        assert!(a.eq_normalized(&b), "{a:?} {b:?}");
+        assert!(b.eq_normalized(&a), "{a:?} {b:?}");
+    }
+    #[track_caller]
+    fn ne(a: &[&str], b: &[&str]) {
+        let (a, b) = (Flags::from(a), Flags::from(b));
+        assert!(!a.eq_normalized(&b), "{a:?} {b:?}");
+        assert!(!b.eq_normalized(&a), "{a:?} {b:?}");
+    }
+
+    // Equal modulo order of independent flags.
+    eq(&["-C", "x", "-C", "y"], &["-C", "y", "-C", "x"]);
+    eq(&["-C", "opt-level=3", "-Z", "sanitizer=address"], &[
+        "-Zsanitizer=address",
+        "-Copt-level=3",
+    ]);
+    eq(&["--codegen", "a=1", "--codegen=b=2"], &["-Cb=2", "-C", "a=1"]);
+    eq(&["--cfg", "a", "--cfg=b", "-L", "native=/lib"], &[
+        "-Lnative=/lib",
+        "--cfg",
+        "b",
+        "--cfg",
+        "a",
+    ]);
+    eq(&["-D", "warnings", "--cfg", "a"], &["--cfg", "a", "--deny=warnings"]);
+    eq(&["-C", "link-arg=-a", "-C", "link-arg=-a"], &["-C", "link-arg=-a", "-C", "link-arg=-a"]);
+    eq(&["--verbose", "-C", "x"], &["-Cx", "--verbose"]);
+    eq(&[], &[]);
+
+    // Genuinely different.
+    ne(&["-C", "x"], &["-C", "y"]);
+    ne(&["-C", "opt-level=1", "-C", "opt-level=3"], &["-C", "opt-level=3", "-C", "opt-level=1"]);
+    ne(&["-C", "link-arg=-a", "-C", "link-arg=-b"], &["-C", "link-arg=-b", "-C", "link-arg=-a"]);
+    ne(&["-A", "warnings", "-D", "unused"], &["-D", "unused", "-A", "warnings"]);
+    ne(&["-L", "/a", "-L", "/b"], &["-L", "/b", "-L", "/a"]);
+    ne(&["-l", "a", "-l", "b"], &["-lb", "-la"]);
+    ne(&["-L", "/a", "-l", "a", "-L", "/b"], &["-L", "/b", "-l", "a", "-L", "/a"]);
+    ne(&["-C", "x", "-C", "x"], &["-C", "x"]);
+    ne(&["-C", "x"], &["-Z", "x"]);
+    ne(&["--cfg", "a"], &["--cfg"]);
+    ne(&["-C", "x"], &[]);
+
+    // Exact equality is unchanged.
+    assert_ne!(Flags::from(["-C", "x", "-C", "y"]), Flags::from(["-C", "y", "-C", "x"]));
+}
+
+#[test]
+fn flags_codegen_options() {
+    #[track_caller]
+    fn t(flags: &[&str], expected: &[(&str, Option<&str>)]) {
+        let flags = Flags::from(flags);
+        assert_eq!(flags.codegen_options().collect::<Vec<_>>(), expected, "{flags:?}");
+    }
+
+    t(&["-C", "opt-level=3"], &[("opt-level", Some("3"))]);
+    t(&["-Copt-level=3"], &[("opt-level", Some("3"))]);
+    t(&["--codegen", "opt-level=3"], &[("opt-level", Some("3"))]);
+    t(&["--codegen=opt-level=3"], &[("opt-level", Some("3"))]);
+    t(&["-C", "target-feature=+avx"], &[("target-feature", Some("+avx"))]);
+    t(&["-Ctarget-feature=+avx,-sse"], &[("target-feature", Some("+avx,-sse"))]);
+    t(&["-C", "link-arg=-Wl,--as-needed"], &[("link-arg", Some("-Wl,--as-needed"))]);
+    t(&["-C", "rpath"], &[("rpath", None)]);
+    t(&["--cfg", "a", "-Copt-level=3", "-Z", "x", "-C", "debuginfo=2", "-L", "/lib"], &[
+        ("opt-level", Some("3")),
+        ("debuginfo", Some("2")),
+    ]);
+    t(&["-C", "-C", "a"], &[("-C", None)]);
+    t(&["-Copt-level=3", "-C"], &[("opt-level", Some("3"))]);
+    t(&["--verbose", "-Z", "x"], &[]);
+    t(&[], &[]);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn no_config_no_env() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    let dir = tmpdir.path();
+    assert_eq!(Walk::with_cargo_home(dir, None).count(), 0);
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config, Config::default());
+    assert_eq!(toml::to_string(&config).unwrap(), "");
+
+    // Loading config without config files and environment variables skips
+    // loading and resolving config, so check that the result is the same as
+    // resolving an empty config.
+    let slow = Config::resolve(de::Config::default(), test_options(), dir).unwrap();
+    assert_eq!(config, slow);
+    assert_eq!(format!("{config:?}"), format!("{slow:?}"));
+    assert_eq!(config.current_dir(), dir);
+    assert_eq!(config.rustc(), slow.rustc());
+    assert_eq!(
+        config.rustflags("x86_64-unknown-linux-gnu").unwrap(),
+        slow.rustflags("x86_64-unknown-linux-gnu").unwrap()
+    );
+    let options = ResolveOptions::default().env::<_, String, String>([]).cargo_home(None);
+    let slow = Config::resolve(de::Config::default(), options, ".").unwrap();
+    assert_eq!(format!("{:?}", Config::default()), format!("{slow:?}"));
+
+    // Environment variables are still applied.
+    let config =
+        Config::load_with_options(dir, test_options().env([("CARGO_BUILD_JOBS", "2")])).unwrap();
+    assert_eq!(config.build.jobs, Some(Jobs::Count(2)));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn rustflags_source() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    let config_path = &root.join(".cargo/config.toml");
+    let target = "x86_64-unknown-linux-gnu";
+    let source = |env: &[(&str, &str)]| {
+        let config =
+            Config::load_with_options(dir, test_options().env(env.iter().copied())).unwrap();
+        config.rustflags_source(target).unwrap()
+    };
+
+    assert_eq!(source(&[]), None);
+
+    fs::write(config_path, "build.rustflags = [\"--cfg\", \"build\"]\n").unwrap();
+    assert_eq!(source(&[]), Some(RustflagsSource::BuildConfig));
+    assert_eq!(
+        source(&[("CARGO_BUILD_RUSTFLAGS", "--cfg env")]),
+        Some(RustflagsSource::BuildConfig)
+    );
+
+    fs::write(
+        config_path,
+        "build.rustflags = [\"--cfg\", \"build\"]\n\
+         [target.'cfg(target_os = \"linux\")']\nrustflags = [\"--cfg\", \"linux\"]\n\
+         [target.'cfg(windows)']\nrustflags = [\"--cfg\", \"windows\"]\n",
+    )
+    .unwrap();
+    assert_eq!(
+        source(&[]),
+        Some(RustflagsSource::TargetCfg("cfg(target_os = \"linux\")".to_owned()))
+    );
+    let env = ("CARGO_TARGET_X86_64_UNKNOWN_LINUX_GNU_RUSTFLAGS", "--cfg target_env");
+    assert_eq!(source(&[env]), Some(RustflagsSource::TargetEnv));
+
+    fs::write(
+        config_path,
+        "[target.x86_64-unknown-linux-gnu]\nrustflags = [\"--cfg\", \"triple\"]\n",
+    )
+    .unwrap();
+    assert_eq!(source(&[]), Some(RustflagsSource::TargetTriple));
+    assert_eq!(source(&[env]), Some(RustflagsSource::TargetTriple));
+
+    assert_eq!(source(&[("RUSTFLAGS", "--cfg env")]), Some(RustflagsSource::Env));
+    assert_eq!(source(&[("RUSTFLAGS", "")]), Some(RustflagsSource::Env));
+    assert_eq!(
+        source(&[("RUSTFLAGS", "--cfg env"), ("CARGO_ENCODED_RUSTFLAGS", "--cfg\x1fencoded")]),
+        Some(RustflagsSource::EncodedEnv)
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn build_rustflags() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    fs::write(
+        root.join(".cargo/config.toml"),
+        "build.rustflags = [\"--cfg\", \"build\"]\n\
+         [target.x86_64-unknown-linux-gnu]\nrustflags = [\"--cfg\", \"triple\"]\n",
+    )
+    .unwrap();
+    let triple = "x86_64-unknown-linux-gnu";
+    let load = |env: &[(&str, &str)]| {
+        Config::load_with_options(dir, test_options().env(env.iter().copied())).unwrap()
+    };
+
+    let config = load(&[]);
+    assert_eq!(config.build_rustflags(), Some(&["--cfg", "build"].into()));
+    assert_eq!(config.rustflags(triple).unwrap(), Some(["--cfg", "triple"].into()));
+
+    let config = load(&[("CARGO_BUILD_RUSTFLAGS", "--cfg env")]);
+    assert_eq!(config.build_rustflags(), Some(&["--cfg", "env"].into()));
+    assert_eq!(config.rustflags(triple).unwrap(), Some(["--cfg", "triple"].into()));
+
+    let config = load(&[("CARGO_TARGET_X86_64_UNKNOWN_LINUX_GNU_RUSTFLAGS", "--cfg target_env")]);
+    assert_eq!(config.build_rustflags(), Some(&["--cfg", "build"].into()));
+
+    let config = load(&[("RUSTFLAGS", "--cfg rustflags")]);
+    assert_eq!(config.build_rustflags(), Some(&["--cfg", "rustflags"].into()));
+    assert_eq!(config.rustflags(triple).unwrap(), Some(["--cfg", "rustflags"].into()));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn combine_rustflags() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    fs::write(
+        root.join(".cargo/config.toml"),
+        "build.rustflags = [\"--cfg\", \"build\"]\n\
+         [target.x86_64-unknown-linux-gnu]\nrustflags = [\"--cfg\", \"triple\"]\n",
+    )
+    .unwrap();
+    let triple = "x86_64-unknown-linux-gnu";
+    let other = "aarch64-unknown-linux-gnu";
+
+    // Cargo-faithful by default: target-specific rustflags replace build.rustflags.
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.rustflags(triple).unwrap(), Some(["--cfg", "triple"].into()));
+    assert_eq!(config.rustflags(other).unwrap(), Some(["--cfg", "build"].into()));
+
+    let config = Config::load_with_options(dir, test_options().combine_rustflags(true)).unwrap();
+    assert_eq!(
+        config.rustflags(triple).unwrap(),
+        Some(["--cfg", "triple", "--cfg", "build"].into())
+    );
+    assert_eq!(config.rustflags(other).unwrap(), Some(["--cfg", "build"].into()));
+
+    // RUSTFLAGS still overrides all other rustflags.
+    let config = Config::load_with_options(
+        dir,
+        test_options().combine_rustflags(true).env([("RUSTFLAGS", "--cfg env")]),
+    )
+    .unwrap();
+    assert_eq!(config.rustflags(triple).unwrap(), Some(["--cfg", "env"].into()));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn to_env_vars() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    fs::write(
+        root.join(".cargo/config.toml"),
+        "alias.b = \"build --release\"\n\
+         [build]\njobs = 4\nrustc-wrapper = \"/usr/bin/sccache\"\ntarget = \"aarch64-unknown-linux-gnu\"\n\
+         target-dir = \"/tmp/target\"\nrustflags = [\"--cfg\", \"build\"]\nincremental = false\n\
+         [doc]\nbrowser = [\"firefox\", \"--new-tab\"]\n\
+         [cargo-new]\nvcs = \"none\"\n\
+         [http]\ntimeout = 30\nmultiplexing = false\n\
+         [net]\nretry = 5\noffline = true\n\
+         [registry]\ndefault = \"my-registry\"\n\
+         [term]\nverbose = true\ncolor = \"never\"\nprogress.when = \"never\"\n\
+         [target.aarch64-unknown-linux-gnu]\nlinker = \"/usr/bin/aarch64-linux-gnu-gcc\"\n\
+         runner = [\"qemu-aarch64\", \"-L\", \"/usr/aarch64-linux-gnu\"]\n\
+         rustflags = [\"--cfg\", \"triple\"]\n",
+    )
+    .unwrap();
+    let triple = "aarch64-unknown-linux-gnu";
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    let vars = config.to_env_vars().unwrap();
+    let var = |k: &str| vars.iter().find(|(key, _)| key == k).map(|(_, v)| &**v);
+    assert_eq!(var("CARGO_ALIAS_b"), Some("build --release"));
+    assert_eq!(var("CARGO_BUILD_RUSTFLAGS"), Some("--cfg build"));
+    assert_eq!(var("CARGO_TARGET_AARCH64_UNKNOWN_LINUX_GNU_RUSTFLAGS"), Some("--cfg triple"));
+    assert_eq!(
+        var("CARGO_TARGET_AARCH64_UNKNOWN_LINUX_GNU_RUNNER"),
+        Some("qemu-aarch64 -L /usr/aarch64-linux-gnu")
+    );
+
+    // Feeding the variables back reproduces the config.
+    let (_tmp2, root2) = test_project("empty");
+    let env_config =
+        Config::load_with_options(&root2, test_options().env(vars.iter().cloned())).unwrap();
+    assert_eq!(env_config, config);
+    assert_eq!(env_config.target(triple).unwrap(), config.target(triple).unwrap());
+    assert_eq!(env_config.to_env_vars().unwrap(), vars);
+
+    // Registry names use the form cargo reads from the environment.
+    fs::write(
+        root.join(".cargo/config.toml"),
+        "registries.crates-io.protocol = \"sparse\"\n\
+         registries.my-registry.index = \"https://example.com/index\"\n\
+         registries.\"a.b\".index = \"https://example.com/a.b\"\n",
+    )
+    .unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    let vars = config.to_env_vars().unwrap();
+    assert_eq!(vars, vec![
+        ("CARGO_REGISTRIES_A.B_INDEX".to_owned(), "https://example.com/a.b".to_owned()),
+        ("CARGO_REGISTRIES_CRATES_IO_PROTOCOL".to_owned(), "sparse".to_owned()),
+        ("CARGO_REGISTRIES_MY_REGISTRY_INDEX".to_owned(), "https://example.com/index".to_owned()),
+    ]);
+    // Registries that are not defined in config can be restored from the environment.
+    fs::write(root.join(".cargo/config.toml"), "").unwrap();
+    let env_config = Config::load_with_options(dir, test_options().env(vars)).unwrap();
+    assert_eq!(env_config.registries, config.registries);
+
+    // Multiple targets cannot be represented.
+    fs::write(
+        root.join(".cargo/config.toml"),
+        "build.target = [\"aarch64-unknown-linux-gnu\", \"x86_64-unknown-linux-gnu\"]\n",
+    )
+    .unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert!(config.to_env_vars().is_err());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn custom_target_env() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    let spec_path = fixtures_path().join("target-specs/avr-unknown-gnu-atmega2560.json");
+    let cli_target = spec_path.to_str().unwrap();
+
+    // Target-specific environment variables are keyed on the file stem of the spec path.
+    let env = [
+        ("CARGO_TARGET_AVR_UNKNOWN_GNU_ATMEGA2560_LINKER", "avr-gcc"),
+        ("CARGO_TARGET_AVR_UNKNOWN_GNU_ATMEGA2560_RUNNER", "simavr -m atmega2560"),
+        ("CARGO_TARGET_AVR_UNKNOWN_GNU_ATMEGA2560_RUSTFLAGS", "-C opt-level=s"),
+    ];
+    let config = Config::load_with_options(dir, test_options().env(env)).unwrap();
+    assert_eq!(config.linker(cli_target).unwrap().unwrap().as_os_str(), "avr-gcc");
+    let runner = config.runner(cli_target).unwrap().unwrap();
+    assert_eq!(runner.path.as_os_str(), "simavr");
+    assert_eq!(runner.args, ["-m", "atmega2560"]);
+    assert_eq!(config.rustflags(cli_target).unwrap(), Some(["-C", "opt-level=s"].into()));
+    assert_eq!(
+        config.target_runner_env(cli_target).unwrap(),
+        Some((env[1].0.to_owned(), env[1].1.to_owned()))
+    );
+
+    // So is the `[target.<triple>]` table.
+    fs::write(
+        root.join(".cargo/config.toml"),
+        "target.avr-unknown-gnu-atmega2560.linker = \"avr-gcc-config\"\n",
+    )
+    .unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.linker(cli_target).unwrap().unwrap().as_os_str(), "avr-gcc-config");
+    let config = Config::load_with_options(dir, test_options().env(env)).unwrap();
+    assert_eq!(config.linker(cli_target).unwrap().unwrap().as_os_str(), "avr-gcc");
+}
+
+#[cfg(unix)]
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn canonicalize_cwd() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    // Canonicalize the temporary directory itself, which may be under a symlink (e.g., /tmp on macOS).
+    let tmp = &fs::canonicalize(tmpdir.path()).unwrap();
+    let workspace = &tmp.join("workspace");
+    let project = &workspace.join("project");
+    fs::create_dir_all(project).unwrap();
+    fs::create_dir_all(workspace.join(".cargo")).unwrap();
+    fs::write(workspace.join(".cargo/config.toml"), "build.jobs = 1\n").unwrap();
+    let link = &tmp.join("link");
+    std::os::unix::fs::symlink(project, link).unwrap();
+
+    // The ancestors of the symlink do not contain the workspace config.
+    let config = Config::load_with_options(link, test_options()).unwrap();
+    assert_eq!(config.build.jobs, None);
+    assert_eq!(config.current_dir(), link);
+
+    let config = Config::load_with_options(link, test_options().canonicalize_cwd(true)).unwrap();
+    assert_eq!(config.build.jobs, Some(Jobs::Count(1)));
+    assert_eq!(config.current_dir(), project);
+
+    let e =
+        Config::load_with_options(tmp.join("non-existent"), test_options().canonicalize_cwd(true))
+            .unwrap_err();
+    assert!(e.to_string().starts_with("failed to canonicalize current directory"), "{e}");
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn with_cli_config() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    let path = &root.join(".cargo/config.toml");
+    fs::write(path, "build.jobs = 1\nbuild.rustflags = [\"--cfg\", \"a\"]\nnet.offline = true\n")
+        .unwrap();
+    let config = de::Config::load_with_cwd(dir).unwrap();
+
+    let config = config.with_cli_config(["build.jobs=2"]).unwrap();
+    assert_eq!(config.build.jobs.as_ref().unwrap().val, Jobs::Count(2));
+    assert_eq!(config.get_definition("build.jobs"), Some(&de::Definition::Cli(None)));
+    assert_eq!(config.get_bool("net.offline").unwrap(), Some(true));
+    assert_eq!(config.get_definition("net.offline"), Some(&de::Definition::Path(path.clone())));
+
+    // Later arguments take precedence, and arrays are joined.
+    let config = config
+        .with_cli_config([
+            "build.jobs = 3",
+            "build.jobs = 4",
+            "build.rustflags = [\"--cfg\", \"b\"]",
+        ])
+        .unwrap();
+    assert_eq!(config.get_integer("build.jobs").unwrap(), Some(4));
+    assert_eq!(config.get_list("build.rustflags").unwrap(), Some(vec!["--cfg", "a", "--cfg", "b"]));
+
+    // Paths to config files.
+    let cli_path = &root.join("cli.toml");
+    fs::write(cli_path, "net.offline = false\n").unwrap();
+    let config = config.with_cli_config([cli_path.to_str().unwrap()]).unwrap();
+    assert_eq!(config.get_bool("net.offline").unwrap(), Some(false));
+    assert_eq!(
+        config.get_definition("net.offline"),
+        Some(&de::Definition::Cli(Some(cli_path.clone())))
+    );
+
+    let resolved = Config::resolve(config.clone(), test_options(), dir).unwrap();
+    assert_eq!(resolved.build.jobs, Some(Jobs::Count(4)));
+    assert_eq!(resolved.net.offline, Some(false));
+
+    // --config arguments take precedence over the environment variables
+    // corresponding to config keys, like cargo.
+    let env = [
+        ("CARGO_BUILD_JOBS", "8"),
+        ("CARGO_BUILD_RUSTFLAGS", "--cfg env"),
+        ("CARGO_NET_OFFLINE", "true"),
+        ("CARGO_NET_RETRY", "5"),
+        ("RUSTFLAGS", "--cfg rustflags"),
+    ];
+    let resolved = Config::resolve(config.clone(), test_options().env(env), dir).unwrap();
+    assert_eq!(resolved.build.jobs, Some(Jobs::Count(4)));
+    assert_eq!(resolved.net.offline, Some(false));
+    // Environment variables still override values that are not from --config.
+    assert_eq!(resolved.net.retry, Some(5));
+    // RUSTFLAGS is not a config key, so it takes precedence over --config.
+    assert_eq!(resolved.build.rustflags, Some(["--cfg", "rustflags"].into()));
+    let env = [("CARGO_BUILD_JOBS", "8"), ("CARGO_BUILD_RUSTFLAGS", "--cfg env")];
+    let resolved = Config::resolve(config, test_options().env(env), dir).unwrap();
+    assert_eq!(resolved.build.rustflags, Some(["--cfg", "a", "--cfg", "b"].into()));
+
+    for (arg, msg) in [
+        ("[build]\njobs = 1", "was not a TOML dotted key expression"),
+        ("build.jobs = 1\nnet.offline = true", "was not a TOML dotted key expression"),
+        ("build = { jobs = 1 }", "sets a value to an inline table"),
+        ("build.jobs =", "failed to parse value from --config argument"),
+    ] {
+        let e = de::Config::default().with_cli_config([arg]).unwrap_err();
+        assert!(e.to_string().contains(msg), "{arg}: {e}");
+    }
+    let e = de::Config::default().with_cli_config(["build.jobs = true"]).unwrap_err();
+    assert_eq!(e.to_string(), "failed to parse --config argument `build.jobs = true`");
+}
+
+#[cfg(feature = "toml_edit")]
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn get_table() {
+    let (_tmp, root) = test_project("empty");
+    let a = &root.join("a.toml");
+    let b = &root.join("b.toml");
+    fs::write(
+        a,
+        "build.jobs = 1\n[profile.release]\nlto = true\nopt-level = 3\n[custom]\nlist = [\"a\"]\n",
+    )
+    .unwrap();
+    fs::write(b, "[profile.release]\nlto = false\ndebug = 1\n[custom]\nlist = [\"b\"]\nkey = 1\n")
+        .unwrap();
+
+    let config = de::Config::load_files([a, b]).unwrap();
+    let release = config.get_table("profile.release").unwrap().unwrap();
+    assert_eq!(release.get("lto").unwrap().as_bool(), Some(true));
+    assert_eq!(release.get("opt-level").unwrap().as_integer(), Some(3));
+    assert_eq!(release.get("debug").unwrap().as_integer(), Some(1));
+
+    let config =
+        config.with_cli_config(["profile.release.opt-level = 1", "custom.list = [\"c\"]"]).unwrap();
+    let release = config.get_table("profile.release").unwrap().unwrap();
+    assert_eq!(release.get("lto").unwrap().as_bool(), Some(true));
+    assert_eq!(release.get("opt-level").unwrap().as_integer(), Some(1));
+    let custom = config.get_table("custom").unwrap().unwrap();
+    assert_eq!(custom.get("key").unwrap().as_integer(), Some(1));
+    let list: Vec<_> = custom
+        .get("list")
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|v| v.as_str().unwrap())
+        .collect();
+    assert_eq!(list, ["b", "a", "c"]);
+    // Tables modeled by this crate are also available.
+    let build = config.get_table("build").unwrap().unwrap();
+    assert_eq!(build.get("jobs").unwrap().as_integer(), Some(1));
+
+    assert!(config.get_table("profile.dev").unwrap().is_none());
+    assert!(config.get_table("unknown.table").unwrap().is_none());
+    assert_eq!(
+        config.get_table("build.jobs").err().unwrap().to_string(),
+        "invalid type for `build.jobs`: expected a table, but found integer"
+    );
+
+    let resolved = Config::resolve(config, test_options(), &root).unwrap();
+    let release = resolved.get_table("profile.release").unwrap().unwrap();
+    assert_eq!(release.get("opt-level").unwrap().as_integer(), Some(1));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn build_dir() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+    fs::write(
+        root.join(".cargo/config.toml"),
+        r#"
+            [build]
+            target-dir = "target"
+            build-dir = "build"
+            "#,
+    )
+    .unwrap();
+
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.build.target_dir.as_deref(), Some(&*dir.join("target")));
+    assert_eq!(config.build.build_dir.as_deref(), Some(&*dir.join("build")));
+    assert_eq!(config.build_dir(), Some(&*dir.join("build")));
+
+    let config = Config::load_with_options(
+        dir,
+        test_options().env([("CARGO_BUILD_BUILD_DIR", "env-build")]),
+    )
+    .unwrap();
+    assert_eq!(config.build_dir(), Some(&*dir.join("env-build")));
+
+    // Template variables are not expanded, and a path starting with a template
+    // variable is not resolved relative to the config directory.
+    fs::write(root.join(".cargo/config.toml"), "build.build-dir = \"{workspace-root}/build\"\n")
+        .unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.build_dir(), Some(Path::new("{workspace-root}/build")));
+    assert_eq!(config.to_env_vars().unwrap(), vec![(
+        "CARGO_BUILD_BUILD_DIR".to_owned(),
+        "{workspace-root}/build".to_owned()
+    )]);
+    fs::write(
+        root.join(".cargo/config.toml"),
+        "build.build-dir = \"build/{workspace-path-hash}\"\n",
+    )
+    .unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.build_dir(), Some(&*dir.join("build/{workspace-path-hash}")));
+
+    // build.build-dir defaults to build.target-dir
+    fs::write(root.join(".cargo/config.toml"), "build.target-dir = \"target\"\n").unwrap();
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.build.build_dir, None);
+    assert_eq!(config.build_dir(), Some(&*dir.join("target")));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // Miri doesn't support file with non-default mode: https://github.com/rust-lang/miri/pull/2720
+fn build_warnings() {
+    let (_tmp, root) = test_project("empty");
+    let dir = &root;
+
+    let config = Config::load_with_options(dir, test_options()).unwrap();
+    assert_eq!(config.build.warnings, None);
+    assert_eq!(config.build_warnings(), Warnings::Warn);
+
+    for warnings in [Warnings::Warn, Warnings::Allow, Warnings::Deny] {
+        fs::write(
+            root.join(".cargo/config.toml"),
+            format!("build.warnings = \"{}\"\n", warnings.as_str()),
+        )
+        .unwrap();
+        let config = Config::load_with_options(dir, test_options()).unwrap();
+        assert_eq!(config.build.warnings, Some(warnings));
+        assert_eq!(config.build_warnings(), warnings);
+
+        fs::write(root.join(".cargo/config.toml"), "").unwrap();
+        let config = Config::load_with_options(
+            dir,
+            test_options().env([("CARGO_BUILD_WARNINGS", warnings.as_str())]),
+        )
+        .unwrap();
+        assert_eq!(config.build_warnings(), warnings);
+    }
+
+    // env overrides config
+    fs::write(root.join(".cargo/config.toml"), "build.warnings = \"deny\"\n").unwrap();
+    let config =
+        Config::load_with_options(dir, test_options().env([("CARGO_BUILD_WARNINGS", "allow")]))
+            .unwrap();
+    assert_eq!(config.build_warnings(), Warnings::Allow);
+
+    let config =
+        Config::load_with_options(dir, test_options().env([("CARGO_BUILD_WARNINGS", "error")]));
+    assert!(config.is_err());
+}
+
 #[rustversion::attr(not(nightly), ignore)]
 #[test]
 #[cfg_attr(miri, ignore)] // Miri doesn't support pipe2 (inside std::process::Command::output)
@@ -26,16 +26,17 @@ fn gen_de() {
     const FILES: &[&str] = &["src/de.rs"];
     const MERGE_EXCLUDE: &[&str] =
         &["de::Flags", "de::EnvConfigValue", "de::StringList", "de::PathAndArgs"];
-    const SET_PATH_EXCLUDE: &[&str] = &[];
+    const SET_DEFINITION_EXCLUDE: &[&str] = &[];
+    const GET_VALUE_EXCLUDE: &[&str] = &["de::Flags", "de::StringList", "de::PathAndArgs"];
 
     let workspace_root = &workspace_root();
 
     let mut tokens = quote! {
-        use std::path::Path;
         use crate::{
             error::Result,
+            get::{GetValue, ValueRef},
             merge::Merge,
-            value::SetPath,
+            value::{Definition, SetDefinition},
         };
     };
 
@@ -69,7 +70,12 @@ fn gen_de() {
                                     && f.ident.as_ref().unwrap() != "deserialized_repr"
                             })
                             .map(|syn::Field { ident, .. }| {
-                                quote! { self.#ident.merge(low.#ident, force)?; }
+                                let key = ident.as_ref().unwrap().to_string().replace('_', "-");
+                                quote! {
+                                    self.#ident
+                                        .merge(low.#ident, force)
+                                        .map_err(|e| e.merge_key(#key))?;
+                                }
                             });
                         tokens.extend(quote! {
                             impl Merge for crate:: #(#module::)* #ident {
@@ -83,14 +89,65 @@ fn gen_de() {
                 }
                 _ => {}
             }
-            // impl SetPath
+            // impl GetValue
+            match item {
+                syn::Item::Struct(syn::ItemStruct { vis, ident, fields, .. })
+                    if matches!(vis, syn::Visibility::Public(..))
+                        && matches!(fields, syn::Fields::Named(..)) =>
+                {
+                    let path_string = quote! { #(#module::)* #ident }.to_string().replace(' ', "");
+                    visited_types.insert(path_string.clone());
+                    if !GET_VALUE_EXCLUDE.contains(&path_string.as_str()) {
+                        let fields: Vec<_> = fields
+                            .iter()
+                            .filter(|f| {
+                                !serde_skip(&f.attrs)
+                                    && f.ident.as_ref().unwrap() != "serialized_repr"
+                                    && f.ident.as_ref().unwrap() != "deserialized_repr"
+                            })
+                            .collect();
+                        let arms = fields.iter().filter(|f| !serde_flatten(&f.attrs)).map(
+                            |syn::Field { ident, .. }| {
+                                let key = ident.as_ref().unwrap().to_string().replace('_', "-");
+                                quote! { #key => self.#ident.get_value(rest), }
+                            },
+                        );
+                        // Keys not matched by other fields are looked up in the flattened field.
+                        let flatten: Vec<_> = fields
+                            .iter()
+                            .filter(|f| serde_flatten(&f.attrs))
+                            .map(|syn::Field { ident, .. }| ident)
+                            .collect();
+                        assert!(flatten.len() <= 1, "multiple flattened fields in `{path_string}`");
+                        let fallback = match flatten.first() {
+                            Some(ident) => quote! { _ => self.#ident.get_value(key), },
+                            None => quote! { _ => None, },
+                        };
+                        tokens.extend(quote! {
+                            impl GetValue for crate:: #(#module::)* #ident {
+                                fn get_value(&self, key: &[&str]) -> Option<ValueRef<'_>> {
+                                    let Some((first, rest)) = key.split_first() else {
+                                        return Some(ValueRef::Table);
+                                    };
+                                    match *first {
+                                        #(#arms)*
+                                        #fallback
+                                    }
+                                }
+                            }
+                        });
+                    }
+                }
+                _ => {}
+            }
+            // impl SetDefinition
             match item {
                 syn::Item::Struct(syn::ItemStruct { vis, ident, fields, .. })
                     if matches!(vis, syn::Visibility::Public(..)) =>
                 {
                     let path_string = quote! { #(#module::)* #ident }.to_string().replace(' ', "");
                     visited_types.insert(path_string.clone());
-                    if !SET_PATH_EXCLUDE.contains(&path_string.as_str()) {
+                    if !SET_DEFINITION_EXCLUDE.contains(&path_string.as_str()) {
                         match fields {
                             Fields::Named(fields) => {
                                 let fields = fields
@@ -102,11 +159,11 @@ fn gen_de() {
                                             && f.ident.as_ref().unwrap() != "deserialized_repr"
                                     })
                                     .map(|syn::Field { ident, .. }| {
-                                        quote! { self.#ident.set_path(path); }
+                                        quote! { self.#ident.set_definition(def); }
                                     });
                                 tokens.extend(quote! {
-                                    impl SetPath for crate:: #(#module::)* #ident {
-                                        fn set_path(&mut self, path: &Path) {
+                                    impl SetDefinition for crate:: #(#module::)* #ident {
+                                        fn set_definition(&mut self, def: &Definition) {
                                             #(#fields)*
                                         }
                                     }
@@ -115,9 +172,9 @@ fn gen_de() {
                             Fields::Unnamed(fields) => {
                                 assert_eq!(fields.unnamed.len(), 1);
                                 tokens.extend(quote! {
-                                    impl SetPath for crate:: #(#module::)* #ident {
-                                        fn set_path(&mut self, path: &Path) {
-                                            self.0.set_path(path);
+                                    impl SetDefinition for crate:: #(#module::)* #ident {
+                                        fn set_definition(&mut self, def: &Definition) {
+                                            self.0.set_definition(def);
                                         }
                                     }
                                 });
@@ -132,7 +189,7 @@ fn gen_de() {
                 {
                     let path_string = quote! { #(#module::)* #ident }.to_string().replace(' ', "");
                     visited_types.insert(path_string.clone());
-                    if !SET_PATH_EXCLUDE.contains(&path_string.as_str()) {
+                    if !SET_DEFINITION_EXCLUDE.contains(&path_string.as_str()) {
                         let mut arms = Vec::with_capacity(variants.len());
                         for syn::Variant { ident, fields, .. } in variants {
                             match fields {
@@ -145,7 +202,7 @@ fn gen_de() {
                                     let calls =
                                         fields.named.iter().filter(|f| !serde_skip(&f.attrs)).map(
                                             |syn::Field { ident, .. }| {
-                                                quote! { #ident.set_path(path); }
+                                                quote! { #ident.set_definition(def); }
                                             },
                                         );
                                     arms.push(quote! {
@@ -158,7 +215,7 @@ fn gen_de() {
                                     assert_eq!(fields.unnamed.len(), 1);
                                     arms.push(quote! {
                                         Self::#ident(v) => {
-                                            v.set_path(path);
+                                            v.set_definition(def);
                                         }
                                     });
                                 }
@@ -166,8 +223,8 @@ fn gen_de() {
                             }
                         }
                         tokens.extend(quote! {
-                            impl SetPath for crate:: #(#module::)* #ident {
-                                fn set_path(&mut self, path: &Path) {
+                            impl SetDefinition for crate:: #(#module::)* #ident {
+                                fn set_definition(&mut self, def: &Definition) {
                                     match self {
                                         #(#arms,)*
                                     }
@@ -188,10 +245,16 @@ fn gen_de() {
             "unknown type `{t}` specified in MERGE_EXCLUDE constant"
         );
     }
-    for &t in SET_PATH_EXCLUDE {
+    for &t in GET_VALUE_EXCLUDE {
+        assert!(
+            visited_types.contains(t),
+            "unknown type `{t}` specified in GET_VALUE_EXCLUDE constant"
+        );
+    }
+    for &t in SET_DEFINITION_EXCLUDE {
         assert!(
             visited_types.contains(t),
-            "unknown type `{t}` specified in SET_PATH_EXCLUDE constant"
+            "unknown type `{t}` specified in SET_DEFINITION_EXCLUDE constant"
         );
     }
 
@@ -203,6 +266,7 @@ fn gen_is_none() {
     const EXCLUDE: &[&str] = &[
         "de::Config",
         "de::Flags",
+        "de::LinkOverride",
         "de::PathAndArgs",
         "de::StringList",
         "de::TargetConfig",
@@ -210,6 +274,7 @@ fn gen_is_none() {
         "easy::Config",
         "easy::EnvConfigValue",
         "easy::Flags",
+        "easy::LinkOverride",
         "easy::PathAndArgs",
         "easy::StringList",
         "easy::TargetConfig",
@@ -241,13 +306,19 @@ fn gen_is_none() {
                 visited_types.insert(path_string.clone());
                 if !EXCLUDE.contains(&path_string.as_str()) {
                     let fields = fields.iter().filter(|f| !serde_skip(&f.attrs)).map(
-                        |syn::Field { ident, .. }| {
-                            quote! { self.#ident.is_none() }
+                        |syn::Field { ident, ty, .. }| {
+                            if is_option(ty) {
+                                quote! { self.#ident.is_none() }
+                            } else {
+                                quote! { self.#ident.is_empty() }
+                            }
                         },
                     );
                     tokens.extend(quote! {
                         impl crate:: #(#module::)* #ident {
-                            pub(crate) fn is_none(&self) -> bool {
+                            /// Returns `true` if no values are set in this table.
+                            #[must_use]
+                            pub fn is_empty(&self) -> bool {
                                 #(#fields) &&*
                             }
                         }
@@ -266,7 +337,24 @@ fn gen_is_none() {
     write(function_name!(), workspace_root.join("src/gen/is_none.rs"), tokens).unwrap();
 }
 
+fn is_option(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => {
+            path.segments.last().is_some_and(|s| s.ident == "Option")
+        }
+        _ => false,
+    }
+}
+
 fn serde_skip(attrs: &[syn::Attribute]) -> bool {
+    serde_path_attr(attrs, "skip")
+}
+
+fn serde_flatten(attrs: &[syn::Attribute]) -> bool {
+    serde_path_attr(attrs, "flatten")
+}
+
+fn serde_path_attr(attrs: &[syn::Attribute], name: &str) -> bool {
     for meta in attrs
         .iter()
         .filter(|attr| attr.path().is_ident("serde"))
@@ -276,7 +364,7 @@ fn serde_skip(attrs: &[syn::Attribute]) -> bool {
         .flatten()
     {
         if let syn::Meta::Path(p) = meta {
-            if p.is_ident("skip") {
+            if p.is_ident(name) {
                 return true;
             }
         }
@@ -286,11 +374,10 @@ fn serde_skip(attrs: &[syn::Attribute]) -> bool {
 
 fn gen_assert_impl() {
     const NOT_SEND: &[&str] = &[];
-    const NOT_SYNC: &[&str] = &["easy::Config", "resolve::ResolveContext"];
+    const NOT_SYNC: &[&str] = &[];
     const NOT_UNPIN: &[&str] = &[];
     const NOT_UNWIND_SAFE: &[&str] = &["error::Error"];
-    const NOT_REF_UNWIND_SAFE: &[&str] =
-        &["error::Error", "easy::Config", "resolve::ResolveContext"];
+    const NOT_REF_UNWIND_SAFE: &[&str] = &["error::Error"];
 
     let workspace_root = &workspace_root();
     let out_dir = &workspace_root.join("src/gen");